@@ -1,13 +1,17 @@
 // Imports
 use libc::*;
 use ssb_renderer::{
-    ssb_parser::{Ssb, SsbRender},
+    ssb_parser::{
+        resolvers::{ResourceResolver, DirectoryResolver},
+        Ssb,
+        SsbRender,
+        RenderOptions
+    },
     image::{ColorType, ImageView},
     RenderTrigger,
     SsbRenderer
 };
 use std::{
-    convert::TryFrom,
     error::Error,
     ffi::CStr,
     fs::File,
@@ -59,14 +63,15 @@ pub extern fn ssb_new_renderer_by_file(file: *const c_char, error_message: *mut
     }
 }
 fn ssb_new_renderer_by_file_inner(file: *const c_char) -> Result<SsbRenderer, Box::<dyn Error>> {
+    let file = unsafe{ CStr::from_ptr(file) }.to_str()?;
     ssb_new_renderer_inner(BufReader::new(
-        File::open(unsafe{ CStr::from_ptr(file) }.to_str()?)?
-    ))
+        File::open(file)?
+    ), &DirectoryResolver::for_script(file))
 }
-fn ssb_new_renderer_inner<R: BufRead>(script: R) -> Result<SsbRenderer, Box::<dyn Error>> {
+fn ssb_new_renderer_inner<R: BufRead>(script: R, resolver: &dyn ResourceResolver) -> Result<SsbRenderer, Box::<dyn Error>> {
     Ok(SsbRenderer::new(
         Ssb::default().parse_owned(script)
-        .and_then(SsbRender::try_from)?
+        .and_then(|ssb| SsbRender::from_with_options(ssb, &RenderOptions::new().resolver(resolver)).map(|(ssb_render, _)| ssb_render ) )?
     ))
 }
 
@@ -89,7 +94,8 @@ pub extern fn ssb_new_renderer_by_script(script: *const c_char, error_message: *
 }
 fn ssb_new_renderer_by_script_inner(script: *const c_char) -> Result<SsbRenderer, Box::<dyn Error>> {
     ssb_new_renderer_inner(
-        Cursor::new(unsafe{ CStr::from_ptr(script) }.to_str()?)
+        Cursor::new(unsafe{ CStr::from_ptr(script) }.to_str()?),
+        &DirectoryResolver::default()
     )
 }
 
@@ -11,7 +11,13 @@ use vapoursynth::{
 };
 use failure::{Error, err_msg, format_err, bail};
 use ssb_renderer::{
-    ssb_parser::{Ssb,SsbRender},
+    ssb_parser::{
+        resolvers::{ResourceResolver,DirectoryResolver},
+        objects::ssb_objects::Framerate as SsbFramerate,
+        Ssb,
+        SsbRender,
+        RenderOptions
+    },
     image::{ColorType,ImageView},
     RenderTrigger,
     SsbRenderer
@@ -19,7 +25,6 @@ use ssb_renderer::{
 use std::{
     io::{BufRead,BufReader,Cursor},
     fs::File,
-    convert::TryFrom,
     sync::Mutex,
     cell::RefCell,
     slice::from_raw_parts_mut
@@ -56,12 +61,11 @@ make_filter_function! {
         clip: Node<'core>,
         script: &[u8]
     ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, Error> {
+        let script = String::from_utf8( script.to_vec() )?;
         Ok(Some(Box::new(
             build_render_filter(clip, BufReader::new(
-                File::open(
-                    String::from_utf8( script.to_vec() )?
-                )?
-            ))?
+                File::open(&script)?
+            ), &DirectoryResolver::for_script(&script))?
         )))
     }
 }
@@ -76,19 +80,19 @@ make_filter_function! {
         data: &[u8]
     ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, Error> {
         Ok(Some(Box::new(
-            build_render_filter(clip, Cursor::new(data))?
+            build_render_filter(clip, Cursor::new(data), &DirectoryResolver::default())?
         )))
     }
 }
 
 // Build vapoursynth filter instance
-fn build_render_filter<R>(clip: Node, reader: R) -> Result<RenderFilter, Error>
+fn build_render_filter<R>(clip: Node, reader: R, resolver: &dyn ResourceResolver) -> Result<RenderFilter, Error>
     where R: BufRead {
     Ok(RenderFilter{
         source: clip,
         renderer: Mutex::new(RefCell::new(SsbRenderer::new(
             Ssb::default().parse_owned(reader)
-            .and_then(SsbRender::try_from)
+            .and_then(|ssb| SsbRender::from_with_options(ssb, &RenderOptions::new().resolver(resolver)).map(|(ssb_render, _)| ssb_render ) )
             .map_err(|err| err_msg(err.to_string()) )?
         )))
     })
@@ -164,20 +168,22 @@ impl<'core> Filter<'core> for RenderFilter<'core> {
                             }
                         }
                     ).map_err(|err| err_msg(err.to_string()) )?,
-                    RenderTrigger::Time(
+                    match self.source.info().framerate {
+                        // Frame number by framerate (resolves frame-triggered events too)
+                        Property::Constant(framerate) => RenderTrigger::Frame(
+                            n as u32,
+                            SsbFramerate::new(framerate.numerator as u32, framerate.denominator as u32).ok_or_else(|| format_err!("Couldn't use framerate {}/{}!", framerate.numerator, framerate.denominator))?
+                        ),
                         // Calculate frame time (in milliseconds)
-                        match self.source.info().framerate {
-                            Property::Constant(framerate) => (framerate.denominator as f64 / framerate.numerator as f64 * 1000.0 * n as f64) as u32,
-                            Property::Variable => { // Reserved frame properties: <http://www.vapoursynth.com/doc/apireference.html#reserved-frame-properties>
-                                let frame_props = frame.props();
-                                if let (Ok(duration_numerator), Ok(duration_denominator)) = (frame_props.get_int("_DurationNum"), frame_props.get_int("_DurationDen")) {
-                                    (duration_numerator as f64 / duration_denominator as f64 * 1000.0) as u32
-                                } else {
-                                    bail!("Couldn't get frame time! No constant framerate or variable frame property.")
-                                }
+                        Property::Variable => RenderTrigger::Time({ // Reserved frame properties: <http://www.vapoursynth.com/doc/apireference.html#reserved-frame-properties>
+                            let frame_props = frame.props();
+                            if let (Ok(duration_numerator), Ok(duration_denominator)) = (frame_props.get_int("_DurationNum"), frame_props.get_int("_DurationDen")) {
+                                (duration_numerator as f64 / duration_denominator as f64 * 1000.0) as u32
+                            } else {
+                                bail!("Couldn't get frame time! No constant framerate or variable frame property.")
                             }
-                        }
-                    )
+                        })
+                    }
                 ).map_err(|err| err_msg(err.to_string()) )?;
                 // Pass processed frame copy further through the pipeline
                 Ok(frame.into())
@@ -0,0 +1,322 @@
+// Imports
+use crate::{
+    state::error::ParseError,
+    objects::{
+        ssb_objects::{View,Framerate,Timecode,Event,EventRender,EventTrigger,FontFace,FontStyle,ResourceData,TextureDataVariant},
+        event_objects::EventObject
+    },
+    parsers::{
+        ssb::Ssb,
+        ssb_render::SsbRender
+    }
+};
+use std::{
+    convert::TryFrom,
+    io::Cursor
+};
+
+
+/// Fluent construction of one SSB event.
+///
+/// Objects get formatted to escaped event data, so text can be passed as it is.
+/// Building validates the trigger and parses the data again, so built events are guaranteed to parse.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EventBuilder {
+    trigger: EventTrigger,
+    macro_name: Option<String>,
+    note: Option<String>,
+    objects: Vec<EventObject>
+}
+impl EventBuilder {
+    /// New event triggered by time range (in milliseconds).
+    pub fn at_time(start_ms: u32, end_ms: u32) -> Self {
+        Self::with_trigger(EventTrigger::Time((start_ms, end_ms)))
+    }
+    /// New event triggered by frame range.
+    pub fn at_frames(start_frame: u32, end_frame: u32) -> Self {
+        Self::with_trigger(EventTrigger::Frame((start_frame, end_frame)))
+    }
+    /// New event triggered by timecode range.
+    pub fn at_timecodes(start_timecode: Timecode, end_timecode: Timecode) -> Self {
+        Self::with_trigger(EventTrigger::Timecode((start_timecode, end_timecode)))
+    }
+    /// New event triggered by id.
+    pub fn by_id(id: &str) -> Self {
+        Self::with_trigger(EventTrigger::Id(id.to_owned()))
+    }
+    /// New event with any trigger.
+    pub fn with_trigger(trigger: EventTrigger) -> Self {
+        Self {
+            trigger,
+            macro_name: None,
+            note: None,
+            objects: vec![]
+        }
+    }
+    /// Macro to insert before event data.
+    pub fn macro_name(mut self, name: &str) -> Self {
+        self.macro_name = Some(name.to_owned());
+        self
+    }
+    /// Note for humans, ignored by rendering.
+    pub fn note(mut self, note: &str) -> Self {
+        self.note = Some(note.to_owned());
+        self
+    }
+    /// Append text (merged with text right before).
+    pub fn text(mut self, text: &str) -> Self {
+        if let Some(EventObject::GeometryText(last_text)) = self.objects.last_mut() {
+            last_text.push_str(text);
+        } else {
+            self.objects.push(EventObject::GeometryText(text.to_owned()));
+        }
+        self
+    }
+    /// Append any object (tag or geometry).
+    pub fn object(mut self, object: EventObject) -> Self {
+        self.objects.push(object);
+        self
+    }
+    /// Append many objects.
+    pub fn objects<I>(mut self, objects: I) -> Self
+        where I: IntoIterator<Item = EventObject> {
+        self.objects.extend(objects);
+        self
+    }
+    /// Build raw event.
+    pub fn build(self) -> Result<Event, ParseError> {
+        let data = self.validate()?.0;
+        Ok(Event {
+            trigger: self.trigger,
+            macro_name: self.macro_name,
+            note: self.note,
+            data,
+            data_location: (0, 0)
+        })
+    }
+    /// Build event for rendering, which can't use a macro (needs macros of a whole script, see [`SsbBuilder::build_render`]).
+    pub fn build_render(self) -> Result<EventRender, ParseError> {
+        if let Some(macro_name) = &self.macro_name {
+            return Err(ParseError::new(&format!("Macro '{}' can't be resolved without script!", macro_name)));
+        }
+        let objects = self.validate()?.1;
+        Ok(EventRender {
+            trigger: self.trigger,
+            objects
+        })
+    }
+    // Check trigger & fields, format & reparse data
+    fn validate(&self) -> Result<(String, Vec<EventObject>), ParseError> {
+        match &self.trigger {
+            EventTrigger::Id(id) => check_field(id, "Event trigger id")?,
+            EventTrigger::Time((start_time, end_time)) => if start_time > end_time {
+                return Err(ParseError::new("Start time greater than end time!"));
+            }
+            EventTrigger::Frame((start_frame, end_frame)) => if start_frame > end_frame {
+                return Err(ParseError::new("Start frame greater than end frame!"));
+            }
+            EventTrigger::Timecode((start_timecode, end_timecode)) => if start_timecode > end_timecode {
+                return Err(ParseError::new("Start timecode greater than end timecode!"));
+            }
+        }
+        for (value, description) in [(&self.macro_name, "Event macro name"), (&self.note, "Event note")].iter() {
+            if let Some(value) = value {
+                check_field(value, description)?;
+            }
+        }
+        let data = EventObject::format_many(&self.objects).map_err(|err| ParseError::new_with_source("Event objects not representable in SSB!", err) )?;
+        let objects = EventObject::parse_many(&data)?;
+        Ok((data, objects))
+    }
+}
+// Single-line fields between event separators
+fn check_field(value: &str, description: &str) -> Result<(), ParseError> {
+    if value.is_empty() || value.contains(&['\n', '\r', '|'][..]) {
+        Err(ParseError::new(&format!("{} '{}' is empty or contains line break or separator!", description, value.escape_debug())))
+    } else {
+        Ok(())
+    }
+}
+
+/// Fluent construction of whole SSB data.
+///
+/// Building writes & parses the collected data again, so built scripts are guaranteed to parse.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SsbBuilder {
+    ssb: Ssb,
+    macros: Vec<(String, Vec<EventObject>)>,
+    events: Vec<EventBuilder>
+}
+impl SsbBuilder {
+    /// New builder with default target.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // Info section
+    pub fn title(mut self, title: &str) -> Self {
+        self.ssb.info_title = Some(title.to_owned());
+        self
+    }
+    pub fn author(mut self, author: &str) -> Self {
+        self.ssb.info_author = Some(author.to_owned());
+        self
+    }
+    pub fn description(mut self, description: &str) -> Self {
+        self.ssb.info_description = Some(description.to_owned());
+        self
+    }
+    pub fn version(mut self, version: &str) -> Self {
+        self.ssb.info_version = Some(version.to_owned());
+        self
+    }
+    /// Custom info entry.
+    pub fn info(mut self, key: &str, value: &str) -> Self {
+        self.ssb.info_custom.insert(key.to_owned(), value.to_owned());
+        self
+    }
+    // Target section
+    pub fn size(mut self, width: u16, height: u16) -> Self {
+        self.ssb.target_width = Some(width);
+        self.ssb.target_height = Some(height);
+        self
+    }
+    pub fn depth(mut self, depth: u16) -> Self {
+        self.ssb.target_depth = depth;
+        self
+    }
+    pub fn view(mut self, view: View) -> Self {
+        self.ssb.target_view = view;
+        self
+    }
+    pub fn framerate(mut self, framerate: Framerate) -> Self {
+        self.ssb.target_framerate = Some(framerate);
+        self
+    }
+    // Macros & events sections
+    /// Macro by objects (replaces previous definition of same name).
+    pub fn define_macro<I>(mut self, name: &str, objects: I) -> Self
+        where I: IntoIterator<Item = EventObject> {
+        self.macros.retain(|(macro_name, _)| macro_name != name );
+        self.macros.push((name.to_owned(), objects.into_iter().collect()));
+        self
+    }
+    pub fn event(mut self, event: EventBuilder) -> Self {
+        self.events.push(event);
+        self
+    }
+    // Resources section
+    pub fn font(mut self, family: &str, style: FontStyle, data: Vec<u8>) -> Self {
+        self.ssb.fonts.insert(FontFace {family: family.to_owned(), style}, ResourceData::from(data));
+        self
+    }
+    pub fn texture(mut self, id: &str, data: Vec<u8>) -> Self {
+        self.ssb.textures.insert(id.to_owned(), TextureDataVariant::Raw(ResourceData::from(data)));
+        self
+    }
+    /// Texture by url, resolved on rendering conversion.
+    pub fn texture_url(mut self, id: &str, url: &str) -> Self {
+        self.ssb.textures.insert(id.to_owned(), TextureDataVariant::Url(url.to_owned()));
+        self
+    }
+    /// Build raw SSB data (with event data locations as by written text).
+    pub fn build(self) -> Result<Ssb, ParseError> {
+        let mut ssb = self.ssb;
+        for (name, objects) in self.macros {
+            let data = EventObject::format_many(&objects).map_err(|err| ParseError::new_with_source(&format!("Macro '{}' not representable in SSB!", name), err) )?;
+            EventObject::parse_many(&data).map_err(|err| ParseError::new_with_source(&format!("Invalid macro '{}'!", name), err) )?;
+            ssb.macros.insert(name, data);
+        }
+        for (event_index, event) in self.events.into_iter().enumerate() {
+            ssb.events.push(event.build().map_err(|err| ParseError::new_with_source(&format!("Invalid event {}!", event_index), err) )?);
+        }
+        let mut buffer = vec![];
+        ssb.write(&mut buffer).map_err(|err| ParseError::new_with_source("Data not representable in text!", err) )?;
+        Ssb::default().parse_owned(Cursor::new(buffer))
+    }
+    /// Build SSB data for rendering (macros resolved, resources loaded).
+    pub fn build_render(self) -> Result<SsbRender, ParseError> {
+        SsbRender::try_from(self.build()?)
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{EventBuilder,SsbBuilder,Ssb,SsbRender,TryFrom};
+    use crate::objects::{
+        ssb_objects::{EventTrigger,Timecode},
+        event_objects::EventObject
+    };
+
+    #[test]
+    fn event() {
+        let event = EventBuilder::at_time(1000, 2000)
+            .note("greeting")
+            .object(EventObject::TagBold(true))
+            .text("Hello [")
+            .text("world]\\\n!")
+            .build().unwrap();
+        assert_eq!(event.trigger, EventTrigger::Time((1000, 2000)));
+        assert_eq!(event.note.as_deref(), Some("greeting"));
+        assert_eq!(event.data, "[bold=y]Hello \\[world\\]\\\\\\n!");
+        let event = EventBuilder::at_time(1000, 2000).text("a").object(EventObject::TagItalic(true)).text("b").build_render().unwrap();
+        assert_eq!(event.objects, vec![
+            EventObject::GeometryText("a".to_owned()),
+            EventObject::TagItalic(true),
+            EventObject::GeometryText("b".to_owned())
+        ]);
+        let timecode = |seconds| Timecode {hours: 0, minutes: 0, seconds, frames: 0, drop_frame: false};
+        assert!(EventBuilder::at_timecodes(timecode(1), timecode(2)).build().is_ok());
+        assert!(EventBuilder::by_id("title").build().is_ok());
+    }
+
+    #[test]
+    fn event_errors() {
+        assert_eq!(EventBuilder::at_time(2000, 1000).build().map_err(|err| err.to_string() ), Err("Start time greater than end time!".to_owned()));
+        assert_eq!(EventBuilder::at_frames(2, 1).build().map_err(|err| err.to_string() ), Err("Start frame greater than end frame!".to_owned()));
+        assert_eq!(EventBuilder::by_id("a|b").build().map_err(|err| err.to_string() ), Err("Event trigger id 'a|b' is empty or contains line break or separator!".to_owned()));
+        assert_eq!(EventBuilder::at_time(0, 1).note("").build().map_err(|err| err.to_string() ), Err("Event note '' is empty or contains line break or separator!".to_owned()));
+        assert_eq!(EventBuilder::at_time(0, 1).macro_name("foo").build_render().map_err(|err| err.to_string() ), Err("Macro 'foo' can't be resolved without script!".to_owned()));
+        assert_eq!(EventBuilder::at_time(0, 1).object(EventObject::TagFont("a;b".to_owned())).build().map_err(|err| err.to_string() ), Err("Event objects not representable in SSB!\nTag 'font=a;b' not representable!".to_owned()));
+    }
+
+    #[test]
+    fn ssb() {
+        let ssb = SsbBuilder::new()
+            .title("Test")
+            .info("Tool", "builder")
+            .size(1280, 720)
+            .define_macro("bold", vec![EventObject::TagBold(true)])
+            .event(EventBuilder::at_time(0, 1000).macro_name("bold").text("Hi|there"))
+            .event(EventBuilder::by_id("logo").text("Logo"))
+            .texture("pixel", vec![0xFF])
+            .build().unwrap();
+        assert_eq!(ssb.info_title.as_deref(), Some("Test"));
+        assert_eq!(ssb.macros["bold"], "[bold=y]");
+        assert_eq!(ssb.events.len(), 2);
+        assert_eq!(ssb.events[0].data, "Hi|there");
+        assert_ne!(ssb.events[0].data_location, (0, 0));
+        let ssb_render = SsbBuilder::new()
+            .define_macro("bold", vec![EventObject::TagBold(true)])
+            .event(EventBuilder::at_time(0, 1000).macro_name("bold").text("Hi"))
+            .build_render().unwrap();
+        assert_eq!(ssb_render.events[0].objects, vec![EventObject::TagBold(true), EventObject::GeometryText("Hi".to_owned())]);
+        assert_eq!(
+            SsbBuilder::new().event(EventBuilder::at_time(0, 1000)).event(EventBuilder::at_time(5, 1)).build().map_err(|err| err.to_string() ),
+            Err("Invalid event 1!\nStart time greater than end time!".to_owned())
+        );
+        assert!(SsbBuilder::new().info("Title", "Reserved").build().is_err());
+    }
+
+    #[test]
+    fn event_with_macro_in_ssb() {
+        let mut ssb = Ssb::default();
+        ssb.macros.insert("bold".to_owned(), "[bold=y]".to_owned());
+        ssb.events.push(EventBuilder::at_time(0, 1000).macro_name("bold").text("Hi").build().unwrap());
+        assert_eq!(
+            SsbRender::try_from(ssb).unwrap().events[0].objects,
+            vec![EventObject::TagBold(true), EventObject::GeometryText("Hi".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,980 @@
+// Imports
+use crate::{
+    state::error::{ParseError,ConversionWarning},
+    geometry::arcs_to_curves,
+    objects::{
+        ssb_objects::{Event,EventTrigger,FontFace,FontStyle,TextureDataVariant,ResourceData},
+        event_objects::{EventObject,ShapeSegment,Alignment,WrapStyle,Space,Rotate,Scale,Shear,Border,Blur,Color,Alpha,Animate,Rgb}
+    },
+    parsers::{
+        ssb::Ssb,
+        ssb_render::SsbRender
+    },
+    formats::{
+        event_meta,
+        event_times,
+        EventMeta
+    },
+    utils::pattern::*
+};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    io::{self,BufRead,Write}
+};
+
+
+// Constants
+const STYLES_FORMAT: &str = "Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding";
+const LEGACY_STYLES_FORMAT: &str = "Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, TertiaryColour, BackColour, Bold, Italic, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, AlphaLevel, Encoding";
+const EVENTS_FORMAT: &str = "Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+const FORMAT_KEY: &str = "Format";
+const DEFAULT_STYLE_NAME: &str = "Default";
+const DEFAULT_STYLE_VALUES: &str = "Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,0,0,2,0,0,0,1";
+// Override tag names, longer names first to match prefixes correctly
+const OVERRIDE_TAGS: &[&str] = &[
+    "xbord", "ybord", "xshad", "yshad", "fscx", "fscy", "fsp", "fs", "fn", "frx", "fry", "frz", "fr", "fax", "fay", "fade", "fad", "fe",
+    "bord", "shad", "blur", "be", "alpha", "1a", "2a", "3a", "4a", "1c", "2c", "3c", "4c", "an", "a", "b", "iclip", "clip", "i", "u", "s", "c",
+    "q", "r", "pos", "move", "org", "t", "kf", "ko", "K", "k", "pbo", "p"
+];
+
+
+// States
+#[derive(Debug, PartialEq, Clone)]
+enum AssSection {
+    ScriptInfo,
+    Styles,
+    LegacyStyles,
+    Events,
+    Fonts,
+    Graphics,
+    Unknown
+}
+impl From<&str> for AssSection {
+    fn from(value: &str) -> Self {
+        match value {
+            "[Script Info]" => Self::ScriptInfo,
+            "[V4+ Styles]" => Self::Styles,
+            "[V4 Styles]" => Self::LegacyStyles,
+            "[Events]" => Self::Events,
+            "[Fonts]" => Self::Fonts,
+            "[Graphics]" => Self::Graphics,
+            _ => Self::Unknown
+        }
+    }
+}
+
+
+impl Ssb {
+    /// Convert ASS (or SSA) input into SSB data.
+    ///
+    /// Styles become macros (names restricted to characters allowed in inline macros), dialogues become time-triggered events with override tags translated and embedded fonts & graphics become resources.
+    /// Alpha values get inverted (ASS counts transparency, SSB opacity).
+    /// Constructs without SSB counterpart get skipped and reported by warnings.
+    pub fn from_ass<R>(reader: R) -> Result<(Self, Vec<ConversionWarning>), ParseError>
+        where R: BufRead {
+        // Initial state
+        let mut ssb = Self::default();
+        let mut warnings = vec![];
+        let mut section: Option<AssSection> = None;
+        let mut styles_format = split_format(STYLES_FORMAT);
+        let mut events_format = split_format(EVENTS_FORMAT);
+        let mut attachment: Option<(usize, String, String)> = None;
+        // Iterate through text lines
+        for (line_index, line) in reader.lines().enumerate() {
+            // Check for valid UTF-8 and remove carriage return & byte order mark
+            let mut line = line?;
+            if line.ends_with('\r') {line.pop();}
+            let line = line.trim_start_matches('\u{feff}');
+            // Switch section
+            if line.starts_with('[') && line.ends_with(']') {
+                finish_attachment(&mut ssb, attachment.take(), &section, &mut warnings);
+                let parsed_section = AssSection::from(line);
+                match parsed_section {
+                    AssSection::LegacyStyles => styles_format = split_format(LEGACY_STYLES_FORMAT),
+                    AssSection::Unknown => warnings.push(ConversionWarning::new("Skipped unknown section", line, Some((line_index, 0)))),
+                    _ => ()
+                }
+                section = Some(parsed_section);
+                continue;
+            }
+            // Ignore empty lines & comments
+            if line.trim().is_empty() || (line.starts_with(';') && section != Some(AssSection::Fonts) && section != Some(AssSection::Graphics)) {
+                continue;
+            }
+            // Handle section entry
+            let entry = line.find(':').map(|pos| (line[..pos].trim(), line[pos + 1 /* : */..].trim_start()) );
+            match (&section, entry) {
+                // Script info
+                (Some(AssSection::ScriptInfo), Some((key, value))) => match key {
+                    "Title" => ssb.info_title = Some(value.to_owned()),
+                    "Original Script" => ssb.info_author = Some(value.to_owned()),
+                    "PlayResX" | "PlayResY" => match value.parse() {
+                        Ok(size) if key == "PlayResX" => ssb.target_width = Some(size),
+                        Ok(size) => ssb.target_height = Some(size),
+                        Err(_) => warnings.push(ConversionWarning::new("Invalid script resolution", line, Some((line_index, 0))))
+                    }
+                    _ => {ssb.info_custom.insert(key.to_owned(), value.to_owned());}
+                }
+                // Styles
+                (Some(AssSection::Styles), Some((FORMAT_KEY, value))) | (Some(AssSection::LegacyStyles), Some((FORMAT_KEY, value))) => styles_format = split_format(value),
+                (Some(AssSection::Styles), Some(("Style", value))) | (Some(AssSection::LegacyStyles), Some(("Style", value))) => {
+                    let fields = styles_format.iter().map(String::as_str).zip(value.splitn(styles_format.len(), VALUE_SEPARATOR)).collect::<HashMap<_,_>>();
+                    let style_name = fields.get("name").map(|name| macro_name(name.trim_start_matches('*')) ).unwrap_or_default();
+                    let tags = style_tags(&fields, section == Some(AssSection::LegacyStyles), (line_index, line.len() - value.len()), &mut warnings);
+                    ssb.macros.insert(style_name, TAG_START.to_owned() + &tags.join(&TAG_SEPARATOR.to_string()) + TAG_END);
+                }
+                // Events
+                (Some(AssSection::Events), Some((FORMAT_KEY, value))) => events_format = split_format(value),
+                (Some(AssSection::Events), Some(("Dialogue", value))) => {
+                    if let Some(event) = convert_event(&ssb.macros, &events_format, value, (line_index, line.len() - value.len()), &mut warnings) {
+                        ssb.events.push(event);
+                    }
+                }
+                (Some(AssSection::Events), Some((kind, _))) => warnings.push(ConversionWarning::new(&format!("Skipped {} event", kind.to_lowercase()), line, Some((line_index, 0)))),
+                // Attachments
+                (Some(AssSection::Fonts), Some(("fontname", value))) | (Some(AssSection::Graphics), Some(("filename", value))) => {
+                    finish_attachment(&mut ssb, attachment.take(), &section, &mut warnings);
+                    attachment = Some((line_index, value.to_owned(), String::new()));
+                }
+                (Some(AssSection::Fonts), _) | (Some(AssSection::Graphics), _) if attachment.is_some() => {
+                    if let Some((_, _, data)) = attachment.as_mut() {
+                        data.push_str(line.trim());
+                    }
+                }
+                // Skipped
+                (Some(AssSection::Unknown), _) => (),
+                _ => warnings.push(ConversionWarning::new("Skipped invalid entry", line, Some((line_index, 0))))
+            }
+        }
+        finish_attachment(&mut ssb, attachment, &section, &mut warnings);
+        // Return converted data
+        Ok((ssb, warnings))
+    }
+}
+impl Ssb {
+    /// Convert SSB data into ASS output.
+    ///
+    /// Works like `SsbRender::write_ass` but keeps script info and event notes (as dialogue names).
+    /// Warnings point to the event data locations.
+    pub fn write_ass<W>(&self, writer: W) -> Result<Vec<ConversionWarning>, ParseError>
+        where W: Write {
+        let render = SsbRender::try_from(self.clone())?;
+        // Script info
+        let mut warnings = vec![];
+        let mut info = vec![];
+        if let Some(title) = &self.info_title {
+            info.push(("Title", title.as_str()));
+        }
+        if let Some(author) = &self.info_author {
+            info.push(("Original Script", author.as_str()));
+        }
+        let mut custom_info = self.info_custom.iter().collect::<Vec<_>>();
+        custom_info.sort();
+        for (key, value) in custom_info {
+            match key.as_str() {
+                // Written anyway
+                "ScriptType" => (),
+                "Title" | "Original Script" | "PlayResX" | "PlayResY" => warnings.push(ConversionWarning::new("Skipped reserved script info", &format!("{}: {}", key, value), None)),
+                _ => info.push((key, value))
+            }
+        }
+        warnings.extend(write_ass_script(writer, &render, &info, &event_meta(self))?);
+        Ok(warnings)
+    }
+}
+impl SsbRender {
+    /// Convert SSB render data into ASS output.
+    ///
+    /// Time-triggered events become dialogues of one default style, tags with ASS counterpart become override tags and shapes become drawings.
+    /// Fonts & textures get embedded as attachments.
+    /// Constructs without ASS counterpart get skipped and reported by warnings.
+    pub fn write_ass<W>(&self, writer: W) -> io::Result<Vec<ConversionWarning>>
+        where W: Write {
+        write_ass_script(writer, self, &[], &vec![(None, None); self.events.len()])
+    }
+}
+
+
+// Sections helpers
+fn split_format(format: &str) -> Vec<String> {
+    format.split(VALUE_SEPARATOR).map(|field| field.trim().to_lowercase() ).collect()
+}
+pub(crate) fn macro_name(style_name: &str) -> String {
+    style_name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' {c} else {'_'} ).collect()
+}
+fn finish_attachment(ssb: &mut Ssb, attachment: Option<(usize, String, String)>, section: &Option<AssSection>, warnings: &mut Vec<ConversionWarning>) {
+    if let Some((line_index, name, data)) = attachment {
+        match (uudecode(&data), section) {
+            (Ok(data), Some(AssSection::Fonts)) => {ssb.fonts.insert(font_face(&name), data.into());}
+            (Ok(data), _) => {ssb.textures.insert(name, TextureDataVariant::Raw(data.into()));}
+            (Err(_), _) => warnings.push(ConversionWarning::new("Skipped attachment with invalid data", &name, Some((line_index, 0))))
+        }
+    }
+}
+fn font_face(file_name: &str) -> FontFace {
+    // Remove extension
+    let name = file_name.rfind('.').map_or(file_name, |pos| &file_name[..pos]);
+    // Find style suffix (f.e. 'arial_B0.ttf')
+    if let Some(pos) = name.rfind('_') {
+        let suffix = &name[pos + 1 /* _ */..];
+        let digits = suffix.trim_start_matches(&['B', 'I'][..]);
+        if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+            return FontFace {
+                family: name[..pos].to_owned(),
+                style: match &suffix[..suffix.len() - digits.len()] {
+                    "B" => FontStyle::Bold,
+                    "I" => FontStyle::Italic,
+                    "BI" => FontStyle::BoldItalic,
+                    _ => FontStyle::Regular
+                }
+            };
+        }
+    }
+    FontFace {
+        family: name.to_owned(),
+        style: FontStyle::Regular
+    }
+}
+fn style_tags(fields: &HashMap<&str, &str>, legacy: bool, pos: (usize, usize), warnings: &mut Vec<ConversionWarning>) -> Vec<String> {
+    let mut tags = vec![];
+    for (name, value) in fields.iter().map(|(name, value)| (*name, value.trim()) ) {
+        let converted = match name {
+            "fontname" => Ok(vec![format!("font={}", value)]),
+            "fontsize" => number(value, 1.0).map(|size| vec![format!("size={}", size)] ),
+            "primarycolour" => color(value).map(|(rgb, alpha)| vec![format!("color={}", rgb), format!("alpha={}", alpha)] ),
+            "secondarycolour" => color(value).map(|(rgb, _)| vec![format!("kcolor={}", rgb)] ),
+            "outlinecolour" | "tertiarycolour" => color(value).map(|(rgb, alpha)| vec![format!("bordercolor={}", rgb), format!("borderalpha={}", alpha)] ),
+            "bold" | "italic" | "underline" | "strikeout" => Ok(vec![format!("{}={}", name, if value == "0" {"n"} else {"y"})]),
+            "scalex" => number(value, 100.0).map(|scale| vec![format!("scale-x={}", scale)] ),
+            "scaley" => number(value, 100.0).map(|scale| vec![format!("scale-y={}", scale)] ),
+            "spacing" => number(value, 1.0).map(|space| vec![format!("space-h={}", space)] ),
+            "angle" => number(value, 1.0).map(|angle| vec![format!("rotate-z={}", angle)] ),
+            "outline" => number(value, 1.0).map(|border| vec![format!("border={}", border)] ),
+            "alignment" => alignment(value, legacy).map(|alignment| vec![format!("alignment={}", alignment)] ),
+            "marginl" => number(value, 1.0).map(|margin| vec![format!("margin-left={}", margin)] ),
+            "marginr" => number(value, 1.0).map(|margin| vec![format!("margin-right={}", margin)] ),
+            "marginv" => number(value, 1.0).map(|margin| vec![format!("margin-top={}", margin), format!("margin-bottom={}", margin)] ),
+            "borderstyle" if value == "1" => Ok(vec![]),
+            "shadow" if value == "0" => Ok(vec![]),
+            // Shadow color is irrelevant without shadow, encoding is replaced by unicode
+            "name" | "backcolour" | "encoding" | "alphalevel" => Ok(vec![]),
+            _ => Err(())
+        };
+        match converted {
+            Ok(converted) => tags.extend(converted),
+            Err(_) => warnings.push(ConversionWarning::new("Unsupported style field", &format!("{}: {}", name, value), Some(pos)))
+        }
+    }
+    // Fields order of a hashmap isn't stable
+    tags.sort();
+    tags
+}
+fn convert_event(macros: &HashMap<String, String>, format: &[String], value: &str, pos: (usize, usize), warnings: &mut Vec<ConversionWarning>) -> Option<Event> {
+    // Map fields to format
+    let fields = format.iter().map(String::as_str).zip(value.splitn(format.len(), VALUE_SEPARATOR)).collect::<HashMap<_,_>>();
+    let field = |name: &str| fields.get(name).map(|value| value.trim() ).unwrap_or_default();
+    // Times
+    let (start_time, end_time) = match (parse_time(field("start")), parse_time(field("end"))) {
+        (Ok(start_time), Ok(end_time)) if start_time <= end_time => (start_time, end_time),
+        _ => {
+            warnings.push(ConversionWarning::new("Skipped dialogue with invalid times", value, Some(pos)));
+            return None;
+        }
+    };
+    // Style
+    let style = Some(macro_name(field("style").trim_start_matches('*'))).filter(|style| !style.is_empty() );
+    if let Some(style) = style.as_ref().filter(|style| !macros.contains_key(*style) ) {
+        warnings.push(ConversionWarning::new("Dialogue style not found", style, Some(pos)));
+    }
+    let style = style.filter(|style| macros.contains_key(style) );
+    // Unsupported fields
+    for name in &["layer", "effect"] {
+        if let Some(value) = Some(field(name)).filter(|value| !value.is_empty() && *value != "0") {
+            warnings.push(ConversionWarning::new(&format!("Unsupported dialogue {}", name), value, Some(pos)));
+        }
+    }
+    // Text
+    let text = fields.get("text").copied().unwrap_or_default();
+    let text_pos = (pos.0, pos.1 + value.len() - text.len());
+    let mut converter = TextConverter::new(macros, style.as_deref(), end_time - start_time, warnings);
+    for (name, tag) in &[("marginl", "margin-left"), ("marginr", "margin-right"), ("marginv", "margin-top"), ("marginv", "margin-bottom")] {
+        if let Some(margin) = Some(field(name)).filter(|value| !value.is_empty() && value.parse::<f32>().ok() != Some(0.0)) {
+            converter.tags.push(format!("{}={}", tag, margin));
+        }
+    }
+    converter.convert(text, text_pos);
+    let data = converter.finish();
+    // Build event
+    Some(Event {
+        trigger: EventTrigger::Time((start_time, end_time)),
+        macro_name: style,
+        note: Some(field("name").to_owned()).filter(|name| !name.is_empty() ),
+        data,
+        data_location: text_pos
+    })
+}
+
+
+// Text conversion
+struct TextConverter<'a> {
+    macros: &'a HashMap<String, String>,
+    style: Option<&'a str>,
+    duration: u32,
+    warnings: &'a mut Vec<ConversionWarning>,
+    data: String,
+    tags: Vec<String>,
+    drawing_scale: Option<f32>
+}
+impl<'a> TextConverter<'a> {
+    fn new(macros: &'a HashMap<String, String>, style: Option<&'a str>, duration: u32, warnings: &'a mut Vec<ConversionWarning>) -> Self {
+        Self {
+            macros,
+            style,
+            duration,
+            warnings,
+            data: String::new(),
+            tags: vec![],
+            drawing_scale: None
+        }
+    }
+    fn convert(&mut self, text: &str, pos: (usize, usize)) {
+        let mut rest = text;
+        while !rest.is_empty() {
+            let column = pos.1 + text.len() - rest.len();
+            // Override block
+            if let (Some(0), Some(block_end)) = (rest.find('{'), rest.find('}')) {
+                self.convert_block(&rest[1..block_end], (pos.0, column + 1));
+                rest = &rest[block_end + 1..];
+            // Text or drawing
+            } else {
+                let text_end = rest.char_indices().skip(1).find(|(_, c)| *c == '{' ).map_or(rest.len(), |(pos, _)| pos);
+                self.convert_text(&rest[..text_end], (pos.0, column));
+                rest = &rest[text_end..];
+            }
+        }
+    }
+    fn finish(mut self) -> String {
+        self.flush_tags();
+        self.data
+    }
+    fn flush_tags(&mut self) {
+        if !self.tags.is_empty() {
+            self.data.push_str(TAG_START);
+            self.data.push_str(&self.tags.join(&TAG_SEPARATOR.to_string()));
+            self.data.push_str(TAG_END);
+            self.tags.clear();
+        }
+    }
+    fn warn(&mut self, msg: &str, construct: &str, pos: (usize, usize)) {
+        self.warnings.push(ConversionWarning::new(msg, construct, Some(pos)));
+    }
+    fn convert_text(&mut self, text: &str, pos: (usize, usize)) {
+        // Drawing
+        if let Some(scale) = self.drawing_scale {
+            match drawing(text, scale) {
+                Ok(shape) => {
+                    self.flush_tags();
+                    self.data.push_str(&shape);
+                }
+                Err(_) => self.warn("Unsupported drawing", text, pos)
+            }
+        // Text
+        } else {
+            self.flush_tags();
+            let mut chars = text.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => match chars.peek() {
+                        Some('N') => {chars.next(); self.data.push_str("\\n");}
+                        Some('n') => {chars.next(); self.data.push(' ');}
+                        Some('h') => {chars.next(); self.data.push('\u{a0}');}
+                        _ => self.data.push_str("\\\\")
+                    }
+                    TAG_START_CHAR | TAG_END_CHAR => {self.data.push('\\'); self.data.push(c);}
+                    _ => self.data.push(c)
+                }
+            }
+        }
+    }
+    fn convert_block(&mut self, block: &str, pos: (usize, usize)) {
+        // Comment before tags
+        let tags_start = block.find('\\').unwrap_or(block.len());
+        if tags_start > 0 {
+            self.warn("Skipped comment", &block[..tags_start], pos);
+        }
+        // Tags
+        for (offset, token) in split_override_tags(&block[tags_start..]) {
+            let tag_pos = (pos.0, pos.1 + tags_start + offset);
+            let (name, arg) = split_override_tag(token);
+            let args = arguments(arg);
+            match name {
+                "r" => {
+                    self.tags.push("reset".to_owned());
+                    let style = if arg.is_empty() {self.style.map(ToOwned::to_owned)} else {Some(macro_name(arg))};
+                    if let Some(style) = style.filter(|style| self.macros.contains_key(style) ) {
+                        self.flush_tags();
+                        self.data.push_str(&format!("{}{}{}", MACRO_INLINE_START, style, MACRO_INLINE_END));
+                    } else if !arg.is_empty() {
+                        self.warn("Reset style not found", token, tag_pos);
+                    }
+                }
+                "p" => match arg.parse::<u8>() {
+                    Ok(0) => {
+                        self.drawing_scale = None;
+                        self.tags.push("mode=text".to_owned());
+                    }
+                    Ok(scale) if scale < 32 => {
+                        self.drawing_scale = Some((1u32 << (scale - 1)) as f32);
+                        self.tags.push("mode=shape".to_owned());
+                    }
+                    _ => self.warn("Invalid drawing mode", token, tag_pos)
+                }
+                "clip" | "iclip" => {
+                    let shape = match args.as_slice() {
+                        [x1, y1, x2, y2] => match (x1.parse::<f32>(), y1.parse::<f32>(), x2.parse::<f32>(), y2.parse::<f32>()) {
+                            (Ok(x1), Ok(y1), Ok(x2), Ok(y2)) => Ok(format!("m {0} {1} l {2} {1} {2} {3} {0} {3} c", x1, y1, x2, y2)),
+                            _ => Err(())
+                        }
+                        [shape] => drawing(shape, 1.0),
+                        [scale, shape] => scale.parse::<u8>().ok().filter(|scale| (1..32).contains(scale) ).ok_or(()).and_then(|scale| drawing(shape, (1u32 << (scale - 1)) as f32) ),
+                        _ => Err(())
+                    };
+                    match shape {
+                        Ok(shape) => {
+                            self.tags.extend(vec!["mask-clear".to_owned(), "target=mask".to_owned(), "mode=shape".to_owned()]);
+                            self.flush_tags();
+                            self.data.push_str(&shape);
+                            self.tags.push("target=frame".to_owned());
+                            self.tags.push(format!("mask-mode={}", if name == "clip" {"normal"} else {"invert"}));
+                            self.tags.push(format!("mode={}", if self.drawing_scale.is_some() {"shape"} else {"text"}));
+                        }
+                        Err(_) => self.warn("Unsupported clip", token, tag_pos)
+                    }
+                }
+                _ => match convert_override_tag(name, arg, &args, self.duration) {
+                    Ok(tags) => {
+                        // Karaoke effects fall back to plain karaoke
+                        if let "K" | "kf" | "ko" = name {
+                            self.warn("Replaced karaoke effect by plain karaoke", token, tag_pos);
+                        }
+                        self.tags.extend(tags);
+                    }
+                    Err(_) => self.warn("Unsupported override tag", token, tag_pos)
+                }
+            }
+        }
+    }
+}
+pub(crate) fn split_override_tags(block: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut depth = 0usize;
+    let mut token_start = None;
+    for (index, c) in block.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '\\' if depth == 0 => {
+                if let Some(token_start) = token_start {
+                    tokens.push((token_start, &block[token_start..index]));
+                }
+                token_start = Some(index + 1 /* \ */);
+            }
+            _ => ()
+        }
+    }
+    if let Some(token_start) = token_start {
+        tokens.push((token_start, &block[token_start..]));
+    }
+    tokens.retain(|(_, token)| !token.is_empty() );
+    tokens
+}
+fn split_override_tag(token: &str) -> (&str, &str) {
+    OVERRIDE_TAGS.iter()
+        .find(|name| token.starts_with(*name) )
+        .map_or((token, ""), |name| (&token[..name.len()], token[name.len()..].trim()) )
+}
+fn arguments(arg: &str) -> Vec<&str> {
+    if let Some(content) = arg.strip_prefix('(') {
+        let content = content.strip_suffix(')').unwrap_or(content);
+        // Tags of transformations stay together
+        let tags_start = content.find('\\').unwrap_or(content.len());
+        let mut args = content[..tags_start].split(VALUE_SEPARATOR).map(str::trim).filter(|arg| !arg.is_empty() ).collect::<Vec<_>>();
+        if tags_start < content.len() {
+            args.push(&content[tags_start..]);
+        }
+        args
+    } else {
+        vec![]
+    }
+}
+fn convert_override_tag(name: &str, arg: &str, args: &[&str], duration: u32) -> Result<Vec<String>, ()> {
+    Ok(match name {
+        "b" => vec![format!("bold={}", match arg.parse::<u16>().map_err(|_| () )? {0 => "n", 1 => "y", weight if weight >= 700 => "y", _ => "n"})],
+        "i" | "u" | "s" => vec![format!("{}={}", match name {"i" => "italic", "u" => "underline", _ => "strikeout"}, match arg {"0" => "n", "1" => "y", _ => return Err(())})],
+        "fn" if !arg.is_empty() => vec![format!("font={}", arg)],
+        "fs" => vec![format!("size={}", number(arg, 1.0)?)],
+        "fscx" => vec![format!("scale-x={}", number(arg, 100.0)?)],
+        "fscy" => vec![format!("scale-y={}", number(arg, 100.0)?)],
+        "fsp" => vec![format!("space-h={}", number(arg, 1.0)?)],
+        "frx" => vec![format!("rotate-x={}", number(arg, 1.0)?)],
+        "fry" => vec![format!("rotate-y={}", number(arg, 1.0)?)],
+        "frz" | "fr" => vec![format!("rotate-z={}", number(arg, 1.0)?)],
+        "fax" => vec![format!("shear-x={}", number(arg, 1.0)?)],
+        "fay" => vec![format!("shear-y={}", number(arg, 1.0)?)],
+        "bord" => vec![format!("border={}", number(arg, 1.0)?)],
+        "xbord" => vec![format!("border-h={}", number(arg, 1.0)?)],
+        "ybord" => vec![format!("border-v={}", number(arg, 1.0)?)],
+        "blur" => vec![format!("blur={}", number(arg, 1.0)?)],
+        "c" | "1c" => vec![format!("color={}", color(arg)?.0)],
+        "2c" => vec![format!("kcolor={}", color(arg)?.0)],
+        "3c" => vec![format!("bordercolor={}", color(arg)?.0)],
+        "alpha" => {
+            let alpha = alpha(arg)?;
+            vec![format!("alpha={}", alpha), format!("borderalpha={}", alpha)]
+        }
+        "1a" => vec![format!("alpha={}", alpha(arg)?)],
+        "3a" => vec![format!("borderalpha={}", alpha(arg)?)],
+        "an" => vec![format!("alignment={}", alignment(arg, false)?)],
+        "a" => vec![format!("alignment={}", alignment(arg, true)?)],
+        "q" => vec![format!("wrap-style={}", match arg {"2" => "nowrap", "0" | "1" | "3" => "space", _ => return Err(())})],
+        "k" | "K" | "kf" | "ko" => vec![format!("k={}", arg.parse::<u32>().ok().and_then(|duration| duration.checked_mul(10) ).ok_or(())?)],
+        "pos" => match args {
+            [x, y] => vec![format!("position={},{}", number(x, 1.0)?, number(y, 1.0)?)],
+            _ => return Err(())
+        }
+        "move" => {
+            let (from, to, times) = match args {
+                [x1, y1, x2, y2] => ((x1, y1), (x2, y2), String::new()),
+                [x1, y1, x2, y2, t1, t2] => ((x1, y1), (x2, y2), format!("{},{},", integer(t1)?, integer(t2)?)),
+                _ => return Err(())
+            };
+            vec![
+                format!("position={},{}", number(from.0, 1.0)?, number(from.1, 1.0)?),
+                format!("animate={}[position={},{}]", times, number(to.0, 1.0)?, number(to.1, 1.0)?)
+            ]
+        }
+        "fad" => match args {
+            [fade_in, fade_out] => {
+                let (fade_in, fade_out) = (integer(fade_in)?, integer(fade_out)?);
+                let mut tags = vec![];
+                if fade_in > 0 {
+                    tags.push("alpha=0".to_owned());
+                    tags.push("borderalpha=0".to_owned());
+                    tags.push(format!("animate=0,{},[alpha=FF;borderalpha=FF]", fade_in));
+                }
+                if fade_out > 0 {
+                    tags.push(format!("animate={},{},[alpha=0;borderalpha=0]", (i64::from(duration) - fade_out).max(0), duration));
+                }
+                tags
+            }
+            _ => return Err(())
+        }
+        "fade" => match args {
+            [a1, a2, a3, t1, t2, t3, t4] => {
+                let (a1, a2, a3) = (255 - a1.parse::<u8>().map_err(|_| () )?, 255 - a2.parse::<u8>().map_err(|_| () )?, 255 - a3.parse::<u8>().map_err(|_| () )?);
+                vec![
+                    format!("alpha={:X};borderalpha={0:X}", a1),
+                    format!("animate={},{},[alpha={:X};borderalpha={2:X}]", integer(t1)?, integer(t2)?, a2),
+                    format!("animate={},{},[alpha={:X};borderalpha={2:X}]", integer(t3)?, integer(t4)?, a3)
+                ]
+            }
+            _ => return Err(())
+        }
+        "t" => {
+            let (tags, options) = args.split_last().filter(|(tags, _)| tags.starts_with('\\') ).ok_or(())?;
+            let (times, accel) = match options {
+                [] => (String::new(), None),
+                [accel] => (String::new(), Some(accel)),
+                [t1, t2] => (format!("{},{},", integer(t1)?, integer(t2)?), None),
+                [t1, t2, accel] => (format!("{},{},", integer(t1)?, integer(t2)?), Some(accel)),
+                _ => return Err(())
+            };
+            let formula = match accel.map(|accel| number(accel, 1.0) ).transpose()? {
+                Some(accel) if accel != "1" => format!("t^{},", accel),
+                _ => String::new()
+            };
+            let mut animated_tags = vec![];
+            for (_, token) in split_override_tags(tags) {
+                let (name, arg) = split_override_tag(token);
+                match name {
+                    "r" | "p" | "clip" | "iclip" | "t" | "move" | "fad" | "fade" | "pos" | "an" | "a" | "q" | "fn" | "b" | "i" | "u" | "s" | "k" | "K" | "kf" | "ko" => return Err(()),
+                    _ => animated_tags.extend(convert_override_tag(name, arg, &arguments(arg), duration)?)
+                }
+            }
+            vec![format!("animate={}{}[{}]", times, formula, animated_tags.join(&TAG_SEPARATOR.to_string()))]
+        }
+        _ => return Err(())
+    })
+}
+
+
+// Script output
+fn write_ass_script<W>(mut writer: W, render: &SsbRender, info: &[(&str, &str)], events: &[EventMeta]) -> io::Result<Vec<ConversionWarning>>
+    where W: Write {
+    let mut warnings = vec![];
+    // Script info
+    writeln!(writer, "[Script Info]\nScriptType: v4.00+")?;
+    for (key, value) in info {
+        writeln!(writer, "{}: {}", key, single_line(value))?;
+    }
+    if let Some(width) = render.target_width {
+        writeln!(writer, "PlayResX: {}", width)?;
+    }
+    if let Some(height) = render.target_height {
+        writeln!(writer, "PlayResY: {}", height)?;
+    }
+    // Styles
+    writeln!(writer, "\n[V4+ Styles]\n{}: {}\nStyle: {},{}", FORMAT_KEY, STYLES_FORMAT, DEFAULT_STYLE_NAME, DEFAULT_STYLE_VALUES)?;
+    // Events
+    writeln!(writer, "\n[Events]\n{}: {}", FORMAT_KEY, EVENTS_FORMAT)?;
+    for (event, (note, pos)) in render.events.iter().zip(events) {
+        if let Some((start_time, end_time)) = event_times(&event.trigger, render.target_framerate.as_ref(), *pos, &mut warnings) {
+            let name = note.map(|note| {
+                if note.contains(VALUE_SEPARATOR) {
+                    warnings.push(ConversionWarning::new("Replaced separators in dialogue name", note, *pos));
+                }
+                single_line(note).replace(VALUE_SEPARATOR, ";")
+            }).unwrap_or_default();
+            let mut converter = ObjectsConverter::new(*pos, end_time - start_time, &mut warnings);
+            converter.convert(&event.objects);
+            writeln!(writer, "Dialogue: 0,{},{},{},{},0,0,0,,{}", ass_time(start_time), ass_time(end_time), DEFAULT_STYLE_NAME, name, converter.finish())?;
+        }
+    }
+    // Attachments
+    if !render.fonts.is_empty() {
+        writeln!(writer, "\n[Fonts]")?;
+        let mut fonts = render.fonts.iter().collect::<Vec<_>>();
+        fonts.sort_by_key(|(face, _)| (&face.family, face.style.to_string()) );
+        for (face, data) in fonts {
+            let suffix = match face.style {
+                FontStyle::Regular => "",
+                FontStyle::Bold => "B",
+                FontStyle::Italic => "I",
+                FontStyle::BoldItalic => "BI"
+            };
+            writeln!(writer, "fontname: {}_{}0.ttf", face.family, suffix)?;
+            write_uuencoded(&mut writer, data)?;
+        }
+    }
+    if !render.textures.is_empty() {
+        writeln!(writer, "\n[Graphics]")?;
+        let mut textures = render.textures.iter().collect::<Vec<_>>();
+        textures.sort_by_key(|(id, _)| *id );
+        for (id, data) in textures {
+            writeln!(writer, "filename: {}", id)?;
+            write_uuencoded(&mut writer, data)?;
+        }
+    }
+    Ok(warnings)
+}
+fn write_uuencoded<W>(writer: &mut W, data: &ResourceData) -> io::Result<()>
+    where W: Write {
+    let data = data.data().map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()) )?;
+    for line in uuencode(data).as_bytes().chunks(80) {
+        writer.write_all(line)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+fn single_line(value: &str) -> String {
+    value.replace("\r\n", " ").replace(&['\n', '\r'][..], " ")
+}
+
+
+// Objects conversion
+struct ObjectsConverter<'a> {
+    pos: Option<(usize, usize)>,
+    duration: u32,
+    warnings: &'a mut Vec<ConversionWarning>,
+    text: String,
+    tags: String,
+    drawing: bool
+}
+impl<'a> ObjectsConverter<'a> {
+    fn new(pos: Option<(usize, usize)>, duration: u32, warnings: &'a mut Vec<ConversionWarning>) -> Self {
+        Self {
+            pos,
+            duration,
+            warnings,
+            text: String::new(),
+            tags: String::new(),
+            drawing: false
+        }
+    }
+    fn convert(&mut self, objects: &[EventObject]) {
+        for object in objects {
+            match object {
+                EventObject::GeometryText(text) => {
+                    if self.drawing {
+                        self.tags.push_str("\\p0");
+                        self.drawing = false;
+                    }
+                    self.flush_tags();
+                    // Braces would start override blocks
+                    if text.contains(&['{', '}'][..]) {
+                        self.warn("Replaced braces in text", text);
+                    }
+                    let mut chars = text.chars().peekable();
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\n' => self.text.push_str("\\N"),
+                            '{' => self.text.push('('),
+                            '}' => self.text.push(')'),
+                            // Empty override block keeps backslash from forming an escape sequence
+                            '\\' if matches!(chars.peek(), Some('N') | Some('n') | Some('h')) => self.text.push_str("\\{}"),
+                            _ => self.text.push(c)
+                        }
+                    }
+                }
+                EventObject::GeometryShape(segments) => {
+                    if !self.drawing {
+                        self.tags.push_str("\\p1");
+                        self.drawing = true;
+                    }
+                    self.flush_tags();
+                    let drawing = self.shape_drawing(segments);
+                    self.text.push_str(&drawing);
+                }
+                EventObject::GeometryPoints(_) => self.warn("Unsupported geometry", &format!("{:?}", object)),
+                _ => if let Some(tag) = self.override_tag(object) {
+                    self.tags.push_str(&tag);
+                }
+            }
+        }
+    }
+    fn finish(mut self) -> String {
+        self.flush_tags();
+        self.text
+    }
+    fn flush_tags(&mut self) {
+        if !self.tags.is_empty() {
+            self.text.push('{');
+            self.text.push_str(&self.tags);
+            self.text.push('}');
+            self.tags.clear();
+        }
+    }
+    fn warn(&mut self, msg: &str, construct: &str) {
+        self.warnings.push(ConversionWarning::new(msg, construct, self.pos));
+    }
+    fn shape_drawing(&mut self, segments: &[ShapeSegment]) -> String {
+        let mut commands = vec![];
+        // Drawings know no arcs
+        for segment in arcs_to_curves(segments) {
+            match segment {
+                ShapeSegment::MoveTo(point) => commands.push(format!("m {} {}", point.x, point.y)),
+                ShapeSegment::LineTo(point) => commands.push(format!("l {} {}", point.x, point.y)),
+                ShapeSegment::CurveTo(point1, point2, point3) => commands.push(format!("b {} {} {} {} {} {}", point1.x, point1.y, point2.x, point2.y, point3.x, point3.y)),
+                // Drawings get closed implicitly
+                ShapeSegment::ArcBy(..) | ShapeSegment::Close => ()
+            }
+        }
+        commands.join(" ")
+    }
+    fn override_tag(&mut self, object: &EventObject) -> Option<String> {
+        let tag = match object {
+            EventObject::TagFont(font) => Some(format!("\\fn{}", font)),
+            EventObject::TagSize(size) => Some(format!("\\fs{}", size)),
+            EventObject::TagBold(bold) => Some(format!("\\b{}", u8::from(*bold))),
+            EventObject::TagItalic(italic) => Some(format!("\\i{}", u8::from(*italic))),
+            EventObject::TagUnderline(underline) => Some(format!("\\u{}", u8::from(*underline))),
+            EventObject::TagStrikeout(strikeout) => Some(format!("\\s{}", u8::from(*strikeout))),
+            EventObject::TagPosition(position) if position.z == 0.0 => Some(format!("\\pos({},{})", position.x, position.y)),
+            EventObject::TagAlignment(Alignment::Numpad(numpad)) => Some(format!("\\an{}", numpad)),
+            EventObject::TagWrapStyle(WrapStyle::Space) => Some("\\q0".to_owned()),
+            EventObject::TagWrapStyle(WrapStyle::NoWrap) => Some("\\q2".to_owned()),
+            EventObject::TagSpace(Space::Horizontal(space)) => Some(format!("\\fsp{}", space)),
+            EventObject::TagRotate(Rotate::X(angle)) => Some(format!("\\frx{}", angle)),
+            EventObject::TagRotate(Rotate::Y(angle)) => Some(format!("\\fry{}", angle)),
+            EventObject::TagRotate(Rotate::Z(angle)) => Some(format!("\\frz{}", angle)),
+            EventObject::TagScale(Scale::X(scale)) => Some(format!("\\fscx{}", scale * 100.0)),
+            EventObject::TagScale(Scale::Y(scale)) => Some(format!("\\fscy{}", scale * 100.0)),
+            EventObject::TagScale(Scale::All(scale_x, scale_y, scale_z)) if *scale_z == 1.0 => Some(format!("\\fscx{}\\fscy{}", scale_x * 100.0, scale_y * 100.0)),
+            EventObject::TagShear(Shear::X(shear)) => Some(format!("\\fax{}", shear)),
+            EventObject::TagShear(Shear::Y(shear)) => Some(format!("\\fay{}", shear)),
+            EventObject::TagShear(Shear::All(shear_x, shear_y)) => Some(format!("\\fax{}\\fay{}", shear_x, shear_y)),
+            EventObject::TagBorder(Border::All(horizontal, vertical)) if horizontal == vertical => Some(format!("\\bord{}", horizontal)),
+            EventObject::TagBorder(Border::All(horizontal, vertical)) => Some(format!("\\xbord{}\\ybord{}", horizontal, vertical)),
+            EventObject::TagBorder(Border::Horizontal(horizontal)) => Some(format!("\\xbord{}", horizontal)),
+            EventObject::TagBorder(Border::Vertical(vertical)) => Some(format!("\\ybord{}", vertical)),
+            EventObject::TagBlur(Blur::All(horizontal, vertical)) if horizontal == vertical => Some(format!("\\blur{}", horizontal)),
+            EventObject::TagReset => Some("\\r".to_owned()),
+            EventObject::TagColor(Color::Mono(rgb)) => Some(format!("\\1c{}", ass_color(rgb))),
+            EventObject::TagBorderColor(Color::Mono(rgb)) => Some(format!("\\3c{}", ass_color(rgb))),
+            EventObject::TagKaraokeColor(rgb) => Some(format!("\\2c{}", ass_color(rgb))),
+            EventObject::TagAlpha(Alpha::Mono(alpha)) => Some(format!("\\1a&H{:02X}&", 255 - alpha)),
+            EventObject::TagBorderAlpha(Alpha::Mono(alpha)) => Some(format!("\\3a&H{:02X}&", 255 - alpha)),
+            EventObject::TagKaraoke(duration) => Some(format!("\\k{}", (duration + 5) / 10)),
+            EventObject::TagAnimate(animate) => self.animation(animate),
+            _ => None
+        };
+        if tag.is_none() {
+            self.warn("Unsupported tag", &format!("{:?}", object));
+        }
+        tag
+    }
+    fn animation(&mut self, animate: &Animate) -> Option<String> {
+        // Negative times count from event end, ASS knows just times from event start
+        let mut options = match animate.time {
+            Some((start_time, end_time)) => {
+                let resolve = |time: i32| if time < 0 {i64::from(self.duration) + i64::from(time)} else {i64::from(time)};
+                let (start_time, end_time) = (resolve(start_time), resolve(end_time));
+                if start_time < 0 || end_time < 0 {
+                    return None;
+                }
+                format!("{},{},", start_time, end_time)
+            }
+            None => String::new()
+        };
+        // Only exponential progression has an ASS counterpart
+        if let Some(formula) = &animate.formula {
+            options += &format!("{},", formula.source().strip_prefix("t^").and_then(|accel| accel.trim().parse::<f32>().ok() ).filter(|accel| *accel > 0.0 )?);
+        }
+        let mut tags = String::new();
+        for tag in &animate.tags {
+            match tag {
+                // Not animatable in ASS
+                EventObject::TagFont(_) | EventObject::TagBold(_) | EventObject::TagItalic(_) | EventObject::TagUnderline(_) | EventObject::TagStrikeout(_) |
+                EventObject::TagPosition(_) | EventObject::TagAlignment(_) | EventObject::TagWrapStyle(_) | EventObject::TagReset | EventObject::TagKaraoke(_) |
+                EventObject::TagAnimate(_) => self.warn("Unsupported animated tag", &format!("{:?}", tag)),
+                _ => if let Some(tag) = self.override_tag(tag) {
+                    tags += &tag;
+                }
+            }
+        }
+        Some(format!("\\t({}{})", options, tags)).filter(|_| !tags.is_empty() )
+    }
+}
+
+
+// Value conversions
+fn number(value: &str, divisor: f32) -> Result<String, ()> {
+    value.trim().parse::<f32>().map(|value| (value / divisor).to_string() ).map_err(|_| () )
+}
+fn integer(value: &str) -> Result<i64, ()> {
+    value.trim().parse::<f64>().map(|value| value.round() as i64 ).map_err(|_| () )
+}
+fn ass_hex(value: &str) -> Result<u32, ()> {
+    let value = value.trim().trim_end_matches('&');
+    if let Some(hex) = value.strip_prefix("&H").or_else(|| value.strip_prefix("&h") ) {
+        u32::from_str_radix(hex, 16).map_err(|_| () )
+    } else {
+        value.parse::<i64>().map(|value| value as u32 ).map_err(|_| () )
+    }
+}
+pub(crate) fn color(value: &str) -> Result<(String, String), ()> {
+    // Layout AABBGGRR with inverted alpha
+    let bytes = ass_hex(value)?.to_le_bytes();
+    Ok((format!("{:02X}{:02X}{:02X}", bytes[0], bytes[1], bytes[2]), format!("{:02X}", 255 - bytes[3])))
+}
+fn alpha(value: &str) -> Result<String, ()> {
+    ass_hex(value).and_then(|alpha| u8::try_from(alpha).map_err(|_| () )).map(|alpha| format!("{:02X}", 255 - alpha) )
+}
+fn alignment(value: &str, legacy: bool) -> Result<u8, ()> {
+    match (value.trim().parse::<u8>().map_err(|_| () )?, legacy) {
+        (alignment @ 1..=9, false) | (alignment @ 1..=3, true) => Ok(alignment),
+        // Legacy alignment counts top row as 5-7 and middle row as 9-11
+        (alignment @ 5..=7, true) => Ok(alignment + 2),
+        (alignment @ 9..=11, true) => Ok(alignment - 5),
+        _ => Err(())
+    }
+}
+fn drawing(commands: &str, scale: f32) -> Result<String, ()> {
+    commands.split_ascii_whitespace()
+        .map(|token| match token {
+            "m" | "n" => Ok("m".to_owned()),
+            "l" | "b" | "c" => Ok(token.to_owned()),
+            _ => token.parse::<f32>().map(|coordinate| (coordinate / scale).to_string() ).map_err(|_| () )
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|tokens| tokens.join(" ") )
+}
+fn ass_color(rgb: &Rgb) -> String {
+    format!("&H{:02X}{:02X}{:02X}&", rgb[2], rgb[1], rgb[0])
+}
+fn ass_time(time: u32) -> String {
+    // Format H:MM:SS.cc
+    format!("{}:{:02}:{:02}.{:02}", time / 3_600_000, time / 60_000 % 60, time / 1000 % 60, time % 1000 / 10)
+}
+fn parse_time(value: &str) -> Result<u32, ParseError> {
+    // Format H:MM:SS.cc
+    let invalid = || ParseError::new(&format!("Invalid time '{}'!", value));
+    let mut tokens = value.splitn(3, ':');
+    let mut number = || tokens.next().and_then(|token| token.parse::<u32>().ok() ).ok_or_else(invalid);
+    let (hours, minutes) = (number()?, number()?);
+    let seconds = tokens.next().ok_or_else(invalid)?;
+    let (seconds, fraction) = seconds.find('.').map_or((seconds, ""), |pos| (&seconds[..pos], &seconds[pos + 1..]) );
+    let (seconds, milliseconds) = (
+        seconds.parse::<u32>().map_err(|_| invalid() )?,
+        format!("{:0<3}", fraction).get(..3).and_then(|milliseconds| milliseconds.parse::<u32>().ok() ).ok_or_else(invalid)?
+    );
+    hours.checked_mul(60).and_then(|time| time.checked_add(minutes) )
+        .and_then(|time| time.checked_mul(60) ).and_then(|time| time.checked_add(seconds) )
+        .and_then(|time| time.checked_mul(1000) ).and_then(|time| time.checked_add(milliseconds) )
+        .ok_or_else(|| ParseError::new(&format!("Time '{}' too large!", value)) )
+}
+pub(crate) fn uudecode(data: &str) -> Result<Vec<u8>, ()> {
+    // Characters represent 6 bits, offset by 33
+    let values = data.bytes().map(|byte| if (33..=96).contains(&byte) {Ok(byte - 33)} else {Err(())} ).collect::<Result<Vec<u8>, _>>()?;
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, value)| bits | (u32::from(*value) << (18 - index * 6)) ).to_be_bytes();
+        match chunk.len() {
+            4 => bytes.extend_from_slice(&bits[1..4]),
+            3 => bytes.extend_from_slice(&bits[1..3]),
+            2 => bytes.push(bits[1]),
+            _ => return Err(())
+        }
+    }
+    Ok(bytes)
+}
+
+fn uuencode(data: &[u8]) -> String {
+    let mut text = String::with_capacity(data.len() / 3 * 4 + 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, byte)| bits | (u32::from(*byte) << (16 - index * 8)) );
+        for index in 0..=chunk.len() {
+            text.push(char::from(((bits >> (18 - index * 6)) & 0x3F) as u8 + 33));
+        }
+    }
+    text
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{uudecode, uuencode, font_face, color, ass_color, alignment, drawing, ass_time, parse_time, split_override_tags, FontStyle};
+
+    #[test]
+    fn convert_values() {
+        assert_eq!(uudecode("77^V;W%`"), Ok(b"Youka?".to_vec()));
+        assert_eq!(uudecode("77^V;W%"), Ok(b"Youka".to_vec()));
+        assert_eq!(uudecode("5 "), Err(()));
+        assert_eq!(uuencode(b"Youka?"), "77^V;W%`");
+        assert_eq!(uuencode(b"Youka"), "77^V;W%");
+        assert_eq!(font_face("arial_BI0.ttf").style, FontStyle::BoldItalic);
+        assert_eq!(font_face("Open_Sans.otf").family, "Open_Sans");
+        assert_eq!(color("&H80FF8000"), Ok(("0080FF".to_owned(), "7F".to_owned())));
+        assert_eq!(color("255"), Ok(("FF0000".to_owned(), "FF".to_owned())));
+        assert_eq!(ass_color(&[255, 128, 0]), "&H0080FF&");
+        assert_eq!(alignment("10", true), Ok(5));
+        assert_eq!(alignment("10", false), Err(()));
+        assert_eq!(drawing("m 0 0 n 10 10 l 20 0 c", 2.0), Ok("m 0 0 m 5 5 l 10 0 c".to_owned()));
+        assert_eq!(drawing("m 0 0 s 1 2 3 4 5 6", 1.0), Err(()));
+        assert_eq!(parse_time("1:02:03.45").ok(), Some(3_723_450));
+        assert_eq!(parse_time("0:00:1").ok(), Some(1000));
+        assert_eq!(parse_time("0:00").map_err(|err| err.to_string() ), Err("Invalid time '0:00'!".to_owned()));
+        assert_eq!(parse_time("1194:00:00.00").map_err(|err| err.to_string() ), Err("Time '1194:00:00.00' too large!".to_owned()));
+        assert_eq!(ass_time(3_723_456), "1:02:03.45");
+    }
+
+    #[test]
+    fn split_tags() {
+        assert_eq!(
+            split_override_tags("\\pos(1,2)\\t(0,100,\\frz30\\c&HFF&)\\b1"),
+            vec![(1, "pos(1,2)"), (10, "t(0,100,\\frz30\\c&HFF&)"), (33, "b1")]
+        );
+    }
+}
\ No newline at end of file
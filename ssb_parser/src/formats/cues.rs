@@ -0,0 +1,183 @@
+// Imports
+use crate::{
+    state::error::ConversionWarning,
+    objects::event_objects::EventObject,
+    utils::functions::convert::escape_text
+};
+
+
+// Markup flavours
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Markup {
+    SubRip,
+    WebVtt
+}
+
+
+// Times
+pub(crate) fn parse_cue_time(value: &str) -> Option<u32> {
+    // Format [HH:]MM:SS.mmm (SubRip with comma)
+    let (time, milliseconds) = value.trim().split_at(value.trim().rfind(&['.', ','][..])?);
+    let milliseconds = milliseconds[1 /* . */..].parse::<u32>().ok().filter(|_| milliseconds.len() == 4 )?;
+    let mut units = time.rsplit(':');
+    let (seconds, minutes) = (units.next()?.parse::<u32>().ok()?, units.next()?.parse::<u32>().ok()?);
+    let hours = units.next().map_or(Some(0), |hours| hours.parse::<u32>().ok() )?;
+    if units.next().is_some() || seconds > 59 || minutes > 59 {
+        return None;
+    }
+    hours.checked_mul(60).and_then(|time| time.checked_add(minutes) )
+        .and_then(|time| time.checked_mul(60) ).and_then(|time| time.checked_add(seconds) )
+        .and_then(|time| time.checked_mul(1000) ).and_then(|time| time.checked_add(milliseconds) )
+}
+pub(crate) fn format_cue_time(time: u32, markup: Markup) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        time / 3_600_000, time / 60_000 % 60, time / 1000 % 60,
+        if markup == Markup::SubRip {','} else {'.'},
+        time % 1000
+    )
+}
+
+
+// Markup to event data
+pub(crate) fn markup_to_data(text: &str, markup: Markup, pos: (usize, usize), warnings: &mut Vec<ConversionWarning>) -> String {
+    let mut data = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        // Markup tag
+        if let (Some(0), Some(tag_end)) = (rest.find('<'), rest.find('>')) {
+            let tag = &rest[1..tag_end];
+            let (closing, name) = tag.strip_prefix('/').map_or((false, tag), |name| (true, name) );
+            let name = name.split(|c: char| c == '.' || c.is_whitespace()).next().unwrap_or_default();
+            match (name.to_lowercase().as_str(), markup) {
+                ("b", _) => data.push_str(if closing {"[bold=n]"} else {"[bold=y]"}),
+                ("i", _) => data.push_str(if closing {"[italic=n]"} else {"[italic=y]"}),
+                ("u", _) => data.push_str(if closing {"[underline=n]"} else {"[underline=y]"}),
+                ("s", Markup::SubRip) => data.push_str(if closing {"[strikeout=n]"} else {"[strikeout=y]"}),
+                // Voices & classes only matter for styling by css
+                ("v", Markup::WebVtt) | ("c", Markup::WebVtt) | ("lang", Markup::WebVtt) => (),
+                _ => warnings.push(ConversionWarning::new("Skipped unsupported markup", &rest[..=tag_end], Some(pos)))
+            }
+            rest = &rest[tag_end + 1 /* > */..];
+        // Text
+        } else {
+            let text_end = rest.char_indices().skip(1).find(|(_, c)| *c == '<' ).map_or(rest.len(), |(pos, _)| pos);
+            let text = &rest[..text_end];
+            data.push_str(&escape_text(&if markup == Markup::WebVtt {decode_entities(text)} else {text.to_owned()}));
+            rest = &rest[text_end..];
+        }
+    }
+    data
+}
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&nbsp;", "\u{a0}").replace("&lrm;", "\u{200e}").replace("&rlm;", "\u{200f}").replace("&amp;", "&")
+}
+
+
+// Event objects to markup
+pub(crate) struct MarkupWriter<'a> {
+    markup: Markup,
+    pos: Option<(usize, usize)>,
+    warnings: &'a mut Vec<ConversionWarning>,
+    text: String,
+    // Bold, italic, underline, strikeout
+    style: [bool;4],
+    open_style: [bool;4]
+}
+impl<'a> MarkupWriter<'a> {
+    const STYLE_TAGS: [&'static str;4] = ["b", "i", "u", "s"];
+    pub(crate) fn new(markup: Markup, pos: Option<(usize, usize)>, warnings: &'a mut Vec<ConversionWarning>) -> Self {
+        Self {
+            markup,
+            pos,
+            warnings,
+            text: String::new(),
+            style: [false;4],
+            open_style: [false;4]
+        }
+    }
+    pub(crate) fn write(&mut self, objects: &[EventObject]) {
+        for object in objects {
+            match object {
+                EventObject::GeometryText(text) => {
+                    self.update_style();
+                    let text = match self.markup {
+                        Markup::SubRip => text.to_owned(),
+                        Markup::WebVtt => text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+                    };
+                    self.text.push_str(&text);
+                }
+                EventObject::TagBold(bold) => self.style[0] = *bold,
+                EventObject::TagItalic(italic) => self.style[1] = *italic,
+                EventObject::TagUnderline(underline) => self.style[2] = *underline,
+                EventObject::TagStrikeout(strikeout) if self.markup == Markup::SubRip => self.style[3] = *strikeout,
+                EventObject::TagReset => self.style = [false;4],
+                _ => self.warnings.push(ConversionWarning::new("Skipped unsupported object", &format!("{:?}", object), self.pos))
+            }
+        }
+    }
+    pub(crate) fn finish(mut self) -> String {
+        self.style = [false;4];
+        self.update_style();
+        // Empty lines would end cues
+        let mut text = self.text.replace("\r\n", "\n");
+        while text.contains("\n\n") {
+            text = text.replace("\n\n", "\n");
+        }
+        text.trim_matches('\n').to_owned()
+    }
+    fn update_style(&mut self) {
+        if self.style != self.open_style {
+            // Close in reverse order for proper nesting, reopen still required ones
+            for (index, tag) in Self::STYLE_TAGS.iter().enumerate().rev() {
+                if self.open_style[index] {
+                    self.text.push_str(&format!("</{}>", tag));
+                }
+            }
+            for (index, tag) in Self::STYLE_TAGS.iter().enumerate() {
+                if self.style[index] {
+                    self.text.push_str(&format!("<{}>", tag));
+                }
+            }
+            self.open_style = self.style;
+        }
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{parse_cue_time, format_cue_time, markup_to_data, MarkupWriter, Markup, EventObject};
+
+    #[test]
+    fn cue_times() {
+        assert_eq!(parse_cue_time("01:02:03,456"), Some(3_723_456));
+        assert_eq!(parse_cue_time("02:03.456"), Some(123_456));
+        assert_eq!(parse_cue_time("123:00:00.000"), Some(442_800_000));
+        assert_eq!(parse_cue_time("00:60:00.000"), None);
+        assert_eq!(parse_cue_time("00:00.5"), None);
+        assert_eq!(parse_cue_time("1194:00:00.000"), None);
+        assert_eq!(format_cue_time(3_723_456, Markup::SubRip), "01:02:03,456");
+        assert_eq!(format_cue_time(123_456, Markup::WebVtt), "00:02:03.456");
+    }
+
+    #[test]
+    fn markup() {
+        let mut warnings = vec![];
+        assert_eq!(markup_to_data("<b>A&amp;</b>\n<font color=\"red\">[B]</font>", Markup::WebVtt, (0, 0), &mut warnings), "[bold=y]A&[bold=n]\\n\\[B\\]");
+        assert_eq!(warnings.len(), 2);
+        let mut writer = MarkupWriter::new(Markup::WebVtt, None, &mut warnings);
+        writer.write(&[
+            EventObject::TagItalic(true),
+            EventObject::GeometryText("A<".to_owned()),
+            EventObject::TagBold(true),
+            EventObject::GeometryText("B\n\nC".to_owned()),
+            EventObject::TagItalic(false),
+            EventObject::TagStrikeout(true),
+            EventObject::GeometryText("D".to_owned())
+        ]);
+        assert_eq!(writer.finish(), "<i>A&lt;</i><b><i>B\nC</i></b><b>D</b>");
+        assert_eq!(warnings.len(), 3);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+// Imports
+use crate::{
+    state::error::ConversionWarning,
+    objects::ssb_objects::{EventTrigger,Framerate},
+    parsers::ssb::Ssb
+};
+
+
+// Other subtitle formats.
+pub mod ass;
+pub mod srt;
+pub mod vtt;
+mod cues;
+
+
+// Event properties lost by rendering preparation but useful for output (note & data location)
+pub(crate) type EventMeta<'a> = (Option<&'a str>, Option<(usize, usize)>);
+pub(crate) fn event_meta(ssb: &Ssb) -> Vec<EventMeta<'_>> {
+    ssb.events.iter().map(|event| (event.note.as_deref(), Some(event.data_location)) ).collect()
+}
+// Time range of event for formats without other triggers, skips (with warning) if not resolvable
+pub(crate) fn event_times(trigger: &EventTrigger, framerate: Option<&Framerate>, pos: Option<(usize, usize)>, warnings: &mut Vec<ConversionWarning>) -> Option<(u32, u32)> {
+    let times = trigger.time_range(framerate);
+    match trigger {
+        EventTrigger::Id(id) => warnings.push(ConversionWarning::new("Skipped id-triggered event", id, pos)),
+        EventTrigger::Frame((start_frame, end_frame)) if times.is_none() => warnings.push(ConversionWarning::new("Skipped frame-triggered event without framerate", &format!("{}-{}", start_frame, end_frame), pos)),
+        EventTrigger::Timecode((start_timecode, end_timecode)) if times.is_none() => warnings.push(ConversionWarning::new("Skipped timecode-triggered event without valid framerate", &format!("{}-{}", start_timecode, end_timecode), pos)),
+        _ => ()
+    }
+    times
+}
\ No newline at end of file
@@ -0,0 +1,130 @@
+// Imports
+use crate::{
+    state::error::{ParseError,ConversionWarning},
+    objects::ssb_objects::{Event,EventTrigger},
+    parsers::{
+        ssb::Ssb,
+        ssb_render::SsbRender
+    },
+    formats::{
+        cues::{parse_cue_time,format_cue_time,markup_to_data,MarkupWriter,Markup},
+        event_meta,
+        event_times,
+        EventMeta
+    }
+};
+use std::{
+    convert::TryFrom,
+    io::{self,BufRead,Write}
+};
+
+
+// Constants
+const TIMING_SEPARATOR: &str = "-->";
+
+
+impl Ssb {
+    /// Convert SubRip (.srt) input into SSB data.
+    ///
+    /// Cues become time-triggered events, `<b>`, `<i>`, `<u>` & `<s>` markup becomes tags.
+    /// Skipped markup and invalid cues get reported by warnings.
+    pub fn from_srt<R>(reader: R) -> Result<(Self, Vec<ConversionWarning>), ParseError>
+        where R: BufRead {
+        let mut ssb = Self::default();
+        let mut warnings = vec![];
+        let mut block: Vec<(usize, String)> = vec![];
+        for (line_index, line) in reader.lines().enumerate() {
+            // Check for valid UTF-8 and remove carriage return & byte order mark
+            let mut line = line?;
+            if line.ends_with('\r') {line.pop();}
+            let line = line.trim_start_matches('\u{feff}');
+            // Cues are separated by empty lines
+            if line.trim().is_empty() {
+                convert_block(&mut ssb, &block, &mut warnings);
+                block.clear();
+            } else {
+                block.push((line_index, line.to_owned()));
+            }
+        }
+        convert_block(&mut ssb, &block, &mut warnings);
+        Ok((ssb, warnings))
+    }
+    /// Convert SSB data into SubRip output.
+    ///
+    /// Works like `SsbRender::write_srt` but warnings point to the event data locations.
+    pub fn write_srt<W>(&self, writer: W) -> Result<Vec<ConversionWarning>, ParseError>
+        where W: Write {
+        Ok(write_srt_cues(writer, &SsbRender::try_from(self.clone())?, &event_meta(self))?)
+    }
+}
+impl SsbRender {
+    /// Convert SSB render data into SubRip output.
+    ///
+    /// Time-triggered events become cues with text flattened and styled by `<b>`, `<i>`, `<u>` & `<s>` markup.
+    /// Other objects get skipped and reported by warnings.
+    pub fn write_srt<W>(&self, writer: W) -> io::Result<Vec<ConversionWarning>>
+        where W: Write {
+        write_srt_cues(writer, self, &vec![(None, None); self.events.len()])
+    }
+}
+
+
+// Helpers
+fn convert_block(ssb: &mut Ssb, block: &[(usize, String)], warnings: &mut Vec<ConversionWarning>) {
+    // Optional counter before timing
+    let timing_index = match block.iter().position(|(_, line)| line.contains(TIMING_SEPARATOR) ) {
+        Some(timing_index) if timing_index <= 1 => timing_index,
+        _ => {
+            if let Some((line_index, line)) = block.first() {
+                warnings.push(ConversionWarning::new("Skipped block without timing", line, Some((*line_index, 0))));
+            }
+            return;
+        }
+    };
+    let (line_index, timing) = &block[timing_index];
+    let mut times = timing.splitn(2, TIMING_SEPARATOR);
+    let start_time = times.next().and_then(parse_cue_time);
+    let mut end_tokens = times.next().unwrap_or_default().split_whitespace();
+    let (start_time, end_time) = match (start_time, end_tokens.next().and_then(parse_cue_time)) {
+        (Some(start_time), Some(end_time)) if start_time <= end_time => (start_time, end_time),
+        _ => {
+            warnings.push(ConversionWarning::new("Skipped cue with invalid timing", timing, Some((*line_index, 0))));
+            return;
+        }
+    };
+    // Text box coordinates (X1:... Y2:...) aren't widely supported
+    let coordinates = end_tokens.collect::<Vec<_>>();
+    if !coordinates.is_empty() {
+        warnings.push(ConversionWarning::new("Skipped cue coordinates", &coordinates.join(" "), Some((*line_index, 0))));
+    }
+    // Text
+    let text_location = (line_index + 1, 0);
+    let text = block[timing_index + 1..].iter().map(|(_, line)| line.as_str() ).collect::<Vec<_>>().join("\n");
+    let data = markup_to_data(&text, Markup::SubRip, text_location, warnings);
+    ssb.events.push(Event {
+        trigger: EventTrigger::Time((start_time, end_time)),
+        macro_name: None,
+        note: None,
+        data,
+        data_location: text_location
+    });
+}
+fn write_srt_cues<W>(mut writer: W, render: &SsbRender, events: &[EventMeta]) -> io::Result<Vec<ConversionWarning>>
+    where W: Write {
+    let mut warnings = vec![];
+    let mut counter = 0;
+    for (event, (_, pos)) in render.events.iter().zip(events) {
+        if let Some((start_time, end_time)) = event_times(&event.trigger, render.target_framerate.as_ref(), *pos, &mut warnings) {
+            let mut markup_writer = MarkupWriter::new(Markup::SubRip, *pos, &mut warnings);
+            markup_writer.write(&event.objects);
+            counter += 1;
+            writeln!(
+                writer, "{}\n{} {} {}\n{}\n",
+                counter,
+                format_cue_time(start_time, Markup::SubRip), TIMING_SEPARATOR, format_cue_time(end_time, Markup::SubRip),
+                markup_writer.finish()
+            )?;
+        }
+    }
+    Ok(warnings)
+}
\ No newline at end of file
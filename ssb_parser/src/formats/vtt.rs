@@ -0,0 +1,262 @@
+// Imports
+use crate::{
+    state::error::{ParseError,ConversionWarning},
+    objects::{
+        ssb_objects::{Event,EventTrigger},
+        event_objects::{EventObject,Alignment,Numpad}
+    },
+    parsers::{
+        ssb::Ssb,
+        ssb_render::SsbRender
+    },
+    formats::{
+        cues::{parse_cue_time,format_cue_time,markup_to_data,MarkupWriter,Markup},
+        event_meta,
+        event_times,
+        EventMeta
+    }
+};
+use std::{
+    convert::TryFrom,
+    io::{self,BufRead,Write}
+};
+
+
+// Constants
+const SIGNATURE: &str = "WEBVTT";
+const TIMING_SEPARATOR: &str = "-->";
+// Frame size for percentage positions if no target size is given
+const VIEWPORT_WIDTH: u16 = 1920;
+const VIEWPORT_HEIGHT: u16 = 1080;
+
+
+impl Ssb {
+    /// Convert WebVTT (.vtt) input into SSB data.
+    ///
+    /// Cues become time-triggered events with identifiers as notes, `<b>`, `<i>` & `<u>` markup becomes tags.
+    /// Cue settings `align` & `line` become an alignment, percentages of `line` & `position` a position.
+    /// Positions require a target size, missing one gets set to 1920x1080.
+    /// Skipped markup, settings and blocks get reported by warnings.
+    pub fn from_vtt<R>(reader: R) -> Result<(Self, Vec<ConversionWarning>), ParseError>
+        where R: BufRead {
+        let mut ssb = Self::default();
+        let mut warnings = vec![];
+        let mut block: Vec<(usize, String)> = vec![];
+        for (line_index, line) in reader.lines().enumerate() {
+            // Check for valid UTF-8 and remove carriage return & byte order mark
+            let mut line = line?;
+            if line.ends_with('\r') {line.pop();}
+            let line = line.trim_start_matches('\u{feff}');
+            // Check file signature
+            if line_index == 0 {
+                match line.strip_prefix(SIGNATURE) {
+                    Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => (),
+                    _ => return Err(ParseError::new_with_pos("Missing WebVTT signature!", (0, 0)))
+                }
+            }
+            // Blocks are separated by empty lines
+            if line.trim().is_empty() {
+                convert_block(&mut ssb, &block, &mut warnings);
+                block.clear();
+            } else {
+                block.push((line_index, line.to_owned()));
+            }
+        }
+        convert_block(&mut ssb, &block, &mut warnings);
+        Ok((ssb, warnings))
+    }
+    /// Convert SSB data into WebVTT output.
+    ///
+    /// Works like `SsbRender::write_vtt` but keeps event notes (as cue identifiers) and warnings point to the event data locations.
+    pub fn write_vtt<W>(&self, writer: W) -> Result<Vec<ConversionWarning>, ParseError>
+        where W: Write {
+        Ok(write_vtt_cues(writer, &SsbRender::try_from(self.clone())?, &event_meta(self))?)
+    }
+}
+impl SsbRender {
+    /// Convert SSB render data into WebVTT output.
+    ///
+    /// Time-triggered events become cues with text flattened and styled by `<b>`, `<i>` & `<u>` markup.
+    /// First alignment & position of an event become cue settings (positions require a target size).
+    /// Other objects get skipped and reported by warnings.
+    pub fn write_vtt<W>(&self, writer: W) -> io::Result<Vec<ConversionWarning>>
+        where W: Write {
+        write_vtt_cues(writer, self, &vec![(None, None); self.events.len()])
+    }
+}
+
+
+// Import helpers
+fn convert_block(ssb: &mut Ssb, block: &[(usize, String)], warnings: &mut Vec<ConversionWarning>) {
+    let (first_index, first_line) = match block.first() {
+        Some((line_index, line)) => (*line_index, line.as_str()),
+        None => return
+    };
+    // Header, comments & styling
+    let block_type = first_line.split_whitespace().next().unwrap_or_default();
+    match block_type {
+        SIGNATURE if first_index == 0 => return,
+        "NOTE" => return,
+        "STYLE" | "REGION" => {
+            warnings.push(ConversionWarning::new("Skipped unsupported block", block_type, Some((first_index, 0))));
+            return;
+        }
+        _ => ()
+    }
+    // Optional identifier before timing
+    let timing_index = match block.iter().position(|(_, line)| line.contains(TIMING_SEPARATOR) ) {
+        Some(timing_index) if timing_index <= 1 => timing_index,
+        _ => {
+            warnings.push(ConversionWarning::new("Skipped block without timing", first_line, Some((first_index, 0))));
+            return;
+        }
+    };
+    let (line_index, timing) = &block[timing_index];
+    let mut times = timing.splitn(2, TIMING_SEPARATOR);
+    let start_time = times.next().and_then(parse_cue_time);
+    let mut end_tokens = times.next().unwrap_or_default().split_whitespace();
+    let (start_time, end_time) = match (start_time, end_tokens.next().and_then(parse_cue_time)) {
+        (Some(start_time), Some(end_time)) if start_time <= end_time => (start_time, end_time),
+        _ => {
+            warnings.push(ConversionWarning::new("Skipped cue with invalid timing", timing, Some((*line_index, 0))));
+            return;
+        }
+    };
+    // Settings & text
+    let text_location = (line_index + 1, 0);
+    let mut data = cue_settings(&end_tokens.collect::<Vec<_>>(), ssb, (*line_index, 0), warnings);
+    let text = block[timing_index + 1..].iter().map(|(_, line)| line.as_str() ).collect::<Vec<_>>().join("\n");
+    data.push_str(&markup_to_data(&text, Markup::WebVtt, text_location, warnings));
+    ssb.events.push(Event {
+        trigger: EventTrigger::Time((start_time, end_time)),
+        macro_name: None,
+        note: Some(first_line.to_owned()).filter(|_| timing_index == 1 ),
+        data,
+        data_location: text_location
+    });
+}
+fn cue_settings(settings: &[&str], ssb: &mut Ssb, pos: (usize, usize), warnings: &mut Vec<ConversionWarning>) -> String {
+    let (mut column, mut row): (Option<u8>, Option<u8>) = (None, None);
+    let (mut position_x, mut position_y) = (None, None);
+    for setting in settings {
+        let (name, value) = setting.find(':').map_or((*setting, ""), |sep| (&setting[..sep], &setting[sep + 1 /* : */..]) );
+        let mut values = value.splitn(2, ',');
+        let (value, anchor) = (values.next().unwrap_or_default(), values.next());
+        let converted = match (name, value.strip_suffix('%').map(str::parse::<f32>)) {
+            ("align", _) => match value {
+                "start" | "left" => {column = Some(0); true}
+                "center" | "middle" => {column = Some(1); true}
+                "end" | "right" => {column = Some(2); true}
+                _ => false
+            }
+            // Percentage of frame height, anchored by cue box side
+            ("line", Some(Ok(percentage))) => {
+                position_y = Some(percentage);
+                match anchor {
+                    None | Some("start") => {row = Some(0); true}
+                    Some("center") => {row = Some(1); true}
+                    Some("end") => {row = Some(2); true}
+                    _ => false
+                }
+            }
+            // Line number, negative counts from bottom
+            ("line", None) => match value.parse::<i32>() {
+                Ok(line) => {row = Some(if line < 0 {2} else {0}); true}
+                Err(_) => false
+            }
+            ("position", Some(Ok(percentage))) => {position_x = Some(percentage); true}
+            _ => false
+        };
+        if !converted {
+            warnings.push(ConversionWarning::new("Skipped unsupported cue setting", setting, Some(pos)));
+        }
+    }
+    // Build tags
+    let mut tags = vec![];
+    if column.is_some() || row.is_some() {
+        tags.push(format!("alignment={}", row.map_or(1, |row| 7 - row * 3) + column.unwrap_or(1)));
+    }
+    if position_x.is_some() || position_y.is_some() {
+        let width = *ssb.target_width.get_or_insert(VIEWPORT_WIDTH);
+        let height = *ssb.target_height.get_or_insert(VIEWPORT_HEIGHT);
+        tags.push(format!(
+            "position={},{}",
+            position_x.unwrap_or_else(|| f32::from(column.unwrap_or(1)) * 50.0) / 100.0 * f32::from(width),
+            position_y.unwrap_or(100.0) / 100.0 * f32::from(height)
+        ));
+    }
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", tags.join(";"))
+    }
+}
+
+
+// Export helpers
+fn write_vtt_cues<W>(mut writer: W, render: &SsbRender, events: &[EventMeta]) -> io::Result<Vec<ConversionWarning>>
+    where W: Write {
+    let mut warnings = vec![];
+    writeln!(writer, "{}", SIGNATURE)?;
+    for (event, (note, pos)) in render.events.iter().zip(events) {
+        if let Some((start_time, end_time)) = event_times(&event.trigger, render.target_framerate.as_ref(), *pos, &mut warnings) {
+            writeln!(writer)?;
+            // Identifier
+            if let Some(note) = note {
+                if note.contains(TIMING_SEPARATOR) || note.contains(&['\n', '\r'][..]) {
+                    warnings.push(ConversionWarning::new("Skipped invalid cue identifier", note, *pos));
+                } else {
+                    writeln!(writer, "{}", note)?;
+                }
+            }
+            // Timing & settings
+            let (settings, objects) = cue_settings_from_objects(&event.objects, render, *pos, &mut warnings);
+            writeln!(
+                writer, "{} {} {}{}",
+                format_cue_time(start_time, Markup::WebVtt), TIMING_SEPARATOR, format_cue_time(end_time, Markup::WebVtt),
+                settings.iter().map(|setting| format!(" {}", setting) ).collect::<String>()
+            )?;
+            // Text
+            let mut markup_writer = MarkupWriter::new(Markup::WebVtt, *pos, &mut warnings);
+            markup_writer.write(&objects);
+            writeln!(writer, "{}", markup_writer.finish())?;
+        }
+    }
+    Ok(warnings)
+}
+fn cue_settings_from_objects(objects: &[EventObject], render: &SsbRender, pos: Option<(usize, usize)>, warnings: &mut Vec<ConversionWarning>) -> (Vec<String>, Vec<EventObject>) {
+    // Take first alignment & position
+    let (mut alignment, mut position) = (None, None);
+    let mut rest = Vec::with_capacity(objects.len());
+    for object in objects {
+        match object {
+            EventObject::TagAlignment(Alignment::Numpad(numpad)) if alignment.is_none() => alignment = Some(numpad),
+            EventObject::TagPosition(point) if position.is_none() => position = Some(point),
+            _ => rest.push(object.clone())
+        }
+    }
+    // Convert to settings
+    let mut settings = vec![];
+    let (column, row) = match alignment {
+        Some(Numpad::TopLeft) => (0, 0), Some(Numpad::TopCenter) => (1, 0), Some(Numpad::TopRight) => (2, 0),
+        Some(Numpad::MiddleLeft) => (0, 1), Some(Numpad::MiddleCenter) => (1, 1), Some(Numpad::MiddleRight) => (2, 1),
+        Some(Numpad::BottomLeft) => (0, 2), Some(Numpad::BottomCenter) | None => (1, 2), Some(Numpad::BottomRight) => (2, 2)
+    };
+    if alignment.is_some() {
+        settings.push(format!("align:{}", ["start", "center", "end"][column]));
+    }
+    match (position, render.target_width.zip(render.target_height)) {
+        (Some(point), Some((width, height))) => {
+            let percentage = |value: f32, size: u16| (value / f32::from(size) * 10000.0).round() / 100.0;
+            settings.push(format!("position:{}%", percentage(point.x, width)));
+            settings.push(format!("line:{}%{}", percentage(point.y, height), ["", ",center", ",end"][row]));
+        }
+        (Some(point), None) => warnings.push(ConversionWarning::new("Skipped position without target size", &format!("{:?}", point), pos)),
+        (None, _) => match row {
+            0 if alignment.is_some() => settings.push("line:0".to_owned()),
+            1 => settings.push("line:50%,center".to_owned()),
+            _ => ()
+        }
+    }
+    (settings, rest)
+}
\ No newline at end of file
@@ -0,0 +1,427 @@
+// Imports
+use crate::objects::event_objects::{ShapeSegment,Point2D,Coordinate,Degree};
+use std::f32::consts::FRAC_PI_2;
+
+
+/// Open or closed sequence of connected points.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Polyline {
+    pub points: Vec<Point2D>,
+    pub closed: bool
+}
+impl Polyline {
+    /// Signed area (filling closes implicitly), positive for clockwise winding on screen (y pointing down).
+    pub fn area(&self) -> f32 {
+        self.edges().map(|(a, b)| a.x * b.y - b.x * a.y ).sum::<f32>() / 2.0
+    }
+    /// Winding number of point (non-zero for inside), signed like area.
+    pub fn winding_number(&self, point: &Point2D) -> i32 {
+        self.edges().map(|(a, b)| {
+            let side = (b.x - a.x) * (point.y - a.y) - (point.x - a.x) * (b.y - a.y);
+            if a.y <= point.y {
+                if b.y > point.y && side > 0.0 {1} else {0}
+            } else if b.y <= point.y && side < 0.0 {
+                -1
+            } else {
+                0
+            }
+        }).sum()
+    }
+    fn edges(&self) -> impl Iterator<Item = (&Point2D, &Point2D)> {
+        self.points.iter().zip(self.points.iter().cycle().skip(1))
+    }
+}
+
+// Drawing segments with start point, started by move or previous close
+struct Subpath<'a> {
+    start: Point2D,
+    segments: Vec<&'a ShapeSegment>,
+    closed: bool
+}
+fn subpaths(segments: &[ShapeSegment]) -> Vec<Subpath<'_>> {
+    let mut subpaths = vec![];
+    let mut current = Subpath {start: Point2D {x: 0.0, y: 0.0}, segments: vec![], closed: false};
+    let mut current_point = current.start.clone();
+    for segment in segments {
+        match segment {
+            ShapeSegment::MoveTo(point) => {
+                let next = Subpath {start: point.clone(), segments: vec![], closed: false};
+                subpaths.push(std::mem::replace(&mut current, next));
+                current_point = point.clone();
+            }
+            ShapeSegment::Close => {
+                current.closed = true;
+                current_point = current.start.clone();
+                let next = Subpath {start: current_point.clone(), segments: vec![], closed: false};
+                subpaths.push(std::mem::replace(&mut current, next));
+            }
+            _ => {
+                current_point = segment_end(&current_point, segment);
+                current.segments.push(segment);
+            }
+        }
+    }
+    subpaths.push(current);
+    // Without drawing nothing to see
+    subpaths.retain(|subpath| !subpath.segments.is_empty() );
+    subpaths
+}
+pub(crate) fn segment_end(from: &Point2D, segment: &ShapeSegment) -> Point2D {
+    match segment {
+        ShapeSegment::MoveTo(point) | ShapeSegment::LineTo(point) | ShapeSegment::CurveTo(_, _, point) => point.clone(),
+        ShapeSegment::ArcBy(center, angle) => rotate(from, center, angle.to_radians()),
+        ShapeSegment::Close => from.clone()
+    }
+}
+fn rotate(point: &Point2D, center: &Point2D, radians: f32) -> Point2D {
+    let (sin, cos) = radians.sin_cos();
+    let (dx, dy) = (point.x - center.x, point.y - center.y);
+    Point2D {
+        x: center.x + dx * cos - dy * sin,
+        y: center.y + dx * sin + dy * cos
+    }
+}
+fn cubic_point(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D, t: f32) -> Point2D {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    Point2D {
+        x: a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+        y: a * p0.y + b * p1.y + c * p2.y + d * p3.y
+    }
+}
+
+/// Approximate shape by polylines, deviating at most by tolerance from curves & arcs.
+///
+/// Arcs rotate the current point around their center, positive angles clockwise on screen.
+/// Subpaths without drawing segments get dropped.
+pub fn flatten(segments: &[ShapeSegment], tolerance: Coordinate) -> Vec<Polyline> {
+    let tolerance = tolerance.max(Coordinate::EPSILON);
+    subpaths(segments).into_iter().map(|subpath| {
+        let mut points = vec![subpath.start];
+        for segment in subpath.segments {
+            let from = points.last().expect("Start point always exists!").clone();
+            match segment {
+                ShapeSegment::LineTo(point) => points.push(point.clone()),
+                ShapeSegment::CurveTo(point1, point2, point3) => flatten_curve(&from, point1, point2, point3, tolerance, 0, &mut points),
+                ShapeSegment::ArcBy(center, angle) => {
+                    let radius = (from.x - center.x).hypot(from.y - center.y);
+                    let radians = angle.to_radians();
+                    // Maximal step angle keeping chord distance to arc within tolerance
+                    let step = if radius > tolerance {2.0 * (1.0 - tolerance / radius).acos()} else {FRAC_PI_2};
+                    let steps = (radians.abs() / step).ceil().max(1.0).min(1024.0) as usize;
+                    points.extend((1..=steps).map(|index| rotate(&from, center, radians * index as f32 / steps as f32) ));
+                }
+                ShapeSegment::MoveTo(_) | ShapeSegment::Close => ()
+            }
+        }
+        Polyline {
+            points,
+            closed: subpath.closed
+        }
+    }).collect()
+}
+fn flatten_curve(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D, tolerance: Coordinate, depth: u8, points: &mut Vec<Point2D>) {
+    // Control points near chord make the curve flat enough
+    let (chord_x, chord_y) = (p3.x - p0.x, p3.y - p0.y);
+    let chord_length = chord_x.hypot(chord_y);
+    let distance = |point: &Point2D| if chord_length > Coordinate::EPSILON {
+        ((point.x - p0.x) * chord_y - (point.y - p0.y) * chord_x).abs() / chord_length
+    } else {
+        (point.x - p0.x).hypot(point.y - p0.y)
+    };
+    if depth >= 16 || distance(p1).max(distance(p2)) <= tolerance {
+        points.push(p3.clone());
+        return;
+    }
+    // Split by de Casteljau in halves
+    let middle = |a: &Point2D, b: &Point2D| Point2D {x: (a.x + b.x) / 2.0, y: (a.y + b.y) / 2.0};
+    let (p01, p12, p23) = (middle(p0, p1), middle(p1, p2), middle(p2, p3));
+    let (p012, p123) = (middle(&p01, &p12), middle(&p12, &p23));
+    let p0123 = middle(&p012, &p123);
+    flatten_curve(p0, &p01, &p012, &p0123, tolerance, depth + 1, points);
+    flatten_curve(&p0123, &p123, &p23, p3, tolerance, depth + 1, points);
+}
+
+/// Exact bounds (minimum & maximum point) of drawn shape, none for no drawing segments.
+pub fn bounding_box(segments: &[ShapeSegment]) -> Option<(Point2D, Point2D)> {
+    let mut points = vec![];
+    for subpath in subpaths(segments) {
+        let mut from = subpath.start;
+        points.push(from.clone());
+        for segment in subpath.segments {
+            match segment {
+                ShapeSegment::CurveTo(point1, point2, point3) => {
+                    // Extrema where derivative of an axis becomes zero
+                    let axis_roots = |p0: Coordinate, p1: Coordinate, p2: Coordinate, p3: Coordinate| {
+                        let (a, b, c) = (-p0 + 3.0 * p1 - 3.0 * p2 + p3, 2.0 * (p0 - 2.0 * p1 + p2), p1 - p0);
+                        quadratic_roots(a, b, c)
+                    };
+                    let mut roots = axis_roots(from.x, point1.x, point2.x, point3.x);
+                    roots.extend(axis_roots(from.y, point1.y, point2.y, point3.y));
+                    points.extend(
+                        roots.into_iter()
+                        .filter(|t| *t > 0.0 && *t < 1.0)
+                        .map(|t| cubic_point(&from, point1, point2, point3, t) )
+                    );
+                }
+                ShapeSegment::ArcBy(center, angle) => {
+                    // Extrema where arc crosses axes through center
+                    let radius = (from.x - center.x).hypot(from.y - center.y);
+                    let start = (from.y - center.y).atan2(from.x - center.x);
+                    let end = start + angle.to_radians();
+                    let (low, high) = (start.min(end), start.max(end));
+                    let mut quarter = (low / FRAC_PI_2).ceil();
+                    while quarter * FRAC_PI_2 <= high {
+                        let (sin, cos) = (quarter * FRAC_PI_2).sin_cos();
+                        points.push(Point2D {x: center.x + radius * cos.round(), y: center.y + radius * sin.round()});
+                        quarter += 1.0;
+                    }
+                }
+                _ => ()
+            }
+            from = segment_end(&from, segment);
+            points.push(from.clone());
+        }
+    }
+    let first = points.first()?.clone();
+    Some(points.iter().fold((first.clone(), first), |(min, max), point| (
+        Point2D {x: min.x.min(point.x), y: min.y.min(point.y)},
+        Point2D {x: max.x.max(point.x), y: max.y.max(point.y)}
+    )))
+}
+fn quadratic_roots(a: Coordinate, b: Coordinate, c: Coordinate) -> Vec<Coordinate> {
+    if a.abs() <= Coordinate::EPSILON {
+        return if b.abs() <= Coordinate::EPSILON {vec![]} else {vec![-c / b]};
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        vec![]
+    } else {
+        let root = discriminant.sqrt();
+        vec![(-b + root) / (2.0 * a), (-b - root) / (2.0 * a)]
+    }
+}
+
+/// Apply 4x4 matrix (row-major, like `TagMatrix`) on shape in plane z=0, with perspective division.
+///
+/// Arcs become curves because they don't stay circular under general transformations.
+pub fn transform(segments: &[ShapeSegment], matrix: &[Degree;16]) -> Vec<ShapeSegment> {
+    let apply = |point: &Point2D| {
+        let w = matrix[12] * point.x + matrix[13] * point.y + matrix[15];
+        let w = if w.abs() > Coordinate::EPSILON {w} else {1.0};
+        Point2D {
+            x: (matrix[0] * point.x + matrix[1] * point.y + matrix[3]) / w,
+            y: (matrix[4] * point.x + matrix[5] * point.y + matrix[7]) / w
+        }
+    };
+    arcs_to_curves(segments).into_iter().map(|segment| match segment {
+        ShapeSegment::MoveTo(point) => ShapeSegment::MoveTo(apply(&point)),
+        ShapeSegment::LineTo(point) => ShapeSegment::LineTo(apply(&point)),
+        ShapeSegment::CurveTo(point1, point2, point3) => ShapeSegment::CurveTo(apply(&point1), apply(&point2), apply(&point3)),
+        other => other
+    }).collect()
+}
+
+/// Replace arcs by cubic curves (one per quarter circle at most), keeping everything else.
+pub fn arcs_to_curves(segments: &[ShapeSegment]) -> Vec<ShapeSegment> {
+    let mut result = vec![];
+    let mut current_point = Point2D {x: 0.0, y: 0.0};
+    let mut start_point = current_point.clone();
+    for segment in segments {
+        match segment {
+            ShapeSegment::ArcBy(center, angle) => result.extend(
+                arc_to_curves(&current_point, center, *angle).into_iter()
+                .map(|(point1, point2, point3)| ShapeSegment::CurveTo(point1, point2, point3) )
+            ),
+            ShapeSegment::MoveTo(point) => {
+                start_point = point.clone();
+                result.push(segment.clone());
+            }
+            _ => result.push(segment.clone())
+        }
+        current_point = if *segment == ShapeSegment::Close {start_point.clone()} else {segment_end(&current_point, segment)};
+    }
+    result
+}
+// Cubic curves approximating arc, one per quarter circle at most
+fn arc_to_curves(from: &Point2D, center: &Point2D, angle: Degree) -> Vec<(Point2D, Point2D, Point2D)> {
+    let radius = (from.x - center.x).hypot(from.y - center.y);
+    let start = (from.y - center.y).atan2(from.x - center.x);
+    let radians = angle.to_radians();
+    let pieces = (radians.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = radians / pieces as f32;
+    // Tangent length for circle approximation
+    let handle = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+    (0..pieces).map(|index| {
+        let (start_angle, end_angle) = (start + step * index as f32, start + step * (index + 1) as f32);
+        let ((start_sin, start_cos), (end_sin, end_cos)) = (start_angle.sin_cos(), end_angle.sin_cos());
+        (
+            Point2D {x: center.x + radius * start_cos - handle * start_sin, y: center.y + radius * start_sin + handle * start_cos},
+            Point2D {x: center.x + radius * end_cos + handle * end_sin, y: center.y + radius * end_sin - handle * end_cos},
+            Point2D {x: center.x + radius * end_cos, y: center.y + radius * end_sin}
+        )
+    }).collect()
+}
+
+/// Reverse direction of subpaths, keeping their order.
+pub fn reverse(segments: &[ShapeSegment]) -> Vec<ShapeSegment> {
+    let mut result = vec![];
+    for subpath in subpaths(segments) {
+        let mut points = vec![subpath.start];
+        for segment in &subpath.segments {
+            let end = segment_end(points.last().expect("Start point always exists!"), segment);
+            points.push(end);
+        }
+        result.push(ShapeSegment::MoveTo(points.last().expect("Start point always exists!").clone()));
+        for (segment, from) in subpath.segments.iter().zip(points).rev() {
+            result.push(match segment {
+                ShapeSegment::CurveTo(point1, point2, _) => ShapeSegment::CurveTo(point2.clone(), point1.clone(), from),
+                ShapeSegment::ArcBy(center, angle) => ShapeSegment::ArcBy(center.clone(), -angle),
+                _ => ShapeSegment::LineTo(from)
+            });
+        }
+        if subpath.closed {
+            result.push(ShapeSegment::Close);
+        }
+    }
+    result
+}
+
+/// Close all open subpaths.
+pub fn close(segments: &[ShapeSegment]) -> Vec<ShapeSegment> {
+    let mut result = vec![];
+    for subpath in subpaths(segments) {
+        result.push(ShapeSegment::MoveTo(subpath.start));
+        result.extend(subpath.segments.into_iter().cloned());
+        result.push(ShapeSegment::Close);
+    }
+    result
+}
+
+/// Signed area of filled shape (see `Polyline::area`).
+pub fn area(segments: &[ShapeSegment], tolerance: Coordinate) -> f32 {
+    flatten(segments, tolerance).iter().map(Polyline::area).sum()
+}
+
+/// Winding number of point in filled shape (see `Polyline::winding_number`).
+pub fn winding_number(segments: &[ShapeSegment], point: &Point2D, tolerance: Coordinate) -> i32 {
+    flatten(segments, tolerance).iter().map(|polyline| polyline.winding_number(point) ).sum()
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{Polyline,flatten,bounding_box,transform,arcs_to_curves,reverse,close,area,winding_number};
+    use crate::objects::event_objects::{ShapeSegment,Point2D};
+
+    fn point(x: f32, y: f32) -> Point2D {
+        Point2D {x, y}
+    }
+    fn assert_near(a: f32, b: f32, epsilon: f32) {
+        assert!((a - b).abs() < epsilon, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn polylines() {
+        let square = Polyline {points: vec![point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0)], closed: true};
+        assert_eq!(square.area(), 4.0);
+        assert_eq!(square.winding_number(&point(1.0, 1.0)), 1);
+        assert_eq!(square.winding_number(&point(3.0, 1.0)), 0);
+        let square = Polyline {points: square.points.into_iter().rev().collect(), closed: false};
+        assert_eq!(square.area(), -4.0);
+        assert_eq!(square.winding_number(&point(1.0, 1.0)), -1);
+    }
+
+    #[test]
+    fn flattening() {
+        let shape = vec![
+            ShapeSegment::MoveTo(point(10.0, 0.0)),
+            ShapeSegment::ArcBy(point(0.0, 0.0), 360.0),
+            ShapeSegment::Close,
+            ShapeSegment::LineTo(point(0.0, 5.0)),
+            ShapeSegment::CurveTo(point(0.0, 10.0), point(10.0, 10.0), point(10.0, 5.0))
+        ];
+        let polylines = flatten(&shape, 0.01);
+        assert_eq!(polylines.len(), 2);
+        assert!(polylines[0].closed && !polylines[1].closed);
+        assert_eq!(polylines[1].points[..2], [point(10.0, 0.0), point(0.0, 5.0)]);
+        assert_eq!(polylines[1].points.last(), Some(&point(10.0, 5.0)));
+        // Inscribed polygon loses tolerance along perimeter at most
+        assert_near(polylines[0].area(), std::f32::consts::PI * 100.0, std::f32::consts::PI * 20.0 * 0.01);
+        assert_near(area(&shape[..3], 0.01), polylines[0].area(), 0.01);
+        assert_eq!(winding_number(&shape[..3], &point(5.0, 5.0), 0.01), 1);
+        assert_eq!(winding_number(&shape[..3], &point(9.0, 9.0), 0.01), 0);
+        assert!(polylines[0].points.iter().all(|p| (p.x.hypot(p.y) - 10.0).abs() < 0.01 ));
+    }
+
+    #[test]
+    fn bounds() {
+        assert_eq!(bounding_box(&[ShapeSegment::MoveTo(point(1.0, 1.0))]), None);
+        let (min, max) = bounding_box(&[
+            ShapeSegment::MoveTo(point(0.0, 0.0)),
+            ShapeSegment::CurveTo(point(0.0, 10.0), point(10.0, 10.0), point(10.0, 0.0))
+        ]).unwrap();
+        assert_eq!((min, max.x), (point(0.0, 0.0), 10.0));
+        assert_near(max.y, 7.5, 0.01);
+        let (min, max) = bounding_box(&[
+            ShapeSegment::MoveTo(point(10.0, 0.0)),
+            ShapeSegment::ArcBy(point(0.0, 0.0), -180.0)
+        ]).unwrap();
+        assert_eq!(min.y, -10.0);
+        assert_near(min.x, -10.0, 0.01);
+        assert_near(max.x, 10.0, 0.01);
+        assert_near(max.y, 0.0, 0.01);
+    }
+
+    #[test]
+    fn transformations() {
+        let shape = vec![
+            ShapeSegment::MoveTo(point(1.0, 0.0)),
+            ShapeSegment::ArcBy(point(0.0, 0.0), 90.0),
+            ShapeSegment::LineTo(point(0.0, 0.0)),
+            ShapeSegment::Close
+        ];
+        let scaled = transform(&shape, &[
+            2.0, 0.0, 0.0, 5.0,
+            0.0, 3.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0
+        ]);
+        assert_eq!(scaled.len(), 4);
+        assert_eq!(scaled[0], ShapeSegment::MoveTo(point(7.0, 0.0)));
+        if let ShapeSegment::CurveTo(_, _, end) = &scaled[1] {
+            assert_near(end.x, 5.0, 0.01);
+            assert_near(end.y, 3.0, 0.01);
+        } else {
+            panic!("Arc should become curve!");
+        }
+        assert_eq!(scaled[2..], [ShapeSegment::LineTo(point(5.0, 0.0)), ShapeSegment::Close]);
+        assert_near(area(&scaled, 0.001), area(&shape, 0.001) * 6.0, 0.01);
+        let curved = arcs_to_curves(&shape);
+        assert_eq!(curved.len(), 4);
+        assert_near(area(&curved, 0.001), area(&shape, 0.001), 0.01);
+    }
+
+    #[test]
+    fn directions() {
+        let shape = vec![
+            ShapeSegment::MoveTo(point(0.0, 0.0)),
+            ShapeSegment::LineTo(point(4.0, 0.0)),
+            ShapeSegment::CurveTo(point(5.0, 1.0), point(5.0, 3.0), point(4.0, 4.0)),
+            ShapeSegment::MoveTo(point(10.0, 0.0)),
+            ShapeSegment::ArcBy(point(10.0, 1.0), 90.0)
+        ];
+        let reversed = reverse(&shape);
+        assert_eq!(reversed[..3], [
+            ShapeSegment::MoveTo(point(4.0, 4.0)),
+            ShapeSegment::CurveTo(point(5.0, 3.0), point(5.0, 1.0), point(4.0, 0.0)),
+            ShapeSegment::LineTo(point(0.0, 0.0))
+        ]);
+        assert_eq!(reversed[4], ShapeSegment::ArcBy(point(10.0, 1.0), -90.0));
+        assert_near(area(&reverse(&close(&shape)), 0.01), -area(&shape, 0.01), 0.01);
+        let closed = close(&shape);
+        assert_eq!(closed.iter().filter(|segment| **segment == ShapeSegment::Close ).count(), 2);
+        assert_eq!(close(&closed), closed);
+    }
+}
@@ -0,0 +1,170 @@
+// Imports
+use crate::objects::ssb_objects::{EventRender,EventTrigger,Framerate};
+use std::{
+    collections::HashMap,
+    ops::Range
+};
+
+
+/// Lookup of events by trigger, built once for repeated queries.
+///
+/// Time-triggered events are held in an interval tree (sorted by start time, subtrees know their latest end time),
+/// id-triggered events in a hash map. Queries return positions in the indexed events, ascending (=render order).
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct EventIndex {
+    intervals: Vec<(u32, u32, usize)>,
+    max_ends: Vec<u32>,
+    ids: HashMap<String, Vec<usize>>
+}
+impl EventIndex {
+    /// New index over events (frame-triggered ones get skipped).
+    pub fn new(events: &[EventRender]) -> Self {
+        Self::with_framerate(events, None)
+    }
+    /// New index over events, frame-triggered ones converted to times by framerate.
+    pub fn with_framerate(events: &[EventRender], framerate: Option<&Framerate>) -> Self {
+        let mut intervals = vec![];
+        let mut ids = HashMap::<_, Vec<_>>::new();
+        for (event_index, event) in events.iter().enumerate() {
+            match (&event.trigger, event.trigger.time_range(framerate)) {
+                (EventTrigger::Id(id), _) => ids.entry(id.clone()).or_default().push(event_index),
+                // Empty time ranges are never active
+                (_, Some((start_ms, end_ms))) => if start_ms < end_ms {
+                    intervals.push((start_ms, end_ms, event_index));
+                }
+                (_, None) => ()
+            }
+        }
+        intervals.sort_unstable();
+        let mut max_ends = vec![0; intervals.len()];
+        fill_max_ends(&intervals, &mut max_ends, 0..intervals.len());
+        Self {
+            intervals,
+            max_ends,
+            ids
+        }
+    }
+    /// Events active at time (start inclusive, end exclusive).
+    pub fn at_time(&self, ms: u32) -> Vec<usize> {
+        self.overlapping(ms..ms.saturating_add(1))
+    }
+    /// Events overlapping time range.
+    pub fn overlapping(&self, range: Range<u32>) -> Vec<usize> {
+        let mut found = vec![];
+        if range.start < range.end {
+            self.collect_overlapping(&range, 0..self.intervals.len(), &mut found);
+            found.sort_unstable();
+        }
+        found
+    }
+    /// Events triggered by id.
+    pub fn by_id(&self, id: &str) -> &[usize] {
+        self.ids.get(id).map_or(&[], Vec::as_slice)
+    }
+    // Descend into subtrees which can contain overlaps
+    fn collect_overlapping(&self, query: &Range<u32>, nodes: Range<usize>, found: &mut Vec<usize>) {
+        if nodes.is_empty() {
+            return;
+        }
+        let middle = (nodes.start + nodes.end) >> 1;
+        if self.max_ends[middle] <= query.start {
+            return;
+        }
+        self.collect_overlapping(query, nodes.start..middle, found);
+        let (start_ms, end_ms, event_index) = self.intervals[middle];
+        if start_ms < query.end {
+            if end_ms > query.start {
+                found.push(event_index);
+            }
+            self.collect_overlapping(query, middle + 1..nodes.end, found);
+        }
+    }
+}
+// Latest end time per subtree (implicit by array halves)
+fn fill_max_ends(intervals: &[(u32, u32, usize)], max_ends: &mut [u32], nodes: Range<usize>) -> u32 {
+    if nodes.is_empty() {
+        return 0;
+    }
+    let middle = (nodes.start + nodes.end) >> 1;
+    let max_end = intervals[middle].1
+        .max(fill_max_ends(intervals, max_ends, nodes.start..middle))
+        .max(fill_max_ends(intervals, max_ends, middle + 1..nodes.end));
+    max_ends[middle] = max_end;
+    max_end
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::EventIndex;
+    use crate::objects::ssb_objects::{EventRender,EventTrigger,Framerate};
+
+    fn events(triggers: Vec<EventTrigger>) -> Vec<EventRender> {
+        triggers.into_iter().map(|trigger| EventRender {trigger, objects: vec![]} ).collect()
+    }
+
+    #[test]
+    fn time_and_id() {
+        let index = EventIndex::new(&events(vec![
+            EventTrigger::Time((0, 1000)),
+            EventTrigger::Id("title".to_owned()),
+            EventTrigger::Time((500, 600)),
+            EventTrigger::Time((2000, 3000)),
+            EventTrigger::Time((100, 100)),
+            EventTrigger::Id("title".to_owned()),
+            EventTrigger::Time((0, 5000))
+        ]));
+        assert_eq!(index.at_time(0), vec![0, 6]);
+        assert_eq!(index.at_time(100), vec![0, 6]);
+        assert_eq!(index.at_time(550), vec![0, 2, 6]);
+        assert_eq!(index.at_time(1000), vec![6]);
+        assert_eq!(index.at_time(5000), Vec::<usize>::new());
+        assert_eq!(index.overlapping(600..2001), vec![0, 3, 6]);
+        assert_eq!(index.overlapping(1000..1000), Vec::<usize>::new());
+        assert_eq!(index.by_id("title"), &[1, 5]);
+        assert!(index.by_id("credits").is_empty());
+    }
+
+    #[test]
+    fn frames() {
+        let events = events(vec![
+            EventTrigger::Frame((24, 48)),
+            EventTrigger::Time((500, 1500))
+        ]);
+        assert_eq!(EventIndex::new(&events).at_time(1200), vec![1]);
+        let framerate = Framerate::new(24000, 1001).unwrap();
+        let index = EventIndex::with_framerate(&events, Some(&framerate));
+        assert_eq!(index.at_time(framerate.frame_to_ms(24).unwrap()), vec![0, 1]);
+        assert_eq!(index.at_time(framerate.frame_to_ms(23).unwrap()), vec![1]);
+        assert_eq!(index.at_time(framerate.frame_to_ms(48).unwrap()), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn overflowing_frames() {
+        use crate::{Ssb, SsbRender};
+        use std::{convert::TryFrom, io::Cursor};
+        for script in &[
+            "#TARGET\nFramerate: 100000\n#EVENTS\n00:00:00:00-99:00:00:00|||A",
+            "#TARGET\nFramerate: 1/1000000000\n#EVENTS\n0f-4000000000f|||A"
+        ] {
+            let ssb_render = SsbRender::try_from(Ssb::default().parse_owned(Cursor::new(*script)).unwrap()).unwrap();
+            let index = EventIndex::with_framerate(&ssb_render.events, ssb_render.target_framerate.as_ref());
+            assert_eq!(index.at_time(0), Vec::<usize>::new());
+        }
+    }
+
+    #[test]
+    fn matches_linear_search() {
+        let triggers = (0..200u32).map(|i| EventTrigger::Time((i * 37 % 1000, i * 37 % 1000 + i * 13 % 300))).collect();
+        let events = events(triggers);
+        let index = EventIndex::new(&events);
+        for (start, end) in (0..1400).step_by(7).map(|ms| (ms, ms + 1 + ms % 50) ) {
+            let expected = events.iter().enumerate().filter_map(|(event_index, event)| match event.trigger {
+                EventTrigger::Time((start_ms, end_ms)) if start_ms < end && end_ms > start && start_ms < end_ms => Some(event_index),
+                _ => None
+            }).collect::<Vec<_>>();
+            assert_eq!(index.overlapping(start..end), expected);
+        }
+    }
+}
\ No newline at end of file
@@ -31,16 +31,45 @@ println!("{:#?}", ssb_render);
 /// Objects in SSB.
 pub mod objects;
 
+/// Resolvers of resources referenced by SSB.
+pub mod resolvers;
+
+/// Indices for fast lookup of SSB events.
+pub mod index;
+
+/// Retiming of SSB events.
+pub mod retiming;
+
+/// Resolved styles of SSB event geometries.
+pub mod style;
+
+/// Geometry operations on SSB shapes.
+pub mod geometry;
+
+/// Builders of SSB data.
+pub mod builder;
+
+/// Registry of custom SSB tags.
+pub mod tags;
+
 // States for SSB processing.
 mod state;
-pub use state::error::ParseError;
+pub use state::{
+    error::{ParseError,ConversionWarning,Diagnostic,DiagnosticCode,Severity,Span},
+    limits::{ParseLimits,Limit,LimitError}
+};
 
 // Internal utility structures & functions for data processing.
 mod utils;
 
+// Conversions from & to other subtitle formats.
+mod formats;
+
 // Parsers for different levels of SSB data.
 mod parsers;
 pub use parsers::{
+    ssb_cst::SsbCst,
     ssb::Ssb,
-    ssb_render::SsbRender
+    ssb_events::SsbEventReader,
+    ssb_render::{SsbRender,RenderOptions}
 };
\ No newline at end of file
@@ -0,0 +1,94 @@
+// Imports
+use std::fmt;
+
+
+// Lines
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
+pub struct CstLine {
+    pub kind: CstLineKind,
+    pub ending: Option<LineEnding>,
+    pub span: Option<(usize,usize)>
+}
+impl CstLine {
+    /// New line (with unix ending) which isn't part of any source.
+    pub fn new(kind: CstLineKind) -> Self {
+        Self {
+            kind,
+            ending: Some(LineEnding::Lf),
+            span: None
+        }
+    }
+}
+impl fmt::Display for CstLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        self.ending.as_ref().map_or(Ok(()), |ending| write!(f, "{}", ending))
+    }
+}
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
+pub enum CstLineKind {
+    Empty,
+    Comment(String),
+    Section(String),
+    Entry{
+        key: String,
+        value: String
+    },
+    Event{
+        trigger: String,
+        macro_name: String,
+        note: String,
+        data: String
+    },
+    Other(String)
+}
+impl fmt::Display for CstLineKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::utils::pattern::{KEY_SUFFIX,EVENT_SEPARATOR};
+        match self {
+            Self::Empty => Ok(()),
+            Self::Comment(text) => write!(f, "//{}", text),
+            Self::Section(text) | Self::Other(text) => f.write_str(text),
+            Self::Entry{key, value} => write!(f, "{}{}{}", key, KEY_SUFFIX, value),
+            Self::Event{trigger, macro_name, note, data} => write!(f, "{1}{0}{2}{0}{3}{0}{4}", EVENT_SEPARATOR, trigger, macro_name, note, data)
+        }
+    }
+}
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
+pub enum LineEnding {
+    Lf,
+    CrLf
+}
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n"
+        })
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn display() {
+        use super::{CstLine, CstLineKind, LineEnding};
+        assert_eq!(CstLine::new(CstLineKind::Comment(" note".to_owned())).to_string(), "// note\n");
+        assert_eq!(CstLine {
+            kind: CstLineKind::Event {
+                trigger: "0-1.".to_owned(),
+                macro_name: "".to_owned(),
+                note: "".to_owned(),
+                data: "Hi|there".to_owned()
+            },
+            ending: Some(LineEnding::CrLf),
+            span: Some((0, 13))
+        }.to_string(), "0-1.|||Hi|there\r\n");
+        assert_eq!(CstLineKind::Entry {key: "Width".to_owned(), value: "640".to_owned()}.to_string(), "Width: 640");
+    }
+}
\ No newline at end of file
@@ -1,5 +1,15 @@
 // Imports
-use std::convert::TryFrom;
+use std::{
+    convert::TryFrom,
+    fmt,
+    sync::Arc
+};
+use meval::{Expr,Context};
+use crate::{
+    state::error::ParseError,
+    tags::CustomTag,
+    utils::functions::convert::escape_text
+};
 
 
 // General
@@ -69,7 +79,223 @@ pub enum EventObject {
     TagAnimate(Box<Animate>),
     TagKaraoke(u32),
     TagKaraokeSet(i32),
-    TagKaraokeColor(Rgb)
+    TagKaraokeColor(Rgb),
+    /// Tag not known by this implementation, kept by lenient parsing (boxed strings keep objects small).
+    TagUnknown{
+        name: Box<str>,
+        value: Option<Box<str>>
+    },
+    /// Tag registered by a [`TagRegistry`](crate::tags::TagRegistry), payload can't be serialized.
+    #[cfg_attr(feature = "serialization", serde(skip))]
+    TagCustom(Arc<dyn CustomTag>)
+}
+
+impl fmt::Display for EventObject {
+    /// Canonical SSB event data, geometries other than text with leading mode tag.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::GeometryShape(_) => write!(f, "[mode=shape]{}", Geometry(self)),
+            Self::GeometryPoints(_) => write!(f, "[mode=points]{}", Geometry(self)),
+            Self::GeometryText(_) => write!(f, "{}", Geometry(self)),
+            tag => write!(f, "[{}]", Tag(tag))
+        }
+    }
+}
+impl EventObject {
+    /// Canonical SSB event data of objects, mode tags inserted where geometries need them.
+    ///
+    /// Fails for values SSB can't represent, like tag separators in names or values.
+    pub fn format_many(objects: &[Self]) -> Result<String, ParseError> {
+        let mut data = String::new();
+        let mut mode = "text";
+        let mut after_geometry = false;
+        for object in objects {
+            let object_mode = match object {
+                Self::GeometryShape(_) => "shape",
+                Self::GeometryPoints(_) => "points",
+                Self::GeometryText(_) => "text",
+                tag => {
+                    check_tag(tag)?;
+                    data.push_str(&format!("[{}]", Tag(tag)));
+                    after_geometry = false;
+                    continue;
+                }
+            };
+            // Adjacent geometries would merge without a tag in-between
+            if object_mode != mode || after_geometry {
+                data.push_str(&format!("[mode={}]", object_mode));
+                mode = object_mode;
+            }
+            data.push_str(&Geometry(object).to_string());
+            after_geometry = true;
+        }
+        Ok(data)
+    }
+}
+// Geometry data without mode
+struct Geometry<'a>(&'a EventObject);
+impl fmt::Display for Geometry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            EventObject::GeometryShape(segments) => {
+                let mut segment_type = None;
+                let mut tokens = vec![];
+                for segment in segments {
+                    let (letter, coordinates) = match segment {
+                        ShapeSegment::MoveTo(point) => ("m", vec![point.x, point.y]),
+                        ShapeSegment::LineTo(point) => ("l", vec![point.x, point.y]),
+                        ShapeSegment::CurveTo(point1, point2, point3) => ("b", vec![point1.x, point1.y, point2.x, point2.y, point3.x, point3.y]),
+                        ShapeSegment::ArcBy(center, angle) => ("a", vec![center.x, center.y, *angle]),
+                        ShapeSegment::Close => {
+                            tokens.push("c".to_owned());
+                            segment_type = None;
+                            continue;
+                        }
+                    };
+                    if segment_type != Some(letter) {
+                        tokens.push(letter.to_owned());
+                        segment_type = Some(letter);
+                    }
+                    tokens.extend(coordinates.iter().map(ToString::to_string));
+                }
+                f.write_str(&tokens.join(" "))
+            }
+            EventObject::GeometryPoints(points) => f.write_str(
+                &points.iter().map(|point| format!("{} {}", point.x, point.y) ).collect::<Vec<_>>().join(" ")
+            ),
+            EventObject::GeometryText(text) => f.write_str(&escape_text(text)),
+            _ => Ok(())
+        }
+    }
+}
+// Tag without brackets
+struct Tag<'a>(&'a EventObject);
+impl fmt::Display for Tag<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bool_str = |value: bool| if value {"y"} else {"n"};
+        let join = |values: &[f32]| values.iter().map(ToString::to_string).collect::<Vec<_>>().join(",");
+        match self.0 {
+            EventObject::GeometryShape(_) | EventObject::GeometryPoints(_) | EventObject::GeometryText(_) => Ok(()),
+            EventObject::TagFont(font) => write!(f, "font={}", escape_text(font)),
+            EventObject::TagSize(size) => write!(f, "size={}", size),
+            EventObject::TagBold(bold) => write!(f, "bold={}", bool_str(*bold)),
+            EventObject::TagItalic(italic) => write!(f, "italic={}", bool_str(*italic)),
+            EventObject::TagUnderline(underline) => write!(f, "underline={}", bool_str(*underline)),
+            EventObject::TagStrikeout(strikeout) => write!(f, "strikeout={}", bool_str(*strikeout)),
+            EventObject::TagPosition(position) => write!(f, "position={}", join(&[position.x, position.y, position.z])),
+            EventObject::TagAlignment(Alignment::Numpad(numpad)) => write!(f, "alignment={}", numpad),
+            EventObject::TagAlignment(Alignment::Offset(offset)) => write!(f, "alignment={}", join(&[offset.x, offset.y])),
+            EventObject::TagMargin(Margin::All(top, right, bottom, left)) => write!(f, "margin={}", join(&[*top, *right, *bottom, *left])),
+            EventObject::TagMargin(Margin::Top(top)) => write!(f, "margin-top={}", top),
+            EventObject::TagMargin(Margin::Right(right)) => write!(f, "margin-right={}", right),
+            EventObject::TagMargin(Margin::Bottom(bottom)) => write!(f, "margin-bottom={}", bottom),
+            EventObject::TagMargin(Margin::Left(left)) => write!(f, "margin-left={}", left),
+            EventObject::TagWrapStyle(wrap_style) => write!(f, "wrap-style={}", wrap_style),
+            EventObject::TagDirection(direction) => write!(f, "direction={}", direction),
+            EventObject::TagSpace(Space::All(horizontal, vertical)) => write!(f, "space={}", join(&[*horizontal, *vertical])),
+            EventObject::TagSpace(Space::Horizontal(horizontal)) => write!(f, "space-h={}", horizontal),
+            EventObject::TagSpace(Space::Vertical(vertical)) => write!(f, "space-v={}", vertical),
+            EventObject::TagRotate(Rotate::X(angle)) => write!(f, "rotate-x={}", angle),
+            EventObject::TagRotate(Rotate::Y(angle)) => write!(f, "rotate-y={}", angle),
+            EventObject::TagRotate(Rotate::Z(angle)) => write!(f, "rotate-z={}", angle),
+            EventObject::TagScale(Scale::All(x, y, z)) => write!(f, "scale={}", join(&[*x, *y, *z])),
+            EventObject::TagScale(Scale::X(x)) => write!(f, "scale-x={}", x),
+            EventObject::TagScale(Scale::Y(y)) => write!(f, "scale-y={}", y),
+            EventObject::TagScale(Scale::Z(z)) => write!(f, "scale-z={}", z),
+            EventObject::TagTranslate(Translate::All(x, y, z)) => write!(f, "translate={}", join(&[*x, *y, *z])),
+            EventObject::TagTranslate(Translate::X(x)) => write!(f, "translate-x={}", x),
+            EventObject::TagTranslate(Translate::Y(y)) => write!(f, "translate-y={}", y),
+            EventObject::TagTranslate(Translate::Z(z)) => write!(f, "translate-z={}", z),
+            EventObject::TagShear(Shear::All(x, y)) => write!(f, "shear={}", join(&[*x, *y])),
+            EventObject::TagShear(Shear::X(x)) => write!(f, "shear-x={}", x),
+            EventObject::TagShear(Shear::Y(y)) => write!(f, "shear-y={}", y),
+            EventObject::TagMatrix(matrix) => write!(f, "matrix={}", join(&matrix[..])),
+            EventObject::TagReset => f.write_str("reset"),
+            EventObject::TagBorder(Border::All(horizontal, vertical)) => write!(f, "border={}", join(&[*horizontal, *vertical])),
+            EventObject::TagBorder(Border::Horizontal(horizontal)) => write!(f, "border-h={}", horizontal),
+            EventObject::TagBorder(Border::Vertical(vertical)) => write!(f, "border-v={}", vertical),
+            EventObject::TagJoin(join) => write!(f, "join={}", join),
+            EventObject::TagCap(cap) => write!(f, "cap={}", cap),
+            EventObject::TagTexture(texture) => write!(f, "texture={}", escape_text(texture)),
+            EventObject::TagTexFill{x0, y0, x1, y1, wrap} => write!(f, "texfill={},{}", join(&[*x0, *y0, *x1, *y1]), wrap),
+            EventObject::TagColor(color) => write!(f, "color={}", color_str(color)),
+            EventObject::TagBorderColor(color) => write!(f, "bordercolor={}", color_str(color)),
+            EventObject::TagAlpha(alpha) => write!(f, "alpha={}", alpha_str(alpha)),
+            EventObject::TagBorderAlpha(alpha) => write!(f, "borderalpha={}", alpha_str(alpha)),
+            EventObject::TagBlur(Blur::All(horizontal, vertical)) => write!(f, "blur={}", join(&[*horizontal, *vertical])),
+            EventObject::TagBlur(Blur::Horizontal(horizontal)) => write!(f, "blur-h={}", horizontal),
+            EventObject::TagBlur(Blur::Vertical(vertical)) => write!(f, "blur-v={}", vertical),
+            EventObject::TagBlend(blend) => write!(f, "blend={}", blend),
+            EventObject::TagTarget(target) => write!(f, "target={}", target),
+            EventObject::TagMaskMode(mask_mode) => write!(f, "mask-mode={}", mask_mode),
+            EventObject::TagMaskClear => f.write_str("mask-clear"),
+            EventObject::TagAnimate(animate) => {
+                f.write_str("animate=")?;
+                if let Some((start_time, end_time)) = animate.time {
+                    write!(f, "{},{},", start_time, end_time)?;
+                }
+                if let Some(formula) = &animate.formula {
+                    write!(f, "{},", formula)?;
+                }
+                f.write_str("[")?;
+                for (index, tag) in animate.tags.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(";")?;
+                    }
+                    write!(f, "{}", Tag(tag))?;
+                }
+                f.write_str("]")
+            }
+            EventObject::TagKaraoke(duration) => write!(f, "k={}", duration),
+            EventObject::TagKaraokeSet(time) => write!(f, "kset={}", time),
+            EventObject::TagKaraokeColor(rgb) => write!(f, "kcolor={}", rgb_str(rgb)),
+            EventObject::TagUnknown{name, value: None} => f.write_str(&escape_text(name)),
+            EventObject::TagUnknown{name, value: Some(value)} => write!(f, "{}={}", escape_text(name), escape_text(value)),
+            EventObject::TagCustom(tag) => match tag.value() {
+                Some(value) => write!(f, "{}={}", escape_text(tag.name()), escape_text(&value)),
+                None => f.write_str(&escape_text(tag.name()))
+            }
+        }
+    }
+}
+// Tag separators can't be escaped, so names & values containing them don't parse back
+fn check_tag(tag: &EventObject) -> Result<(), ParseError> {
+    let (name, value) = match tag {
+        EventObject::TagFont(font) => ("font".to_owned(), Some(font.clone())),
+        EventObject::TagTexture(texture) => ("texture".to_owned(), Some(texture.clone())),
+        EventObject::TagUnknown{name, value} => (name.to_string(), value.as_ref().map(ToString::to_string)),
+        EventObject::TagCustom(tag) => (tag.name().to_owned(), tag.value()),
+        EventObject::TagAnimate(animate) => return animate.tags.iter().try_for_each(check_tag),
+        _ => return Ok(())
+    };
+    if name.is_empty() || name.contains(&['=', ';'][..]) || value.map_or(false, |value| value.contains(';')) {
+        Err(ParseError::new(&format!("Tag '{}' not representable!", Tag(tag))))
+    } else {
+        Ok(())
+    }
+}
+fn rgb_str(rgb: &Rgb) -> String {
+    format!("{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
+}
+fn color_str(color: &Color) -> String {
+    let rgbs = match color {
+        Color::Mono(rgb) => std::slice::from_ref(rgb),
+        Color::Linear(rgbs) => &rgbs[..],
+        Color::LinearWithStop(rgbs) => &rgbs[..],
+        Color::Corners(rgbs) => &rgbs[..],
+        Color::CornersWithStop(rgbs) => &rgbs[..]
+    };
+    rgbs.iter().map(rgb_str).collect::<Vec<_>>().join(",")
+}
+fn alpha_str(alpha: &Alpha) -> String {
+    let alphas = match alpha {
+        Alpha::Mono(alpha) => std::slice::from_ref(alpha),
+        Alpha::Linear(alphas) => &alphas[..],
+        Alpha::LinearWithStop(alphas) => &alphas[..],
+        Alpha::Corners(alphas) => &alphas[..],
+        Alpha::CornersWithStop(alphas) => &alphas[..]
+    };
+    alphas.iter().map(|alpha| format!("{:02X}", alpha) ).collect::<Vec<_>>().join(",")
 }
 
 
@@ -113,6 +339,21 @@ impl TryFrom<u8> for Numpad {
         }
     }
 }
+impl fmt::Display for Numpad {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::BottomLeft => 1,
+            Self::BottomCenter => 2,
+            Self::BottomRight => 3,
+            Self::MiddleLeft => 4,
+            Self::MiddleCenter => 5,
+            Self::MiddleRight => 6,
+            Self::TopLeft => 7,
+            Self::TopCenter => 8,
+            Self::TopRight => 9
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum Margin {
@@ -140,6 +381,15 @@ impl TryFrom<&str> for WrapStyle {
         }
     }
 }
+impl fmt::Display for WrapStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Space => "space",
+            Self::Character => "character",
+            Self::NoWrap => "nowrap"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum Direction {
@@ -160,6 +410,16 @@ impl TryFrom<&str> for Direction {
         }
     }
 }
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::LeftToRight => "ltr",
+            Self::RightToLeft => "rtl",
+            Self::TopToBottom => "ttb",
+            Self::BottomToTop => "btt"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum Space {
@@ -222,6 +482,15 @@ impl TryFrom<&str> for Join {
         }
     }
 }
+impl fmt::Display for Join {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Round => "round",
+            Self::Bevel => "bevel",
+            Self::Miter => "miter"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum Cap {
@@ -240,6 +509,15 @@ impl TryFrom<&str> for Cap {
         }
     }
 }
+impl fmt::Display for Cap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Round => "round",
+            Self::Butt => "butt",
+            Self::Square => "square"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum TextureWrapping {
@@ -260,6 +538,16 @@ impl TryFrom<&str> for TextureWrapping {
         }
     }
 }
+impl fmt::Display for TextureWrapping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pad => "pad",
+            Self::Clamp => "clamp",
+            Self::Repeat => "repeat",
+            Self::Mirror => "mirror"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum Color {
@@ -309,6 +597,18 @@ impl TryFrom<&str> for Blend {
         }
     }
 }
+impl fmt::Display for Blend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Add => "add",
+            Self::Subtract => "subtract",
+            Self::Multiply => "multiply",
+            Self::Invert => "invert",
+            Self::Difference => "difference",
+            Self::Screen => "screen"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum Target {
@@ -325,6 +625,14 @@ impl TryFrom<&str> for Target {
         }
     }
 }
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Frame => "frame",
+            Self::Mask => "mask"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum MaskMode {
@@ -341,13 +649,78 @@ impl TryFrom<&str> for MaskMode {
         }
     }
 }
+impl fmt::Display for MaskMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Normal => "normal",
+            Self::Invert => "invert"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub struct Animate {
     pub time: Option<(i32, i32)>,
-    pub formula: Option<String>,
+    pub formula: Option<Formula>,
     pub tags: Vec<EventObject>
 }
+/// Math expression of animation progress `t` (0 to 1), compiled once for repeated evaluation.
+#[derive(Debug, Clone)]
+pub struct Formula {
+    source: String,
+    expr: Expr
+}
+impl Formula {
+    /// Compile expression, failing on invalid syntax, unknown variables or functions.
+    pub fn new(source: &str) -> Result<Self, ParseError> {
+        let invalid = format!("Invalid formula '{}'!", source);
+        let formula = Self {
+            source: source.to_owned(),
+            expr: source.parse().map_err(|err: meval::Error| ParseError::new_with_source(&invalid, err) )?
+        };
+        formula.try_evaluate(0.0).map_err(|err| ParseError::new_with_source(&invalid, err) )?;
+        Ok(formula)
+    }
+    /// Expression as written.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    /// Value at animation progress.
+    pub fn evaluate(&self, t: f64) -> f64 {
+        self.try_evaluate(t).expect("Variables & functions were checked by compilation!")
+    }
+    fn try_evaluate(&self, t: f64) -> Result<f64, meval::Error> {
+        FORMULA_CONTEXT.with(|context| self.expr.eval_with_context((("t", t), context)) )
+    }
+}
+thread_local! {
+    // Builtin constants & functions (not shareable between threads)
+    static FORMULA_CONTEXT: Context<'static> = Context::new();
+}
+impl PartialEq for Formula {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+#[cfg(feature = "serialization")]
+impl serde::Serialize for Formula {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer {
+        serializer.serialize_str(&self.source)
+    }
+}
+#[cfg(feature = "serialization")]
+impl<'de> serde::Deserialize<'de> for Formula {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de> {
+        Self::new(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
 
 
 // Tests
@@ -357,6 +730,7 @@ mod tests {
     fn convert() {
         use super::{Numpad, WrapStyle, Direction, Join, Cap, TextureWrapping, Blend, Target, MaskMode, TryFrom};
         assert_eq!(Numpad::try_from(7u8), Ok(Numpad::TopLeft));
+        assert_eq!(Numpad::MiddleRight.to_string(), "6");
         assert_eq!(WrapStyle::try_from("character"), Ok(WrapStyle::Character));
         assert_eq!(Direction::try_from("ttb"), Ok(Direction::TopToBottom));
         assert_eq!(Join::try_from("bevel"), Ok(Join::Bevel));
@@ -366,4 +740,16 @@ mod tests {
         assert_eq!(Target::try_from("mask"), Ok(Target::Mask));
         assert_eq!(MaskMode::try_from("invert"), Ok(MaskMode::Invert));
     }
+
+    #[test]
+    fn formula() {
+        use super::Formula;
+        let formula = Formula::new("t^2 * sin(pi / 2)").unwrap();
+        assert_eq!(formula.evaluate(0.5), 0.25);
+        assert_eq!(formula.to_string(), "t^2 * sin(pi / 2)");
+        assert_eq!(Formula::new("t^").map_err(|err| err.to_string() ).unwrap_err().lines().next(), Some("Invalid formula 't^'!"));
+        assert_eq!(Formula::new("x*t").map_err(|err| err.to_string() ), Err("Invalid formula 'x*t'!\nEvaluation error: unknown variable `x`.".to_owned()));
+        assert!(Formula::new("foo(t)").is_err());
+        assert!(Formula::new("sin(t, 2)").is_err());
+    }
 }
\ No newline at end of file
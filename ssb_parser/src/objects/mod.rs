@@ -1,4 +1,7 @@
+/// Line objects of SSB concrete syntax tree.
+pub mod cst_objects;
 /// Sub-level objects of SSB for events.
 pub mod event_objects;
 /// Top-level objects of SSB.
-pub mod ssb_objects;
\ No newline at end of file
+pub mod ssb_objects;/// Conversions of SSB shapes from & to SVG path data.
+pub mod svg_path;
@@ -1,9 +1,13 @@
 // Imports
 use std::{
+    borrow::Cow,
     convert::TryFrom,
-    fmt
+    fmt,
+    sync::Arc
 };
+use once_cell::sync::OnceCell;
 use super::event_objects::EventObject;
+use crate::state::error::ParseError;
 
 
 // Data minor types
@@ -26,7 +30,139 @@ pub struct EventRender {
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
 pub enum EventTrigger {
     Id(String),
-    Time((u32,u32))
+    Time((u32,u32)),
+    Frame((u32,u32)),
+    Timecode((Timecode,Timecode))
+}
+impl EventTrigger {
+    /// Time range (in milliseconds) of time- or frame-triggered event, latter by framerate.
+    pub fn time_range(&self, framerate: Option<&Framerate>) -> Option<(u32,u32)> {
+        match self {
+            Self::Id(_) => None,
+            Self::Time(times) => Some(*times),
+            Self::Frame((start_frame, end_frame)) => framerate.and_then(|framerate| Some((framerate.frame_to_ms(*start_frame)?, framerate.frame_to_ms(*end_frame)?)) ),
+            Self::Timecode((start_timecode, end_timecode)) => framerate.and_then(|framerate| Some((
+                framerate.frame_to_ms(start_timecode.to_frame(framerate)?)?,
+                framerate.frame_to_ms(end_timecode.to_frame(framerate)?)?
+            )) )
+        }
+    }
+}
+
+/// Frames per second as fraction (f.e. 24000/1001 for NTSC film).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
+pub struct Framerate {
+    numerator: u32,
+    denominator: u32
+}
+impl Framerate {
+    /// New framerate, numerator & denominator mustn't be zero.
+    pub fn new(numerator: u32, denominator: u32) -> Option<Self> {
+        Some(Self {numerator, denominator}).filter(|_| numerator != 0 && denominator != 0 )
+    }
+    pub fn numerator(&self) -> u32 {
+        self.numerator
+    }
+    pub fn denominator(&self) -> u32 {
+        self.denominator
+    }
+    /// Frames per second rounded to whole frames, as counted by timecodes.
+    pub fn nominal(&self) -> u32 {
+        ((u64::from(self.numerator) + u64::from(self.denominator >> 1)) / u64::from(self.denominator)) as u32
+    }
+    /// Start time of frame in milliseconds (rounded down, calculated from frame number without accumulating errors).
+    ///
+    /// `None` if the time doesn't fit into milliseconds range.
+    pub fn frame_to_ms(&self, frame: u32) -> Option<u32> {
+        u128::from(frame).checked_mul(1000).and_then(|ms| ms.checked_mul(u128::from(self.denominator)) )
+            .and_then(|ms| u32::try_from(ms / u128::from(self.numerator)).ok() )
+    }
+}
+impl TryFrom<&str> for Framerate {
+    type Error = ();
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.find('/') {
+            Some(separator_pos) => Self::new(value[..separator_pos].parse().map_err(|_| () )?, value[separator_pos + 1..].parse().map_err(|_| () )?),
+            None => match value.find('.') {
+                // Decimals as exact fraction
+                Some(point_pos) => {
+                    let decimals = value.len() - point_pos - 1;
+                    Self::new(
+                        (value[..point_pos].to_owned() + &value[point_pos + 1..]).parse().map_err(|_| () )?,
+                        10u32.checked_pow(decimals as u32).filter(|_| decimals > 0 ).ok_or(())?
+                    )
+                }
+                None => Self::new(value.parse().map_err(|_| () )?, 1)
+            }
+        }.ok_or(())
+    }
+}
+impl fmt::Display for Framerate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+/// SMPTE timecode (`hours:minutes:seconds:frames`, drop-frame with `;` before frames).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub drop_frame: bool
+}
+impl Timecode {
+    /// Frame number by framerate (frames have to fit into a second, drop-frame requires a multiple of 30 fps).
+    ///
+    /// Drop-frame skips the first frame numbers of every minute, except each tenth, to catch up with fractional framerates.
+    /// `None` as well if the frame number doesn't fit into its range.
+    pub fn to_frame(&self, framerate: &Framerate) -> Option<u32> {
+        let nominal = u64::from(framerate.nominal());
+        let total_minutes = u64::from(self.hours) * 60 + u64::from(self.minutes);
+        let frames = (total_minutes * 60 + u64::from(self.seconds)).checked_mul(nominal)?.checked_add(u64::from(self.frames))?;
+        if u64::from(self.frames) >= nominal {
+            None
+        } else if self.drop_frame {
+            let dropped = nominal / 15;
+            if nominal % 30 != 0 || (self.seconds == 0 && self.minutes % 10 != 0 && u64::from(self.frames) < dropped) {
+                None
+            } else {
+                frames.checked_sub(dropped.checked_mul(total_minutes - total_minutes / 10)?).and_then(|frames| u32::try_from(frames).ok() )
+            }
+        } else {
+            u32::try_from(frames).ok()
+        }
+    }
+}
+impl TryFrom<&str> for Timecode {
+    type Error = ();
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut fields = value.split(&[':', ';'][..]);
+        let mut field = |max: u8| fields.next().filter(|field| field.len() == 2 ).and_then(|field| field.parse::<u8>().ok() ).filter(|field| *field <= max ).ok_or(());
+        let timecode = Self {
+            hours: field(99)?,
+            minutes: field(59)?,
+            seconds: field(59)?,
+            frames: field(99)?,
+            drop_frame: value.as_bytes()[value.len() - 3] == b';'
+        };
+        if fields.next().is_some() || value[..value.len() - 3].contains(';') {
+            return Err(());
+        }
+        Ok(timecode)
+    }
+}
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}{}{:02}", self.hours, self.minutes, self.seconds, if self.drop_frame {';'} else {':'}, self.frames)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -45,6 +181,14 @@ impl TryFrom<&str> for View {
         }
     }
 }
+impl fmt::Display for View {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Perspective => "perspective",
+            Self::Orthogonal => "orthogonal"
+        })
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
@@ -77,7 +221,17 @@ impl TryFrom<&str> for FontStyle {
         }
     }
 }
-pub type FontData = Vec<u8>;
+impl fmt::Display for FontStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Regular => "regular",
+            Self::Bold => "bold",
+            Self::Italic => "italic",
+            Self::BoldItalic => "bold-italic"
+        })
+    }
+}
+pub type FontData = ResourceData;
 pub type TextureId = String;
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
@@ -85,7 +239,127 @@ pub enum TextureDataVariant {
     Raw(TextureData),
     Url(String)
 }
-pub type TextureData = Vec<u8>;
+pub type TextureData = ResourceData;
+
+/// Binary data of a resource (font or texture).
+///
+/// Base64 from scripts gets decoded on first access, clones share the same data.
+#[derive(Clone)]
+pub struct ResourceData(Arc<ResourceDataInner>);
+struct ResourceDataInner {
+    encoded: Option<String>,
+    decoded: OnceCell<Option<Vec<u8>>>
+}
+impl ResourceData {
+    /// New resource by base64 text, decoded later.
+    ///
+    /// The format gets fully checked (characters, padding & unused trailing bits), so decoding can't fail.
+    pub fn from_base64(encoded: String) -> Option<Self> {
+        let data_len = encoded.trim_end_matches('=').len();
+        let padding = encoded.len() - data_len;
+        if data_len % 4 == 1 || (padding != 0 && (data_len + padding) % 4 != 0) || !encoded[..data_len].bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'+' || byte == b'/') {
+            return None;
+        }
+        // Bits of the last character beyond the data must be zero
+        let unused_bits_mask = match data_len % 4 {
+            2 => 0b1111,
+            3 => 0b11,
+            _ => 0
+        };
+        if encoded[..data_len].bytes().last().map_or(false, |byte| base64_value(byte) & unused_bits_mask != 0) {
+            return None;
+        }
+        Some(Self(Arc::new(ResourceDataInner {
+            encoded: Some(encoded),
+            decoded: OnceCell::new()
+        })))
+    }
+    /// Decoded data (decoding on first call).
+    pub fn data(&self) -> Result<&[u8], ParseError> {
+        self.0.decoded.get_or_init(|| self.0.encoded.as_ref().and_then(|encoded| base64::decode(encoded).ok() ))
+            .as_deref()
+            .ok_or_else(|| ParseError::new("Resource data not in base64 format!") )
+    }
+    /// Size of decoded data (without decoding).
+    pub fn len(&self) -> usize {
+        match (self.0.decoded.get(), &self.0.encoded) {
+            (Some(Some(data)), _) => data.len(),
+            (_, Some(encoded)) => {
+                let data_len = encoded.trim_end_matches('=').len();
+                data_len / 4 * 3 + (data_len % 4).saturating_sub(1)
+            }
+            _ => 0
+        }
+    }
+    /// Size of decoded data is zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Data was already decoded (or never encoded).
+    pub fn is_decoded(&self) -> bool {
+        self.0.decoded.get().is_some()
+    }
+    /// Data is the same instance as of another resource.
+    pub fn is_shared_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+    /// Data as base64 text (without encoding if not decoded from another one).
+    pub fn to_base64(&self) -> Cow<'_, str> {
+        match &self.0.encoded {
+            Some(encoded) => Cow::Borrowed(encoded),
+            None => Cow::Owned(self.data().map(base64::encode).unwrap_or_default())
+        }
+    }
+}
+fn base64_value(byte: u8) -> u8 {
+    match byte {
+        b'A'..=b'Z' => byte - b'A',
+        b'a'..=b'z' => byte - b'a' + 26,
+        b'0'..=b'9' => byte - b'0' + 52,
+        b'+' => 62,
+        _ => 63
+    }
+}
+impl From<Vec<u8>> for ResourceData {
+    fn from(data: Vec<u8>) -> Self {
+        let decoded = OnceCell::new();
+        decoded.get_or_init(|| Some(data) );
+        Self(Arc::new(ResourceDataInner {
+            encoded: None,
+            decoded
+        }))
+    }
+}
+impl PartialEq for ResourceData {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_shared_with(other) || match (&self.0.encoded, &other.0.encoded) {
+            (Some(encoded), Some(other_encoded)) if encoded == other_encoded => true,
+            _ => self.data().ok() == other.data().ok()
+        }
+    }
+}
+impl fmt::Debug for ResourceData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ResourceData")
+            .field("len", &self.len())
+            .field("decoded", &self.is_decoded())
+            .finish()
+    }
+}
+#[cfg(feature = "serialization")]
+impl serde::Serialize for ResourceData {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer {
+        self.data().map_err(serde::ser::Error::custom)?.serialize(serializer)
+    }
+}
+#[cfg(feature = "serialization")]
+impl<'de> serde::Deserialize<'de> for ResourceData {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de> {
+        Vec::<u8>::deserialize(deserializer).map(Self::from)
+    }
+}
 
 
 // Tests
@@ -102,5 +376,58 @@ mod tests {
         assert_eq!(FontStyle::try_from("italic"), Ok(FontStyle::Italic));
         assert_eq!(FontStyle::try_from("bold-italic"), Ok(FontStyle::BoldItalic));
         assert_eq!(FontStyle::try_from("ultra-bold"), Err(()));
+        assert_eq!(View::Orthogonal.to_string(), "orthogonal");
+        assert_eq!(FontStyle::BoldItalic.to_string(), "bold-italic");
+    }
+
+    #[test]
+    fn frames() {
+        use super::{EventTrigger, Framerate, Timecode, TryFrom};
+        let ntsc = Framerate::try_from("30000/1001").unwrap();
+        assert_eq!(Framerate::try_from("23.976"), Ok(Framerate::new(23976, 1000).unwrap()));
+        assert_eq!(Framerate::try_from("25").map(|framerate| framerate.to_string() ), Ok("25".to_owned()));
+        assert_eq!(Framerate::try_from("25/0"), Err(()));
+        assert_eq!(ntsc.nominal(), 30);
+        assert_eq!(ntsc.frame_to_ms(1_000_000), Some(33_366_666));
+        let timecode = Timecode::try_from("00:10:00;00").unwrap();
+        assert_eq!(timecode.to_string(), "00:10:00;00");
+        assert_eq!(timecode.to_frame(&ntsc), Some(17982));
+        assert_eq!(Timecode::try_from("00:01:00;02").unwrap().to_frame(&ntsc), Some(1800));
+        assert_eq!(Timecode::try_from("00:01:00;00").unwrap().to_frame(&ntsc), None);
+        assert_eq!(Timecode::try_from("00:01:00:00").unwrap().to_frame(&ntsc), Some(1800));
+        assert_eq!(Timecode::try_from("00:00:00:30").unwrap().to_frame(&ntsc), None);
+        assert_eq!(Timecode::try_from("00;00:00:00"), Err(()));
+        assert_eq!(Timecode::try_from("0:00:00:00"), Err(()));
+        assert_eq!(EventTrigger::Frame((30, 60)).time_range(Some(&Framerate::new(30, 1).unwrap())), Some((1000, 2000)));
+        assert_eq!(EventTrigger::Frame((30, 60)).time_range(None), None);
+        // Overflows
+        let huge = Framerate::new(100_000, 1).unwrap();
+        assert_eq!(Timecode::try_from("99:00:00:00").unwrap().to_frame(&huge), None);
+        assert_eq!(EventTrigger::Timecode((Timecode::try_from("00:00:00:00").unwrap(), Timecode::try_from("99:00:00:00").unwrap())).time_range(Some(&huge)), None);
+        let tiny = Framerate::new(1, 1_000_000_000).unwrap();
+        assert_eq!(tiny.frame_to_ms(4_000_000_000), None);
+        assert_eq!(EventTrigger::Frame((0, 4_000_000_000)).time_range(Some(&tiny)), None);
+        assert_eq!(Framerate::new(u32::MAX, 1).unwrap().frame_to_ms(u32::MAX), Some(1000));
+    }
+
+    #[test]
+    fn resource_data() {
+        use super::ResourceData;
+        let resource = ResourceData::from_base64("SGk=".to_owned()).unwrap();
+        let shared = resource.clone();
+        assert_eq!(resource.len(), 2);
+        assert!(!shared.is_decoded());
+        assert_eq!(resource.data().ok(), Some(&b"Hi"[..]));
+        assert!(shared.is_decoded() && shared.is_shared_with(&resource));
+        assert_eq!(resource, ResourceData::from(b"Hi".to_vec()));
+        assert_eq!(ResourceData::from(b"Hi".to_vec()).to_base64(), "SGk=");
+        assert_eq!(ResourceData::from_base64("AQIDBA".to_owned()).map(|resource| resource.len() ), Some(4));
+        assert_eq!(ResourceData::from_base64("A===".to_owned()), None);
+        assert_eq!(ResourceData::from_base64("no_base64".to_owned()), None);
+        // Non-canonical padding & trailing bits
+        assert_eq!(ResourceData::from_base64("SGk".to_owned()).map(|resource| resource.data().map(<[u8]>::to_vec).ok() ), Some(Some(b"Hi".to_vec())));
+        for encoded in &["SGk==", "SGk=A", "SQ=", "SGl=", "SR==", "SR"] {
+            assert_eq!(ResourceData::from_base64((*encoded).to_owned()), None, "{}", encoded);
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,345 @@
+// Imports
+use super::event_objects::{ShapeSegment,Point2D,Coordinate};
+use crate::{
+    state::error::ParseError,
+    geometry::segment_end
+};
+use std::f32::consts::{PI,FRAC_PI_2};
+
+
+/// Parse SVG path data (all commands, absolute & relative) into shape segments.
+///
+/// Quadratic curves get lifted to cubic ones, circular arcs become arcs and elliptical ones cubic curves.
+/// Error positions are byte offsets in line 0.
+pub fn shape_from_svg_path(data: &str) -> Result<Vec<ShapeSegment>, ParseError> {
+    let mut lexer = PathLexer {data, pos: 0};
+    let mut segments = vec![];
+    let (mut current, mut start) = (Point2D {x: 0.0, y: 0.0}, Point2D {x: 0.0, y: 0.0});
+    // Control points for smooth continuation (cubic, quadratic)
+    let (mut last_cubic, mut last_quadratic): (Option<Point2D>, Option<Point2D>) = (None, None);
+    let mut previous_command = None;
+    loop {
+        lexer.skip_separators();
+        let command = match lexer.peek() {
+            None => break,
+            Some(letter) if letter.is_ascii_alphabetic() => {
+                lexer.pos += 1;
+                letter
+            }
+            // Repeated arguments repeat command, moves continue with lines
+            Some(_) => match previous_command {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some(command) if command != 'Z' && command != 'z' => command,
+                _ => return Err(ParseError::new_with_pos("Missing path command!", (0, lexer.pos)))
+            }
+        };
+        previous_command = Some(command);
+        let relative = command.is_ascii_lowercase();
+        let offset = if relative {current.clone()} else {Point2D {x: 0.0, y: 0.0}};
+        let point = |lexer: &mut PathLexer| -> Result<Point2D, ParseError> {
+            Ok(Point2D {
+                x: offset.x + lexer.number()?,
+                y: offset.y + lexer.number()?
+            })
+        };
+        let (mut next_cubic, mut next_quadratic) = (None, None);
+        match command.to_ascii_uppercase() {
+            'M' => {
+                current = point(&mut lexer)?;
+                start = current.clone();
+                segments.push(ShapeSegment::MoveTo(current.clone()));
+            }
+            'L' => {
+                current = point(&mut lexer)?;
+                segments.push(ShapeSegment::LineTo(current.clone()));
+            }
+            'H' => {
+                current.x = offset.x + lexer.number()?;
+                segments.push(ShapeSegment::LineTo(current.clone()));
+            }
+            'V' => {
+                current.y = offset.y + lexer.number()?;
+                segments.push(ShapeSegment::LineTo(current.clone()));
+            }
+            'C' | 'S' => {
+                let control1 = if command.eq_ignore_ascii_case(&'C') {
+                    point(&mut lexer)?
+                } else {
+                    last_cubic.as_ref().map_or_else(|| current.clone(), |control| reflect(control, &current) )
+                };
+                let (control2, end) = (point(&mut lexer)?, point(&mut lexer)?);
+                segments.push(ShapeSegment::CurveTo(control1, control2.clone(), end.clone()));
+                next_cubic = Some(control2);
+                current = end;
+            }
+            'Q' | 'T' => {
+                let control = if command.eq_ignore_ascii_case(&'Q') {
+                    point(&mut lexer)?
+                } else {
+                    last_quadratic.as_ref().map_or_else(|| current.clone(), |control| reflect(control, &current) )
+                };
+                let end = point(&mut lexer)?;
+                // Degree elevation
+                let lift = |from: &Point2D| Point2D {
+                    x: from.x + (control.x - from.x) * 2.0 / 3.0,
+                    y: from.y + (control.y - from.y) * 2.0 / 3.0
+                };
+                segments.push(ShapeSegment::CurveTo(lift(&current), lift(&end), end.clone()));
+                next_quadratic = Some(control);
+                current = end;
+            }
+            'A' => {
+                let (radius_x, radius_y, rotation) = (lexer.number()?, lexer.number()?, lexer.number()?);
+                let (large_arc, sweep) = (lexer.flag()?, lexer.flag()?);
+                let end = point(&mut lexer)?;
+                segments.extend(svg_arc(&current, &end, radius_x, radius_y, rotation, large_arc, sweep));
+                current = end;
+            }
+            'Z' => {
+                segments.push(ShapeSegment::Close);
+                current = start.clone();
+            }
+            _ => return Err(ParseError::new_with_pos(&format!("Invalid path command '{}'!", command), (0, lexer.pos - 1)))
+        }
+        last_cubic = next_cubic;
+        last_quadratic = next_quadratic;
+    }
+    Ok(segments)
+}
+fn reflect(control: &Point2D, center: &Point2D) -> Point2D {
+    Point2D {
+        x: 2.0 * center.x - control.x,
+        y: 2.0 * center.y - control.y
+    }
+}
+// Endpoint to center parameterization (see SVG specification, appendix B.2.4)
+fn svg_arc(from: &Point2D, to: &Point2D, radius_x: Coordinate, radius_y: Coordinate, rotation: Coordinate, large_arc: bool, sweep: bool) -> Vec<ShapeSegment> {
+    let (mut radius_x, mut radius_y) = (radius_x.abs(), radius_y.abs());
+    if from == to {
+        return vec![];
+    }
+    if radius_x <= Coordinate::EPSILON || radius_y <= Coordinate::EPSILON {
+        return vec![ShapeSegment::LineTo(to.clone())];
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (half_x, half_y) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let (x1, y1) = (cos * half_x + sin * half_y, -sin * half_x + cos * half_y);
+    // Scale up too small radii
+    let lambda = (x1 * x1) / (radius_x * radius_x) + (y1 * y1) / (radius_y * radius_y);
+    if lambda > 1.0 {
+        radius_x *= lambda.sqrt();
+        radius_y *= lambda.sqrt();
+    }
+    let (rx2, ry2) = (radius_x * radius_x, radius_y * radius_y);
+    let coefficient = (((rx2 * ry2 - rx2 * y1 * y1 - ry2 * x1 * x1) / (rx2 * y1 * y1 + ry2 * x1 * x1)).max(0.0)).sqrt()
+        * if large_arc != sweep {1.0} else {-1.0};
+    let (center_x1, center_y1) = (coefficient * radius_x * y1 / radius_y, -coefficient * radius_y * x1 / radius_x);
+    let center = Point2D {
+        x: cos * center_x1 - sin * center_y1 + (from.x + to.x) / 2.0,
+        y: sin * center_x1 + cos * center_y1 + (from.y + to.y) / 2.0
+    };
+    let angle = |ux: Coordinate, uy: Coordinate, vx: Coordinate, vy: Coordinate| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let (ux, uy) = ((x1 - center_x1) / radius_x, (y1 - center_y1) / radius_y);
+    let start_angle = angle(1.0, 0.0, ux, uy);
+    let mut delta_angle = angle(ux, uy, (-x1 - center_x1) / radius_x, (-y1 - center_y1) / radius_y);
+    if !sweep && delta_angle > 0.0 {
+        delta_angle -= 2.0 * PI;
+    } else if sweep && delta_angle < 0.0 {
+        delta_angle += 2.0 * PI;
+    }
+    // Circles can keep being arcs
+    if (radius_x - radius_y).abs() <= radius_x.max(radius_y) * 1e-4 {
+        return vec![ShapeSegment::ArcBy(center, delta_angle.to_degrees())];
+    }
+    let pieces = (delta_angle.abs() / FRAC_PI_2 - 1e-4).ceil().max(1.0) as usize;
+    let step = delta_angle / pieces as f32;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+    let ellipse = |angle: Coordinate| {
+        let (angle_sin, angle_cos) = angle.sin_cos();
+        let (x, y, dx, dy) = (radius_x * angle_cos, radius_y * angle_sin, -radius_x * angle_sin, radius_y * angle_cos);
+        (
+            Point2D {x: center.x + cos * x - sin * y, y: center.y + sin * x + cos * y},
+            Point2D {x: cos * dx - sin * dy, y: sin * dx + cos * dy}
+        )
+    };
+    (0..pieces).map(|index| {
+        let (start_point, start_tangent) = ellipse(start_angle + step * index as f32);
+        let (end_point, end_tangent) = if index + 1 == pieces {
+            (to.clone(), ellipse(start_angle + delta_angle).1)
+        } else {
+            ellipse(start_angle + step * (index + 1) as f32)
+        };
+        ShapeSegment::CurveTo(
+            Point2D {x: start_point.x + handle * start_tangent.x, y: start_point.y + handle * start_tangent.y},
+            Point2D {x: end_point.x - handle * end_tangent.x, y: end_point.y - handle * end_tangent.y},
+            end_point
+        )
+    }).collect()
+}
+// Tokens of path data
+struct PathLexer<'a> {
+    data: &'a str,
+    pos: usize
+}
+impl<'a> PathLexer<'a> {
+    fn peek(&self) -> Option<char> {
+        self.data[self.pos..].chars().next()
+    }
+    fn skip_separators(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\r') | Some('\n') | Some(',') = self.peek() {
+            self.pos += 1;
+        }
+    }
+    fn number(&mut self) -> Result<Coordinate, ParseError> {
+        self.skip_separators();
+        let bytes = self.data.as_bytes();
+        let start = self.pos;
+        let mut end = start;
+        if let Some(b'+') | Some(b'-') = bytes.get(end) {
+            end += 1;
+        }
+        let digits = |mut index: usize| {
+            while bytes.get(index).map_or(false, u8::is_ascii_digit) {
+                index += 1;
+            }
+            index
+        };
+        end = digits(end);
+        if bytes.get(end) == Some(&b'.') {
+            end = digits(end + 1);
+        }
+        // Exponent only with digits following
+        if let Some(b'e') | Some(b'E') = bytes.get(end) {
+            let exponent_start = if let Some(b'+') | Some(b'-') = bytes.get(end + 1) {end + 2} else {end + 1};
+            let exponent_end = digits(exponent_start);
+            if exponent_end > exponent_start {
+                end = exponent_end;
+            }
+        }
+        let number = self.data[start..end].parse().map_err(|_| ParseError::new_with_pos("Invalid path number!", (0, start)) )?;
+        self.pos = end;
+        Ok(number)
+    }
+    fn flag(&mut self) -> Result<bool, ParseError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some('0') => false,
+            Some('1') => true,
+            _ => return Err(ParseError::new_with_pos("Invalid path flag!", (0, self.pos)))
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+/// Write shape segments as SVG path data (absolute commands).
+///
+/// Arcs become SVG arcs, split into halves at most.
+pub fn shape_to_svg_path(segments: &[ShapeSegment]) -> String {
+    let mut commands = vec![];
+    let (mut current, mut start) = (Point2D {x: 0.0, y: 0.0}, Point2D {x: 0.0, y: 0.0});
+    for segment in segments {
+        match segment {
+            ShapeSegment::MoveTo(point) => {
+                commands.push(format!("M {} {}", point.x, point.y));
+                start = point.clone();
+            }
+            ShapeSegment::LineTo(point) => commands.push(format!("L {} {}", point.x, point.y)),
+            ShapeSegment::CurveTo(point1, point2, point3) => commands.push(format!("C {} {} {} {} {} {}", point1.x, point1.y, point2.x, point2.y, point3.x, point3.y)),
+            ShapeSegment::ArcBy(center, angle) => {
+                let radius = (current.x - center.x).hypot(current.y - center.y);
+                // Full circles aren't representable by one arc
+                let pieces = (angle.abs() / 180.0).ceil().max(1.0) as usize;
+                let mut from = current.clone();
+                for _ in 0..pieces {
+                    let to = segment_end(&from, &ShapeSegment::ArcBy(center.clone(), angle / pieces as f32));
+                    commands.push(format!("A {} {} 0 0 {} {} {}", radius, radius, u8::from(*angle > 0.0), to.x, to.y));
+                    from = to;
+                }
+            }
+            ShapeSegment::Close => commands.push("Z".to_owned())
+        }
+        current = if *segment == ShapeSegment::Close {start.clone()} else {segment_end(&current, segment)};
+    }
+    commands.join(" ")
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{shape_from_svg_path,shape_to_svg_path};
+    use crate::objects::event_objects::{ShapeSegment,Point2D};
+
+    fn point(x: f32, y: f32) -> Point2D {
+        Point2D {x, y}
+    }
+    fn assert_near(a: &Point2D, b: &Point2D) {
+        assert!((a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn svg_import() {
+        assert_eq!(shape_from_svg_path("M10,20 l5-5 30 0 H0 v.5e1 z m1 1 C 1 2 3 4 5 6 s 1 1 2 2").map_err(|err| err.to_string() ), Ok(vec![
+            ShapeSegment::MoveTo(point(10.0, 20.0)),
+            ShapeSegment::LineTo(point(15.0, 15.0)),
+            ShapeSegment::LineTo(point(45.0, 15.0)),
+            ShapeSegment::LineTo(point(0.0, 15.0)),
+            ShapeSegment::LineTo(point(0.0, 20.0)),
+            ShapeSegment::Close,
+            ShapeSegment::MoveTo(point(11.0, 21.0)),
+            ShapeSegment::CurveTo(point(1.0, 2.0), point(3.0, 4.0), point(5.0, 6.0)),
+            ShapeSegment::CurveTo(point(7.0, 8.0), point(6.0, 7.0), point(7.0, 8.0))
+        ]));
+        assert_eq!(shape_from_svg_path("M0 0 Q 3 3 6 0 T 12 0").map_err(|err| err.to_string() ), Ok(vec![
+            ShapeSegment::MoveTo(point(0.0, 0.0)),
+            ShapeSegment::CurveTo(point(2.0, 2.0), point(4.0, 2.0), point(6.0, 0.0)),
+            ShapeSegment::CurveTo(point(8.0, -2.0), point(10.0, -2.0), point(12.0, 0.0))
+        ]));
+        // Circular arc
+        let segments = shape_from_svg_path("M10 0 A10 10 0 0 1 -10 0").unwrap();
+        if let ShapeSegment::ArcBy(center, angle) = &segments[1] {
+            assert_near(center, &point(0.0, 0.0));
+            assert!((angle - 180.0).abs() < 0.01);
+        } else {
+            panic!("Circular arc expected!");
+        }
+        // Elliptical arc with packed flags
+        let segments = shape_from_svg_path("M0 0a20 10 0 0140 0").unwrap();
+        assert_eq!(segments.len(), 3);
+        if let (ShapeSegment::CurveTo(_, _, middle), ShapeSegment::CurveTo(_, _, end)) = (&segments[1], &segments[2]) {
+            assert_near(middle, &point(20.0, -10.0));
+            assert_eq!(end, &point(40.0, 0.0));
+        } else {
+            panic!("Elliptical arc should become curves!");
+        }
+        // Errors
+        assert_eq!(shape_from_svg_path("10 10").map_err(|err| err.to_string() ), Err("Missing path command! <0:0>".to_owned()));
+        assert_eq!(shape_from_svg_path("M 1 1 X").map_err(|err| err.to_string() ), Err("Invalid path command 'X'! <0:6>".to_owned()));
+        assert_eq!(shape_from_svg_path("M 1 a").map_err(|err| err.to_string() ), Err("Invalid path number! <0:4>".to_owned()));
+        assert_eq!(shape_from_svg_path("M 0 0 A 1 1 0 2 0 1 1").map_err(|err| err.to_string() ), Err("Invalid path flag! <0:14>".to_owned()));
+    }
+
+    #[test]
+    fn svg_export() {
+        let segments = vec![
+            ShapeSegment::MoveTo(point(10.0, 0.0)),
+            ShapeSegment::ArcBy(point(0.0, 0.0), 360.0),
+            ShapeSegment::LineTo(point(0.5, -1.0)),
+            ShapeSegment::CurveTo(point(1.0, 2.0), point(3.0, 4.0), point(5.0, 6.0)),
+            ShapeSegment::Close
+        ];
+        let data = shape_to_svg_path(&segments);
+        assert!(data.starts_with("M 10 0 A 10 10 0 0 1 -10 "), "{}", data);
+        assert!(data.ends_with(" L 0.5 -1 C 1 2 3 4 5 6 Z"), "{}", data);
+        let imported = shape_from_svg_path(&data).unwrap();
+        assert_eq!(imported.len(), 6);
+        if let ShapeSegment::ArcBy(center, angle) = &imported[2] {
+            assert_near(center, &point(0.0, 0.0));
+            assert!((angle - 180.0).abs() < 0.01);
+        } else {
+            panic!("Circular arc expected!");
+        }
+    }
+}
@@ -1,4 +1,8 @@
+// Concrete syntax tree, identical to original text.
+pub mod ssb_cst;
 // Raw SSB data, close to original text.
 pub mod ssb;
+// Pull-based events of raw SSB data, for huge inputs.
+pub mod ssb_events;
 // Processed SSB data, formatted for rendering.
 pub mod ssb_render;
\ No newline at end of file
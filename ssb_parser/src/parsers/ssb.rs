@@ -1,19 +1,24 @@
 // Imports
 use crate::{
     state::{
-        error::ParseError,
-        ssb_state::Section
+        error::{ParseError,Diagnostic,DiagnosticCode,Severity,Span},
+        limits::{ParseLimits,Limit,LimitError},
+        ssb_state::{Section,ParseState}
     },
-    objects::ssb_objects::{View,Event,EventTrigger,FontFace,FontStyle,FontData,TextureId,TextureDataVariant},
+    objects::ssb_objects::{View,Framerate,Timecode,Event,EventTrigger,FontFace,FontStyle,FontData,TextureId,TextureDataVariant,ResourceData},
     utils::{
         pattern::*,
-        functions::convert::parse_timestamp
+        functions::convert::{parse_timestamp,format_timestamp}
     }
 };
 use std::{
     collections::HashMap,
-    io::BufRead,
-    convert::TryFrom
+    io::{self,BufRead,BufReader,Write},
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    fs::File,
+    path::{Path,PathBuf}
 };
 
 
@@ -32,6 +37,7 @@ pub struct Ssb {
     pub target_height: Option<u16>,
     pub target_depth: u16,
     pub target_view: View,
+    pub target_framerate: Option<Framerate>,
     // Macros section
     pub macros: HashMap<String, String>,
     // Events section
@@ -52,6 +58,7 @@ impl Default for Ssb {
             target_height: None,
             target_depth: 1000,
             target_view: View::Perspective,
+            target_framerate: None,
             macros: HashMap::default(),
             events: Vec::default(),
             fonts: HashMap::default(),
@@ -67,184 +74,476 @@ impl Ssb {
         Ok(self)
     }
     /// Parse SSB input and fill structure (which it borrows and returns as reference).
+    ///
+    /// Lines `#INCLUDE <path>` are rejected, input mustn't access files (see [parse_file](#method.parse_file)).
     pub fn parse<R>(&mut self, reader: R) -> Result<&mut Self, ParseError>
         where R: BufRead {
-        // Initial state
-        let mut section: Option<Section> = None;
+        self.parse_with_limits(reader, &ParseLimits::default())
+    }
+    /// Parse SSB input like [parse](#method.parse) but with custom limits (f.e. stricter for untrusted input).
+    pub fn parse_with_limits<R>(&mut self, reader: R, limits: &ParseLimits) -> Result<&mut Self, ParseError>
+        where R: BufRead {
+        self.parse_included(reader, None, &mut vec![], 0, &mut ParseState::new(self.resource_bytes()), limits)
+    }
+    /// Parse SSB file like [parse](#method.parse) but resolve includes.
+    ///
+    /// Lines `#INCLUDE <path>` parse another file in place, relative paths get resolved by the directory of the including file.
+    pub fn parse_file<P>(&mut self, path: P) -> Result<&mut Self, ParseError>
+        where P: AsRef<Path> {
+        self.parse_file_with_limits(path, &ParseLimits::default())
+    }
+    /// Parse SSB file like [parse_file](#method.parse_file) but with custom limits.
+    pub fn parse_file_with_limits<P>(&mut self, path: P, limits: &ParseLimits) -> Result<&mut Self, ParseError>
+        where P: AsRef<Path> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| ParseError::new_with_source(&format!("File '{}' not readable!", path.display()), err) )?;
+        let path = path.canonicalize()?;
+        self.parse_included(BufReader::new(file), Some(&path), &mut vec![path.clone()], 0, &mut ParseState::new(self.resource_bytes()), limits)
+            .map_err(|err| ParseError::new_with_source(&format!("Invalid file '{}'!", path.display()), err) )
+    }
+    fn parse_included<R>(&mut self, reader: R, file: Option<&Path>, includes: &mut Vec<PathBuf>, depth: usize, state: &mut ParseState, limits: &ParseLimits) -> Result<&mut Self, ParseError>
+        where R: BufRead {
+        // Initial section (resource bytes are shared with including files)
+        let outer_section = state.section.take();
         // Iterate through text lines
         for (line_index, line) in reader.lines().enumerate() {
             // Check for valid UTF-8 and remove carriage return (leftover of windows-ending)
             let mut line = line?;
             if line.ends_with('\r') {line.pop();}
-            // Ignore empty lines & comments
-            if !(line.is_empty() || line.starts_with("//")) {
-                // Switch or handle section
-                if let Ok(parsed_section) = Section::try_from(line.as_ref()) {
-                    section = Some(parsed_section);
-                } else {
-                    match section {
-                        // Info section
-                        Some(Section::Info) => {
-                            // Title
-                            if line.starts_with(INFO_TITLE_KEY) {
-                                self.info_title = Some(line[INFO_TITLE_KEY.len()..].to_owned());
-                            }
-                            // Author
-                            else if line.starts_with(INFO_AUTHOR_KEY) {
-                                self.info_author = Some(line[INFO_AUTHOR_KEY.len()..].to_owned());
-                            }
-                            // Description
-                            else if line.starts_with(INFO_DESCRIPTION_KEY) {
-                                self.info_description = Some(line[INFO_DESCRIPTION_KEY.len()..].to_owned());
-                            }
-                            // Version
-                            else if line.starts_with(INFO_VERSION_KEY) {
-                                self.info_version = Some(line[INFO_VERSION_KEY.len()..].to_owned());
-                            }
-                            // Custom
-                            else if let Some(separator_pos) = line.find(KEY_SUFFIX).filter(|pos| *pos > 0) {
-                                self.info_custom.insert(
-                                    line[..separator_pos].to_owned(),
-                                    line[separator_pos + KEY_SUFFIX.len()..].to_owned()
-                                );
-                            }
-                            // Invalid entry
-                            else {
-                                return Err(ParseError::new_with_pos("Invalid info entry!", (line_index, 0)));
-                            }
+            // Include other file (with its own sections), only files may include
+            if let (Some(file), Some(include)) = (file, line.strip_prefix(INCLUDE_PREFIX)) {
+                if depth >= limits.max_include_depth {
+                    return Err(ParseError::new_with_pos_source("Too deep includes!", (line_index, 0), LimitError::new(Limit::IncludeDepth, limits.max_include_depth)));
+                }
+                let path = file.parent().map_or_else(|| PathBuf::from(include), |directory| directory.join(include) );
+                let path = path.canonicalize().map_err(|err| ParseError::new_with_pos_source(&format!("Included file '{}' not found!", include), (line_index, INCLUDE_PREFIX.len()), err) )?;
+                if includes.contains(&path) {
+                    return Err(ParseError::new_with_pos(&format!("Included file '{}' includes itself!", path.display()), (line_index, INCLUDE_PREFIX.len())));
+                }
+                let included_file = File::open(&path).map_err(|err| ParseError::new_with_pos_source(&format!("Included file '{}' not readable!", path.display()), (line_index, INCLUDE_PREFIX.len()), err) )?;
+                includes.push(path.clone());
+                self.parse_included(BufReader::new(included_file), Some(&path), includes, depth + 1, state, limits)
+                    .map_err(|err| ParseError::new_with_pos_source(&format!("Invalid included file '{}'!", path.display()), (line_index, INCLUDE_PREFIX.len()), err) )?;
+                includes.pop();
+                continue;
+            }
+            // Handle line content
+            self.parse_line(&line, line_index, state, limits)?;
+        }
+        // Restore section of including file
+        state.section = outer_section;
+        // Return self for chaining calls
+        Ok(self)
+    }
+    /// Parse SSB input like [parse_owned](#method.parse_owned) but continue on errors, skipping invalid lines.
+    ///
+    /// Returns the partial data with all found problems (invalid entries as errors, duplicate macros & resources as warnings).
+    pub fn parse_with_diagnostics<R>(mut self, reader: R) -> (Self, Vec<Diagnostic>)
+        where R: BufRead {
+        // Initial state
+        let mut state = ParseState::new(self.resource_bytes());
+        let mut diagnostics = vec![];
+        // Iterate through text lines
+        for (line_index, line) in reader.lines().enumerate() {
+            // Check for valid UTF-8 (reading can't continue afterwards)
+            let mut line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    diagnostics.push(Diagnostic::from_error(DiagnosticCode::InvalidInput, &ParseError::new_with_pos_source("Line not readable!", (line_index, 0), err), None));
+                    break;
+                }
+            };
+            if line.ends_with('\r') {line.pop();}
+            // Handle line content & detect overridden entries
+            let entries_count = (self.macros.len(), self.fonts.len() + self.textures.len());
+            match self.parse_line(&line, line_index, &mut state, &ParseLimits::default()) {
+                Ok(_) if line.is_empty() || line.starts_with("//") || Section::try_from(line.as_str()).is_ok() => (),
+                Ok(_) => match state.section {
+                    Some(Section::Macros) if self.macros.len() == entries_count.0 => diagnostics.push(Diagnostic::new(
+                        Severity::Warning, DiagnosticCode::DuplicateMacro, "Macro defined again, overriding previous definition!",
+                        Some(Span::new(line_index, 0, line.find(KEY_SUFFIX).unwrap_or(0)))
+                    )),
+                    Some(Section::Resources) if self.fonts.len() + self.textures.len() == entries_count.1 => diagnostics.push(Diagnostic::new(
+                        Severity::Warning, DiagnosticCode::DuplicateResource, "Resource defined again, overriding previous definition!",
+                        Some(Span::new(line_index, 0, line.find(VALUE_SEPARATOR).unwrap_or(0)))
+                    )),
+                    _ => ()
+                }
+                Err(err) => diagnostics.push(Diagnostic::from_error(
+                    if matches!(err.source(), Some(src) if src.is::<LimitError>()) {DiagnosticCode::LimitExceeded} else {DiagnosticCode::InvalidEntry},
+                    &err, Some((line_index, line.len()))
+                ))
+            }
+        }
+        // Return partial result
+        (self, diagnostics)
+    }
+    /// Parse one SSB line (without line ending) in context of the current section.
+    pub(crate) fn parse_line(&mut self, line: &str, line_index: usize, state: &mut ParseState, limits: &ParseLimits) -> Result<(), ParseError> {
+        // Includes need a file context
+        if line.starts_with(INCLUDE_PREFIX) {
+            return Err(ParseError::new_with_pos("Include not resolvable here, parse file instead!", (line_index, 0)));
+        }
+        // Ignore empty lines & comments
+        if !(line.is_empty() || line.starts_with("//")) {
+            // Switch or handle section
+            if let Ok(parsed_section) = Section::try_from(line) {
+                state.section = Some(parsed_section);
+            } else {
+                match state.section {
+                    // Info section
+                    Some(Section::Info) => {
+                        // Title
+                        if line.starts_with(INFO_TITLE_KEY) {
+                            self.info_title = Some(line[INFO_TITLE_KEY.len()..].to_owned());
                         }
-                        // Target section
-                        Some(Section::Target) => {
-                            // Width
-                            if line.starts_with(TARGET_WIDTH_KEY) {
-                                self.target_width = Some(
-                                    line[TARGET_WIDTH_KEY.len()..].parse().map_err(|_| ParseError::new_with_pos("Invalid target width value!", (line_index, TARGET_WIDTH_KEY.len())) )?
-                                );
-                            }
-                            // Height
-                            else if line.starts_with(TARGET_HEIGHT_KEY) {
-                                self.target_height = Some(
-                                    line[TARGET_HEIGHT_KEY.len()..].parse().map_err(|_| ParseError::new_with_pos("Invalid target height value!", (line_index, TARGET_HEIGHT_KEY.len())) )?
-                                );
-                            }
-                            // Depth
-                            else if line.starts_with(TARGET_DEPTH_KEY) {
-                                self.target_depth = line[TARGET_DEPTH_KEY.len()..].parse().map_err(|_| ParseError::new_with_pos("Invalid target depth value!", (line_index, TARGET_DEPTH_KEY.len())) )?;
-                            }
-                            // View
-                            else if line.starts_with(TARGET_VIEW_KEY) {
-                                self.target_view = View::try_from(&line[TARGET_VIEW_KEY.len()..]).map_err(|_| ParseError::new_with_pos("Invalid target view value!", (line_index, TARGET_VIEW_KEY.len())) )?;
-                            }
-                            // Invalid entry
-                            else {
-                                return Err(ParseError::new_with_pos("Invalid target entry!", (line_index, 0)));
-                            }
+                        // Author
+                        else if line.starts_with(INFO_AUTHOR_KEY) {
+                            self.info_author = Some(line[INFO_AUTHOR_KEY.len()..].to_owned());
                         }
-                        // Macros section
-                        Some(Section::Macros) => {
-                            // Macro
-                            if let Some(separator_pos) = line.find(KEY_SUFFIX).filter(|pos| *pos > 0) {
-                                self.macros.insert(
-                                    line[..separator_pos].to_owned(),
-                                    line[separator_pos + KEY_SUFFIX.len()..].to_owned()
-                                );
-                            }
-                            // Invalid entry
-                            else {
-                                return Err(ParseError::new_with_pos("Invalid macros entry!", (line_index, 0)));
-                            }
+                        // Description
+                        else if line.starts_with(INFO_DESCRIPTION_KEY) {
+                            self.info_description = Some(line[INFO_DESCRIPTION_KEY.len()..].to_owned());
+                        }
+                        // Version
+                        else if line.starts_with(INFO_VERSION_KEY) {
+                            self.info_version = Some(line[INFO_VERSION_KEY.len()..].to_owned());
                         }
-                        // Events section
-                        Some(Section::Events) => {
-                            let mut event_tokens = line.splitn(4, EVENT_SEPARATOR);
-                            if let (Some(trigger), Some(macro_name), Some(note), Some(data)) = (event_tokens.next(), event_tokens.next(), event_tokens.next(), event_tokens.next()) {
-                                // Save event
-                                self.events.push(
-                                    Event {
-                                        trigger: {
-                                            // Tag
-                                            if trigger.starts_with('\'') && trigger.len() >= 2 && trigger.ends_with('\'') {
-                                                EventTrigger::Id(trigger[1..trigger.len()-1].to_owned())
+                        // Custom
+                        else if let Some(separator_pos) = line.find(KEY_SUFFIX).filter(|pos| *pos > 0) {
+                            self.info_custom.insert(
+                                line[..separator_pos].to_owned(),
+                                line[separator_pos + KEY_SUFFIX.len()..].to_owned()
+                            );
+                        }
+                        // Invalid entry
+                        else {
+                            return Err(ParseError::new_with_pos("Invalid info entry!", (line_index, 0)));
+                        }
+                    }
+                    // Target section
+                    Some(Section::Target) => {
+                        // Width
+                        if line.starts_with(TARGET_WIDTH_KEY) {
+                            self.target_width = Some(
+                                line[TARGET_WIDTH_KEY.len()..].parse().map_err(|_| ParseError::new_with_pos("Invalid target width value!", (line_index, TARGET_WIDTH_KEY.len())) )?
+                            );
+                        }
+                        // Height
+                        else if line.starts_with(TARGET_HEIGHT_KEY) {
+                            self.target_height = Some(
+                                line[TARGET_HEIGHT_KEY.len()..].parse().map_err(|_| ParseError::new_with_pos("Invalid target height value!", (line_index, TARGET_HEIGHT_KEY.len())) )?
+                            );
+                        }
+                        // Depth
+                        else if line.starts_with(TARGET_DEPTH_KEY) {
+                            self.target_depth = line[TARGET_DEPTH_KEY.len()..].parse().map_err(|_| ParseError::new_with_pos("Invalid target depth value!", (line_index, TARGET_DEPTH_KEY.len())) )?;
+                        }
+                        // View
+                        else if line.starts_with(TARGET_VIEW_KEY) {
+                            self.target_view = View::try_from(&line[TARGET_VIEW_KEY.len()..]).map_err(|_| ParseError::new_with_pos("Invalid target view value!", (line_index, TARGET_VIEW_KEY.len())) )?;
+                        }
+                        // Framerate
+                        else if line.starts_with(TARGET_FRAMERATE_KEY) {
+                            self.target_framerate = Some(
+                                Framerate::try_from(&line[TARGET_FRAMERATE_KEY.len()..]).map_err(|_| ParseError::new_with_pos("Invalid target framerate value!", (line_index, TARGET_FRAMERATE_KEY.len())) )?
+                            );
+                        }
+                        // Invalid entry
+                        else {
+                            return Err(ParseError::new_with_pos("Invalid target entry!", (line_index, 0)));
+                        }
+                    }
+                    // Macros section
+                    Some(Section::Macros) => {
+                        // Macro
+                        if let Some(separator_pos) = line.find(KEY_SUFFIX).filter(|pos| *pos > 0) {
+                            self.macros.insert(
+                                line[..separator_pos].to_owned(),
+                                line[separator_pos + KEY_SUFFIX.len()..].to_owned()
+                            );
+                        }
+                        // Invalid entry
+                        else {
+                            return Err(ParseError::new_with_pos("Invalid macros entry!", (line_index, 0)));
+                        }
+                    }
+                    // Events section
+                    Some(Section::Events) => {
+                        let mut event_tokens = line.splitn(4, EVENT_SEPARATOR);
+                        if let (Some(trigger), Some(macro_name), Some(note), Some(data)) = (event_tokens.next(), event_tokens.next(), event_tokens.next(), event_tokens.next()) {
+                            if self.events.len() >= limits.max_events {
+                                return Err(ParseError::new_with_pos_source("Too many events!", (line_index, 0), LimitError::new(Limit::Events, limits.max_events)));
+                            }
+                            // Save event
+                            self.events.push(
+                                Event {
+                                    trigger: {
+                                        // Tag
+                                        if trigger.starts_with('\'') && trigger.len() >= 2 && trigger.ends_with('\'') {
+                                            EventTrigger::Id(trigger[1..trigger.len()-1].to_owned())
+                                        // Time
+                                        } else if let Some(seperator_pos) = trigger.find(TRIGGER_SEPARATOR) {
+                                            let (start, end) = (&trigger[..seperator_pos], &trigger[seperator_pos + 1 /* TRIGGER_SEPARATOR */..]);
+                                            let end_pos = (line_index, seperator_pos + 1 /* TRIGGER_SEPARATOR */);
+                                            // Frame
+                                            if start.ends_with(FRAME_SUFFIX) {
+                                                let start_frame = start[..start.len() - 1 /* FRAME_SUFFIX */].parse::<u32>().map_err(|_| ParseError::new_with_pos("Start frame invalid!", (line_index, 0)) )?;
+                                                let end_frame = end.strip_suffix(FRAME_SUFFIX).and_then(|end| end.parse::<u32>().ok() ).ok_or_else(|| ParseError::new_with_pos("End frame invalid!", end_pos) )?;
+                                                if start_frame > end_frame {
+                                                    return Err(ParseError::new_with_pos("Start frame greater than end frame!", (line_index, 0)));
+                                                }
+                                                EventTrigger::Frame((start_frame, end_frame))
+                                            // Timecode
+                                            } else if start.matches(&[':', ';'][..]).count() == 3 {
+                                                let start_timecode = Timecode::try_from(start).map_err(|_| ParseError::new_with_pos("Start timecode invalid!", (line_index, 0)) )?;
+                                                let end_timecode = Timecode::try_from(end).map_err(|_| ParseError::new_with_pos("End timecode invalid!", end_pos) )?;
+                                                if start_timecode > end_timecode {
+                                                    return Err(ParseError::new_with_pos("Start timecode greater than end timecode!", (line_index, 0)));
+                                                }
+                                                EventTrigger::Timecode((start_timecode, end_timecode))
                                             // Time
-                                            } else if let Some(seperator_pos) = trigger.find(TRIGGER_SEPARATOR) {
-                                                let start_time = parse_timestamp(&trigger[..seperator_pos]).map_err(|_| ParseError::new_with_pos("Start timestamp invalid!", (line_index, 0)) )?;
-                                                let end_time = parse_timestamp(&trigger[seperator_pos + 1 /* TRIGGER_SEPARATOR */..]).map_err(|_| ParseError::new_with_pos("End timestamp invalid!", (line_index, seperator_pos + 1 /* TRIGGER_SEPARATOR */) ))?;
+                                            } else {
+                                                let start_time = parse_timestamp(start).map_err(|_| ParseError::new_with_pos("Start timestamp invalid!", (line_index, 0)) )?;
+                                                let end_time = parse_timestamp(end).map_err(|_| ParseError::new_with_pos("End timestamp invalid!", end_pos) )?;
                                                 if start_time > end_time {
                                                     return Err(ParseError::new_with_pos("Start time greater than end time!", (line_index, 0)));
                                                 }
                                                 EventTrigger::Time((start_time, end_time))
-                                            // Invalid
-                                            } else {
-                                                return Err(ParseError::new_with_pos("Invalid trigger format!", (line_index, 0)));
                                             }
-                                        },
-                                        macro_name: Some(macro_name.to_owned()).filter(|s| !s.is_empty()),
-                                        note: Some(note.to_owned()).filter(|s| !s.is_empty()),
-                                        data: data.to_owned(),
-                                        data_location: (line_index, trigger.len() + macro_name.len() + note.len() + 3 /* 3x EVENT_SEPARATOR */)
-                                    }
-                                );
-                            }
-                            // Invalid entry
-                            else {
-                                return Err(ParseError::new_with_pos("Invalid events entry!", (line_index, 0)));
-                            }
-                        }
-                        // Resources section
-                        Some(Section::Resources) => {
-                            // Font
-                            if line.starts_with(RESOURCES_FONT_KEY) {
-                                // Parse tokens
-                                let mut font_tokens = line[RESOURCES_FONT_KEY.len()..].splitn(3, VALUE_SEPARATOR);
-                                if let (Some(family), Some(style), Some(data)) = (font_tokens.next(), font_tokens.next(), font_tokens.next()) {
-                                    // Save font
-                                    self.fonts.insert(
-                                        FontFace {
-                                            family: family.to_owned(),
-                                            style: FontStyle::try_from(style).map_err(|_| ParseError::new_with_pos("Font style invalid!", (line_index, RESOURCES_FONT_KEY.len() + family.len() + 1 /* VALUE_SEPARATOR */) ))?
-                                        },
-                                        base64::decode(data).map_err(|_| ParseError::new_with_pos("Font data not in base64 format!", (line_index, RESOURCES_FONT_KEY.len() + family.len() + style.len() + (1 /* VALUE_SEPARATOR */ << 1))) )?
-                                    );
-                                } else {
-                                    return Err(ParseError::new_with_pos("Font family, style and data expected!", (line_index, RESOURCES_FONT_KEY.len())));
+                                        // Invalid
+                                        } else {
+                                            return Err(ParseError::new_with_pos("Invalid trigger format!", (line_index, 0)));
+                                        }
+                                    },
+                                    macro_name: Some(macro_name.to_owned()).filter(|s| !s.is_empty()),
+                                    note: Some(note.to_owned()).filter(|s| !s.is_empty()),
+                                    data: data.to_owned(),
+                                    data_location: (line_index, trigger.len() + macro_name.len() + note.len() + 3 /* 3x EVENT_SEPARATOR */)
                                 }
+                            );
+                        }
+                        // Invalid entry
+                        else {
+                            return Err(ParseError::new_with_pos("Invalid events entry!", (line_index, 0)));
+                        }
+                    }
+                    // Resources section
+                    Some(Section::Resources) => {
+                        // Font
+                        if line.starts_with(RESOURCES_FONT_KEY) {
+                            // Parse tokens
+                            let mut font_tokens = line[RESOURCES_FONT_KEY.len()..].splitn(3, VALUE_SEPARATOR);
+                            if let (Some(family), Some(style), Some(data)) = (font_tokens.next(), font_tokens.next(), font_tokens.next()) {
+                                // Save font
+                                let face = FontFace {
+                                    family: family.to_owned(),
+                                    style: FontStyle::try_from(style).map_err(|_| ParseError::new_with_pos("Font style invalid!", (line_index, RESOURCES_FONT_KEY.len() + family.len() + 1 /* VALUE_SEPARATOR */) ))?
+                                };
+                                let data_pos = (line_index, RESOURCES_FONT_KEY.len() + family.len() + style.len() + (1 /* VALUE_SEPARATOR */ << 1));
+                                let data = ResourceData::from_base64(data.to_owned()).ok_or_else(|| ParseError::new_with_pos("Font data not in base64 format!", data_pos) )?;
+                                let replaced_bytes = self.fonts.get(&face).map_or(0, ResourceData::len);
+                                state.resource_bytes = Self::check_resource_bytes(state.resource_bytes - replaced_bytes, data.len(), data_pos, limits)?;
+                                self.fonts.insert(face, data);
+                            } else {
+                                return Err(ParseError::new_with_pos("Font family, style and data expected!", (line_index, RESOURCES_FONT_KEY.len())));
                             }
-                            // Texture
-                            else if line.starts_with(RESOURCES_TEXTURE_KEY) {
-                                // Parse tokens
-                                let mut texture_tokens = line[RESOURCES_TEXTURE_KEY.len()..].splitn(3, VALUE_SEPARATOR);
-                                if let (Some(id), Some(data_type), Some(data)) = (texture_tokens.next(), texture_tokens.next(), texture_tokens.next()) {
-                                    // Save texture
-                                    self.textures.insert(
-                                        id.to_owned(),
-                                        match data_type {
-                                            // Raw data
-                                            "data" => TextureDataVariant::Raw(
-                                                base64::decode(data).map_err(|_| ParseError::new_with_pos("Texture data not in base64 format!", (line_index, RESOURCES_TEXTURE_KEY.len() + id.len() + data_type.len() + (1 /* VALUE_SEPARATOR */ << 1))) )?
-                                            ),
-                                            // Data by url
-                                            "url" => TextureDataVariant::Url(
+                        }
+                        // Texture
+                        else if line.starts_with(RESOURCES_TEXTURE_KEY) {
+                            // Parse tokens
+                            let mut texture_tokens = line[RESOURCES_TEXTURE_KEY.len()..].splitn(3, VALUE_SEPARATOR);
+                            if let (Some(id), Some(data_type), Some(data)) = (texture_tokens.next(), texture_tokens.next(), texture_tokens.next()) {
+                                // Save texture
+                                let replaced_bytes = self.textures.get(id).map_or(0, texture_bytes);
+                                self.textures.insert(
+                                    id.to_owned(),
+                                    match data_type {
+                                        // Raw data
+                                        "data" => {
+                                            let data_pos = (line_index, RESOURCES_TEXTURE_KEY.len() + id.len() + data_type.len() + (1 /* VALUE_SEPARATOR */ << 1));
+                                            let data = ResourceData::from_base64(data.to_owned()).ok_or_else(|| ParseError::new_with_pos("Texture data not in base64 format!", data_pos) )?;
+                                            state.resource_bytes = Self::check_resource_bytes(state.resource_bytes - replaced_bytes, data.len(), data_pos, limits)?;
+                                            TextureDataVariant::Raw(data)
+                                        }
+                                        // Data by url
+                                        "url" => {
+                                            state.resource_bytes -= replaced_bytes;
+                                            TextureDataVariant::Url(
                                                 data.to_owned()
-                                            ),
-                                            _ => return Err(ParseError::new_with_pos("Texture data type invalid!", (line_index, RESOURCES_TEXTURE_KEY.len() + id.len() + 1 /* VALUE_SEPARATOR */)))
+                                            )
                                         }
-                                    );
-                                } else {
-                                    return Err(ParseError::new_with_pos("Texture id, data type and data expected!", (line_index, RESOURCES_TEXTURE_KEY.len())));
-                                }
-                            }
-                            // Invalid entry
-                            else {
-                                return Err(ParseError::new_with_pos("Invalid resources entry!", (line_index, 0)));
+                                        _ => return Err(ParseError::new_with_pos("Texture data type invalid!", (line_index, RESOURCES_TEXTURE_KEY.len() + id.len() + 1 /* VALUE_SEPARATOR */)))
+                                    }
+                                );
+                            } else {
+                                return Err(ParseError::new_with_pos("Texture id, data type and data expected!", (line_index, RESOURCES_TEXTURE_KEY.len())));
                             }
                         }
-                        // Unset section
-                        None => return Err(ParseError::new_with_pos("No section set!", (line_index, 0)))
+                        // Invalid entry
+                        else {
+                            return Err(ParseError::new_with_pos("Invalid resources entry!", (line_index, 0)));
+                        }
                     }
+                    // Unset section
+                    None => return Err(ParseError::new_with_pos("No section set!", (line_index, 0)))
                 }
             }
         }
-        // Return self for chaining calls
-        Ok(self)
+        // Line processed
+        Ok(())
+    }
+    // Sum of embedded resource data
+    pub(crate) fn resource_bytes(&self) -> usize {
+        self.fonts.values().map(ResourceData::len).chain(self.textures.values().map(texture_bytes)).sum()
+    }
+    // Add resource data to sum or fail if exceeding limit
+    fn check_resource_bytes(resource_bytes: usize, bytes: usize, pos: (usize, usize), limits: &ParseLimits) -> Result<usize, ParseError> {
+        match resource_bytes.checked_add(bytes) {
+            Some(resource_bytes) if resource_bytes <= limits.max_resource_bytes => Ok(resource_bytes),
+            _ => Err(ParseError::new_with_pos_source("Too much resource data!", pos, LimitError::new(Limit::ResourceBytes, limits.max_resource_bytes)))
+        }
+    }
+    /// Write SSB data as text, which can be parsed again to the same structure.
+    ///
+    /// Entries of unordered collections get sorted by key for a stable output.
+    /// Event data is kept in its escaped form (f.e. `\n` for a line break), so it gets written as it is.
+    /// Values which can't be represented in text (like raw line breaks or separators in keys) cause an error of kind [`InvalidData`](std::io::ErrorKind::InvalidData).
+    /// Event data locations aren't written but recalculated by parsing the output.
+    pub fn write<W>(&self, mut writer: W) -> io::Result<()>
+        where W: Write {
+        // Info section
+        if self.info_title.is_some() || self.info_author.is_some() || self.info_description.is_some() || self.info_version.is_some() || !self.info_custom.is_empty() {
+            writeln!(writer, "{}", Section::Info)?;
+            for (key, value) in &[(INFO_TITLE_KEY, &self.info_title), (INFO_AUTHOR_KEY, &self.info_author), (INFO_DESCRIPTION_KEY, &self.info_description), (INFO_VERSION_KEY, &self.info_version)] {
+                if let Some(value) = value {
+                    writeln!(writer, "{}{}", key, text_value(value, &[], "Info value")?)?;
+                }
+            }
+            let mut info_custom = self.info_custom.iter().collect::<Vec<_>>();
+            info_custom.sort();
+            for (key, value) in info_custom {
+                writeln!(writer, "{}{}{}", key_value(key, &[INFO_TITLE_KEY, INFO_AUTHOR_KEY, INFO_DESCRIPTION_KEY, INFO_VERSION_KEY], "Info key")?, KEY_SUFFIX, text_value(value, &[], "Info value")?)?;
+            }
+            writeln!(writer)?;
+        }
+        // Target section
+        writeln!(writer, "{}", Section::Target)?;
+        if let Some(width) = self.target_width {
+            writeln!(writer, "{}{}", TARGET_WIDTH_KEY, width)?;
+        }
+        if let Some(height) = self.target_height {
+            writeln!(writer, "{}{}", TARGET_HEIGHT_KEY, height)?;
+        }
+        writeln!(writer, "{}{}", TARGET_DEPTH_KEY, self.target_depth)?;
+        writeln!(writer, "{}{}", TARGET_VIEW_KEY, self.target_view)?;
+        if let Some(framerate) = self.target_framerate {
+            writeln!(writer, "{}{}", TARGET_FRAMERATE_KEY, framerate)?;
+        }
+        writeln!(writer)?;
+        // Macros section
+        if !self.macros.is_empty() {
+            writeln!(writer, "{}", Section::Macros)?;
+            let mut macros = self.macros.iter().collect::<Vec<_>>();
+            macros.sort();
+            for (name, value) in macros {
+                writeln!(writer, "{}{}{}", key_value(name, &[], "Macro name")?, KEY_SUFFIX, text_value(value, &[], "Macro value")?)?;
+            }
+            writeln!(writer)?;
+        }
+        // Events section
+        if !self.events.is_empty() {
+            writeln!(writer, "{}", Section::Events)?;
+            for event in &self.events {
+                // Trigger
+                match &event.trigger {
+                    EventTrigger::Id(id) => write!(writer, "'{}'", text_value(id, &[EVENT_SEPARATOR], "Event trigger id")?)?,
+                    EventTrigger::Time((start_time, end_time)) if start_time <= end_time => write!(
+                        writer, "{}{}{}",
+                        format_timestamp(*start_time).map_err(|_| invalid_data(&format!("Event start time '{}' too large!", start_time)) )?,
+                        TRIGGER_SEPARATOR,
+                        format_timestamp(*end_time).map_err(|_| invalid_data(&format!("Event end time '{}' too large!", end_time)) )?
+                    )?,
+                    EventTrigger::Time(_) => return Err(invalid_data("Event start time greater than end time!")),
+                    EventTrigger::Frame((start_frame, end_frame)) if start_frame <= end_frame => write!(writer, "{1}{0}{3}{2}{0}", FRAME_SUFFIX, start_frame, end_frame, TRIGGER_SEPARATOR)?,
+                    EventTrigger::Frame(_) => return Err(invalid_data("Event start frame greater than end frame!")),
+                    EventTrigger::Timecode((start_timecode, end_timecode)) if start_timecode <= end_timecode => write!(writer, "{}{}{}", start_timecode, TRIGGER_SEPARATOR, end_timecode)?,
+                    EventTrigger::Timecode(_) => return Err(invalid_data("Event start timecode greater than end timecode!"))
+                }
+                // Macro, note & data
+                writeln!(
+                    writer, "{3}{0}{3}{1}{3}{2}",
+                    optional_value(&event.macro_name, &[EVENT_SEPARATOR], "Event macro name")?,
+                    optional_value(&event.note, &[EVENT_SEPARATOR], "Event note")?,
+                    text_value(&event.data, &[], "Event data")?,
+                    EVENT_SEPARATOR
+                )?;
+            }
+            writeln!(writer)?;
+        }
+        // Resources section
+        if !(self.fonts.is_empty() && self.textures.is_empty()) {
+            writeln!(writer, "{}", Section::Resources)?;
+            let mut fonts = self.fonts.iter().collect::<Vec<_>>();
+            fonts.sort_by_cached_key(|(face, _)| (face.family.clone(), face.style.to_string()));
+            for (face, data) in fonts {
+                writeln!(writer, "{0}{1}{4}{2}{4}{3}", RESOURCES_FONT_KEY, text_value(&face.family, &[VALUE_SEPARATOR], "Font family")?, face.style, data.to_base64(), VALUE_SEPARATOR)?;
+            }
+            let mut textures = self.textures.iter().collect::<Vec<_>>();
+            textures.sort_by_key(|(id, _)| *id);
+            for (id, data) in textures {
+                write!(writer, "{}{}{}", RESOURCES_TEXTURE_KEY, text_value(id, &[VALUE_SEPARATOR], "Texture id")?, VALUE_SEPARATOR)?;
+                match data {
+                    TextureDataVariant::Raw(data) => writeln!(writer, "data{}{}", VALUE_SEPARATOR, data.to_base64())?,
+                    TextureDataVariant::Url(url) => writeln!(writer, "url{}{}", VALUE_SEPARATOR, text_value(url, &[], "Texture url")?)?
+                }
+            }
+        }
+        // Everything written
+        writer.flush()
+    }
+}
+impl fmt::Display for Ssb {
+    /// Text of SSB data (see [`Ssb::write`]). Formatting fails on data which can't be represented in text.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = vec![];
+        self.write(&mut buffer).map_err(|_| fmt::Error )?;
+        f.write_str(&String::from_utf8_lossy(&buffer))
+    }
+}
+
+
+// Parsing helpers
+fn texture_bytes(data: &TextureDataVariant) -> usize {
+    match data {
+        TextureDataVariant::Raw(data) => data.len(),
+        TextureDataVariant::Url(_) => 0
+    }
+}
+
+// Writing helpers
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+fn text_value<'a>(value: &'a str, separators: &[char], description: &str) -> io::Result<&'a str> {
+    if value.contains(|c| c == '\n' || c == '\r' || separators.contains(&c)) {
+        Err(invalid_data(&format!("{} '{}' contains line break or separator!", description, value.escape_debug())))
+    } else {
+        Ok(value)
+    }
+}
+fn optional_value<'a>(value: &'a Option<String>, separators: &[char], description: &str) -> io::Result<&'a str> {
+    match value {
+        Some(value) if value.is_empty() => Err(invalid_data(&format!("{} mustn't be empty, use None instead!", description))),
+        Some(value) => text_value(value, separators, description),
+        None => Ok("")
+    }
+}
+fn key_value<'a>(key: &'a str, reserved_keys: &[&str], description: &str) -> io::Result<&'a str> {
+    let key = text_value(key, &[], description)?;
+    if key.is_empty() || key.starts_with("//") || key.contains(KEY_SUFFIX) || reserved_keys.contains(&(key.to_owned() + KEY_SUFFIX).as_str()) {
+        Err(invalid_data(&format!("{} '{}' not writable!", description, key)))
+    } else {
+        Ok(key)
     }
 }
\ No newline at end of file
@@ -0,0 +1,139 @@
+// Imports
+use crate::{
+    state::{
+        error::ParseError,
+        limits::ParseLimits,
+        ssb_state::{Section,ParseState}
+    },
+    objects::cst_objects::{CstLine,CstLineKind,LineEnding},
+    utils::pattern::*,
+    parsers::ssb::Ssb
+};
+use std::{
+    io::{self,BufRead,Write},
+    convert::TryFrom,
+    fmt
+};
+
+
+/// Concrete syntax tree of SSB, keeping every line (including empty lines and comments) in original order.
+///
+/// Lines can be edited freely and get written back as they are, so unchanged input is reproduced byte-for-byte.
+/// Line kinds and spans describe the state of parsing, they don't get updated by edits.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serialization", derive(serde::Serialize,serde::Deserialize))]
+pub struct SsbCst {
+    pub lines: Vec<CstLine>
+}
+impl SsbCst {
+    /// Parse SSB input into lines with their kind and byte span (excluding line ending).
+    pub fn parse<R>(mut reader: R) -> Result<Self, ParseError>
+        where R: BufRead {
+        // Initial state
+        let mut lines = vec![];
+        let mut section: Option<Section> = None;
+        let mut line_start = 0;
+        let mut buffer = String::new();
+        // Iterate through text lines (keeping their endings)
+        loop {
+            let line_length = reader.read_line(&mut buffer)?;
+            if line_length == 0 {
+                break;
+            }
+            // Split line ending
+            let ending = if buffer.ends_with("\r\n") {
+                buffer.truncate(buffer.len() - 2);
+                Some(LineEnding::CrLf)
+            } else if buffer.ends_with('\n') {
+                buffer.pop();
+                Some(LineEnding::Lf)
+            } else {
+                None
+            };
+            // Save line
+            lines.push(CstLine {
+                kind: classify_line(&buffer, &mut section),
+                ending,
+                span: Some((line_start, line_start + buffer.len()))
+            });
+            line_start += line_length;
+            buffer.clear();
+        }
+        Ok(Self {
+            lines
+        })
+    }
+    /// Write lines as text.
+    pub fn write<W>(&self, mut writer: W) -> io::Result<()>
+        where W: Write {
+        for line in &self.lines {
+            write!(writer, "{}", line)?;
+        }
+        writer.flush()
+    }
+}
+impl fmt::Display for SsbCst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.lines.iter().try_for_each(|line| write!(f, "{}", line) )
+    }
+}
+impl TryFrom<&SsbCst> for Ssb {
+    type Error = ParseError;
+    fn try_from(data: &SsbCst) -> Result<Self, Self::Error> {
+        let mut ssb = Ssb::default();
+        let mut state = ParseState::default();
+        for (line_index, line) in data.lines.iter().enumerate() {
+            // Same line content as seen by text parsing
+            let mut line = line.kind.to_string();
+            if line.ends_with('\r') {line.pop();}
+            ssb.parse_line(&line, line_index, &mut state, &ParseLimits::default())?;
+        }
+        Ok(ssb)
+    }
+}
+
+
+// Lines classification
+fn classify_line(line: &str, section: &mut Option<Section>) -> CstLineKind {
+    // Empty line
+    if line.is_empty() {
+        CstLineKind::Empty
+    // Comment
+    } else if let Some(comment) = line.strip_prefix("//") {
+        CstLineKind::Comment(comment.to_owned())
+    // Section
+    } else if let Ok(parsed_section) = Section::try_from(line) {
+        *section = Some(parsed_section);
+        CstLineKind::Section(line.to_owned())
+    } else {
+        match section {
+            // Key-value entry
+            Some(Section::Info) | Some(Section::Target) | Some(Section::Macros) | Some(Section::Resources) => {
+                if let Some(separator_pos) = line.find(KEY_SUFFIX).filter(|pos| *pos > 0) {
+                    CstLineKind::Entry {
+                        key: line[..separator_pos].to_owned(),
+                        value: line[separator_pos + KEY_SUFFIX.len()..].to_owned()
+                    }
+                } else {
+                    CstLineKind::Other(line.to_owned())
+                }
+            }
+            // Event entry
+            Some(Section::Events) => {
+                let mut event_tokens = line.splitn(4, EVENT_SEPARATOR);
+                if let (Some(trigger), Some(macro_name), Some(note), Some(data)) = (event_tokens.next(), event_tokens.next(), event_tokens.next(), event_tokens.next()) {
+                    CstLineKind::Event {
+                        trigger: trigger.to_owned(),
+                        macro_name: macro_name.to_owned(),
+                        note: note.to_owned(),
+                        data: data.to_owned()
+                    }
+                } else {
+                    CstLineKind::Other(line.to_owned())
+                }
+            }
+            // Unset section
+            None => CstLineKind::Other(line.to_owned())
+        }
+    }
+}
\ No newline at end of file
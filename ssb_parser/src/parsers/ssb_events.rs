@@ -0,0 +1,154 @@
+// Imports
+use crate::{
+    state::{
+        error::ParseError,
+        limits::ParseLimits,
+        ssb_state::{Section,ParseState}
+    },
+    objects::ssb_objects::Event,
+    parsers::ssb::Ssb
+};
+use std::{
+    convert::TryFrom,
+    io::{BufRead,Lines},
+    iter::Enumerate
+};
+
+
+/// Pull-based reader of SSB input, yielding events one by one instead of collecting them.
+///
+/// Non-event sections fill the [header](#method.header), resources get skipped on demand.
+/// Memory stays bounded by the header, so huge scripts can be scanned or filtered event-wise.
+/// Invalid lines yield errors without stopping, unreadable input ends the iteration.
+pub struct SsbEventReader<R> {
+    lines: Enumerate<Lines<R>>,
+    state: ParseState,
+    header: Ssb,
+    skip_resources: bool,
+    limits: ParseLimits,
+    finished: bool
+}
+impl<R> SsbEventReader<R>
+    where R: BufRead {
+    /// New reader over SSB input, starting with an empty header.
+    pub fn new(reader: R) -> Self {
+        Self::with_header(Ssb::default(), reader)
+    }
+    /// New reader over SSB input, extending given header (its events get ignored).
+    pub fn with_header(mut header: Ssb, reader: R) -> Self {
+        header.events.clear();
+        Self {
+            lines: reader.lines().enumerate(),
+            state: ParseState::new(header.resource_bytes()),
+            header,
+            skip_resources: false,
+            limits: ParseLimits::default(),
+            finished: false
+        }
+    }
+    /// Skip entries of resources sections instead of decoding them into the header.
+    pub fn skip_resources(mut self, skip: bool) -> Self {
+        self.skip_resources = skip;
+        self
+    }
+    /// Use custom limits (events get passed, so their count isn't limited).
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+    /// Data of non-event sections read so far.
+    pub fn header(&self) -> &Ssb {
+        &self.header
+    }
+    /// Consume reader for data of non-event sections read so far.
+    pub fn into_header(self) -> Ssb {
+        self.header
+    }
+}
+impl<R> Iterator for SsbEventReader<R>
+    where R: BufRead {
+    type Item = Result<Event, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            // Check for valid UTF-8 (reading can't continue afterwards) and remove carriage return
+            let (line_index, mut line) = match self.lines.next()? {
+                (line_index, Ok(line)) => (line_index, line),
+                (line_index, Err(err)) => {
+                    self.finished = true;
+                    return Some(Err(ParseError::new_with_pos_source("Line not readable!", (line_index, 0), err)));
+                }
+            };
+            if line.ends_with('\r') {line.pop();}
+            // Skip resources but keep track of section changes
+            if self.skip_resources && self.state.section == Some(Section::Resources) && Section::try_from(line.as_str()).is_err() {
+                continue;
+            }
+            // Handle line content and pass event
+            if let Err(err) = self.header.parse_line(&line, line_index, &mut self.state, &self.limits) {
+                return Some(Err(err));
+            }
+            if let Some(event) = self.header.events.pop() {
+                return Some(Ok(event));
+            }
+        }
+        None
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::SsbEventReader;
+    use crate::{
+        objects::ssb_objects::EventTrigger,
+        parsers::ssb_render::SsbRender,
+        state::limits::{ParseLimits,Limit,LimitError}
+    };
+    use std::{
+        convert::TryFrom,
+        error::Error,
+        io::Cursor
+    };
+
+    #[test]
+    fn skip_resources() {
+        let text = "#RESOURCES\nFont: bar,bold,dXNhZ2k=\nINVALID_ENTRY\n#EVENTS\n0-1.0|||A\n#RESOURCES\nTexture: foo,data,INVALID_BASE64\n#EVENTS\n1.0-2.0|||B";
+        let mut reader = SsbEventReader::new(Cursor::new(text)).skip_resources(true);
+        assert_eq!(reader.by_ref().map(|event| event.map(|event| event.data ).map_err(|err| err.to_string() ) ).collect::<Vec<_>>(), vec![Ok("A".to_owned()), Ok("B".to_owned())]);
+        assert!(reader.header().fonts.is_empty() && reader.header().textures.is_empty());
+        let reader = SsbEventReader::new(Cursor::new(text));
+        assert_eq!(reader.filter(Result::is_err).count(), 2);
+    }
+
+    #[test]
+    fn macros_after_events() {
+        let mut reader = SsbEventReader::new(Cursor::new("#EVENTS\n0-1.0|big||${small}Hi\n#MACROS\nbig: [size=40]\nsmall: [size=10]"));
+        let event = reader.next().expect("Event expected!").expect("Event should be valid!");
+        assert_eq!((&event.trigger, event.macro_name.as_deref()), (&EventTrigger::Time((0, 1000)), Some("big")));
+        assert!(reader.header().macros.is_empty());
+        assert!(reader.next().is_none());
+        // Macros resolvable once header is complete
+        let mut ssb = reader.into_header();
+        ssb.events.push(event);
+        assert!(SsbRender::try_from(ssb).is_ok());
+    }
+
+    #[test]
+    fn limits_and_errors() {
+        let text = "#EVENTS\n0-1.0|||A\n#RESOURCES\nFont: a,regular,dXNhZ2k=\nFont: b,regular,dXNhZ2k=\n#EVENTS\n?|||\n1.0-2.0|||B";
+        let mut reader = SsbEventReader::new(Cursor::new(text)).limits(ParseLimits {max_resource_bytes: 8, max_events: 1, ..ParseLimits::default()});
+        assert_eq!(reader.next().and_then(Result::ok).map(|event| event.data ), Some("A".to_owned()));
+        let err = reader.next().expect("Limit error expected!").expect_err("Resources should exceed limit!");
+        assert_eq!(err.to_string(), "Too much resource data! <4:16>\nLimit of resource bytes exceeded (maximum: 8)!");
+        assert_eq!(err.source().and_then(|err| err.downcast_ref::<LimitError>() ).copied(), Some(LimitError {limit: Limit::ResourceBytes, max: 8}));
+        assert_eq!(reader.next().map(|event| event.map_err(|err| err.to_string() ) ), Some(Err("Invalid trigger format! <6:0>".to_owned())));
+        // Events aren't counted, reading goes on after errors
+        assert_eq!(reader.next().and_then(Result::ok).map(|event| event.data ), Some("B".to_owned()));
+        assert!(reader.next().is_none());
+        assert_eq!(reader.header().fonts.len(), 1);
+        // Unreadable input ends iteration
+        let mut reader = SsbEventReader::new(Cursor::new(b"#EVENTS\n\xFF\n0-1.0|||A".to_vec()));
+        assert_eq!(reader.next().map(|event| event.map_err(|err| err.to_string() ) ), Some(Err("Line not readable! <1:0>\nstream did not contain valid UTF-8".to_owned())));
+        assert!(reader.next().is_none());
+    }
+}
@@ -1,27 +1,34 @@
 // Imports
 use crate::{
     state::{
-        error::ParseError,
+        error::{ParseError,Diagnostic,DiagnosticCode,Severity,Span},
+        limits::{ParseLimits,Limit,LimitError},
         ssb_state::{Mode,ShapeSegmentType}
     },
     utils::{
         pattern::*,
         functions::{
-            macros::flatten_macro,
+            macros::{flatten_macro,expand_macro,MacroError},
             event_iter::{EscapedText,TagsIterator},
             convert::{bool_from_str,alpha_from_str,rgb_from_str},
             option::OptionExt
         }
     },
     objects::{
-        ssb_objects::{View,EventRender,FontFace,FontData,TextureId,TextureData,TextureDataVariant},
-        event_objects::{Point2D,Point3D,EventObject,ShapeSegment,Alignment,Numpad,Margin,WrapStyle,Direction,Space,Rotate,Scale,Translate,Shear,Border,Join,Cap,TextureWrapping,Color,Alpha,Blur,Blend,Target,MaskMode,Animate}
+        ssb_objects::{View,Framerate,EventRender,EventTrigger,FontFace,FontData,TextureId,TextureData,TextureDataVariant,ResourceData},
+        event_objects::{Point2D,Point3D,EventObject,ShapeSegment,Alignment,Numpad,Margin,WrapStyle,Formula,Direction,Space,Rotate,Scale,Translate,Shear,Border,Join,Cap,TextureWrapping,Color,Alpha,Blur,Blend,Target,MaskMode,Animate}
     },
-    parsers::ssb::Ssb
+    parsers::ssb::Ssb,
+    resolvers::{ResourceResolver,DirectoryResolver},
+    tags::TagRegistry
 };
 use std::{
     collections::{HashMap,HashSet},
-    convert::TryFrom
+    convert::TryFrom,
+    fmt,
+    io::Read,
+    ops::Range,
+    str::FromStr
 };
 
 
@@ -34,6 +41,7 @@ pub struct SsbRender {
     pub target_height: Option<u16>,
     pub target_depth: u16,
     pub target_view: View,
+    pub target_framerate: Option<Framerate>,
     // Events section
     pub events: Vec<EventRender>,
     // Resources section
@@ -43,523 +51,855 @@ pub struct SsbRender {
 impl TryFrom<Ssb> for SsbRender {
     type Error = ParseError;
     fn try_from(data: Ssb) -> Result<Self, Self::Error> {
-        Ok(SsbRender {
-            target_width: data.target_width,
-            target_height: data.target_height,
-            target_depth: data.target_depth,
-            target_view: data.target_view,
-            events: {
-                // Flatten macros & detect infinite recursion
-                let mut flat_macros = HashMap::with_capacity(data.macros.len());
-                for macro_name in data.macros.keys() {
-                    flatten_macro(macro_name, &mut HashSet::new(), &data.macros, &mut flat_macros).map_err(|err| ParseError::new(&format!("Flattening macro '{}' caused error: {:?}", macro_name, err)) )?;
+        Self::convert(data, &RenderOptions::default(), &mut vec![])
+    }
+}
+impl SsbRender {
+    /// Convert SSB data like [try_from](#method.try_from) but with given options.
+    ///
+    /// Returns the render data with found problems (see [RenderOptions](struct.RenderOptions.html) for which get collected).
+    pub fn from_with_options(data: Ssb, options: &RenderOptions) -> Result<(Self, Vec<Diagnostic>), ParseError> {
+        let mut diagnostics = vec![];
+        let render = Self::convert(data, options, &mut diagnostics)?;
+        Ok((render, diagnostics))
+    }
+    fn convert(data: Ssb, options: &RenderOptions, diagnostics: &mut Vec<Diagnostic>) -> Result<Self, ParseError> {
+        let (limits, custom_tags) = (&options.limits, options.registry);
+        let default_resolver = DirectoryResolver::default();
+        let resolver = options.resolver.unwrap_or(&default_resolver);
+        // Collect errors as diagnostics or fail with them
+        let collect_errors = options.diagnostics;
+        // Flatten macros & detect infinite recursion
+        let mut macro_names = data.macros.keys().collect::<Vec<_>>();
+        macro_names.sort();
+        let mut flat_macros = HashMap::with_capacity(data.macros.len());
+        for macro_name in macro_names {
+            match flatten_macro(macro_name, &mut HashSet::new(), &data.macros, &mut flat_macros, limits) {
+                Ok(()) => (),
+                Err(MacroError::LimitExceeded(sub_macro_name, limit_error)) => report(
+                    diagnostics, collect_errors, DiagnosticCode::LimitExceeded,
+                    ParseError::new_with_source(&format!("Flattening macro '{}' failed by macro '{}'!", macro_name, sub_macro_name), limit_error), None
+                )?,
+                Err(err) => {
+                    let code = match err {
+                        MacroError::NotFound(_) => DiagnosticCode::UnknownMacro,
+                        MacroError::InfiniteLoop(_) => DiagnosticCode::RecursiveMacro,
+                        _ => DiagnosticCode::MacroArityMismatch
+                    };
+                    report(diagnostics, collect_errors, code, ParseError::new(&format!("Flattening macro '{}' caused error: {:?}", macro_name, err)), None)?;
+                }
+            }
+        }
+        // Detect unused macros & overlapping ids
+        if collect_errors {
+            let used_macros = data.events.iter()
+                .flat_map(|event| event.macro_name.as_deref().into_iter().chain(MACRO_PATTERN.captures_iter(&event.data).filter_map(|captures| captures.get(1) ).map(|name| name.as_str() )) )
+                .chain(data.macros.values().flat_map(|value| MACRO_PATTERN.captures_iter(value).filter_map(|captures| captures.get(1) ).map(|name| name.as_str() ) ))
+                .collect::<HashSet<_>>();
+            let mut unused_macros = data.macros.keys().filter(|macro_name| !used_macros.contains(macro_name.as_str()) ).collect::<Vec<_>>();
+            unused_macros.sort();
+            for macro_name in unused_macros {
+                diagnostics.push(Diagnostic::new(Severity::Warning, DiagnosticCode::UnusedMacro, &format!("Macro '{}' unused!", macro_name), None));
+            }
+            let mut ids = HashSet::new();
+            for event in &data.events {
+                if let EventTrigger::Id(id) = &event.trigger {
+                    if !ids.insert(id) {
+                        diagnostics.push(Diagnostic::new(
+                            Severity::Warning, DiagnosticCode::DuplicateId, &format!("Event id '{}' already used, events overlap!", id),
+                            Some(Span::new(event.data_location.0, 0, id.len() + 2 /* Quotes */))
+                        ));
+                    }
+                }
+            }
+        }
+        // Evaluate events
+        let mut events = Vec::with_capacity(data.events.len());
+        'events: for event in data.events {
+            let data_end = (event.data_location.0, event.data_location.1 + event.data.len());
+            // Insert macros, remembering origins of data parts
+            let mut event_data = String::with_capacity(event.data.len());
+            let mut segments = vec![];
+            if let Some(macro_name) = &event.macro_name {
+                match expand_macro(macro_name, None, &flat_macros, limits) {
+                    Ok(macro_value) => {
+                        // Events not from parsed text have no location to find the macro name by
+                        let column = event.data_location.1
+                            .checked_sub(event.note.as_ref().map_or(0, String::len) + macro_name.len() + 2 /* 2x EVENT_SEPARATOR */);
+                        segments.push(DataSegment {data: 0..macro_value.len(), macro_name: Some(macro_name), source: column.map_or(0..0, |column| column..column + macro_name.len())});
+                        event_data.push_str(&macro_value);
+                    }
+                    Err(err) => {
+                        let (code, err) = insertion_error("Base", macro_name, err, (event.data_location.0, 0));
+                        report(diagnostics, collect_errors, code, err, Some(event.data_location))?;
+                        continue;
+                    }
                 }
-                // Evaluate events
-                let mut events = Vec::with_capacity(data.events.len());
-                for event in data.events {
-                    // Insert base macro
-                    let mut event_data = event.data.clone();
-                    if let Some(macro_name) = &event.macro_name {
-                        event_data.insert_str(0, flat_macros.get(macro_name.as_str()).ok_or_else(|| ParseError::new_with_pos(&format!("Base macro '{}' not found to insert!", macro_name), (event.data_location.0, 0)) )?);
+            }
+            let mut rest_start = 0;
+            for captures in MACRO_PATTERN.captures_iter(&event.data) {
+                let found = captures.get(0).expect("Pattern match is always captured!");
+                let macro_name = captures.get(1).expect("Macro name is always captured!").as_str();
+                let macro_location = event.data_location.1 + found.start()..event.data_location.1 + found.end();
+                match expand_macro(macro_name, captures.get(2).map(|arguments| arguments.as_str() ), &flat_macros, limits).and_then(|macro_value|
+                    if event_data.len() + found.start() - rest_start + macro_value.len() > limits.max_event_length {
+                        Err(MacroError::LimitExceeded(macro_name.to_owned(), LimitError::new(Limit::EventLength, limits.max_event_length)))
+                    } else {
+                        Ok(macro_value)
                     }
-                    // Insert inline macros
-                    while let Some(found) = MACRO_PATTERN.find(&event_data) {
-                        let macro_name = &event_data[found.start()+MACRO_INLINE_START.len()..found.end()-MACRO_INLINE_END.len()];
-                        let macro_location = found.start()..found.end();
-                        let macro_value = flat_macros.get(macro_name).ok_or_else(|| ParseError::new_with_pos(&format!("Inline macro '{}' not found to insert!", macro_name), event.data_location) )?;
-                        event_data.replace_range(macro_location, macro_value);
+                ) {
+                    Ok(macro_value) => {
+                        segments.push(DataSegment {data: event_data.len()..event_data.len() + found.start() - rest_start, macro_name: None, source: event.data_location.1 + rest_start..macro_location.start});
+                        event_data.push_str(&event.data[rest_start..found.start()]);
+                        segments.push(DataSegment {data: event_data.len()..event_data.len() + macro_value.len(), macro_name: Some(macro_name), source: macro_location});
+                        event_data.push_str(&macro_value);
+                        rest_start = found.end();
                     }
-                    // Parse objects and save event for rendering
-                    events.push(
-                        EventRender {
-                            trigger: event.trigger.clone(),
-                            objects: parse_objects(&event_data).map_err(|err| ParseError::new_with_pos_source("Invalid event data!", event.data_location, err) )?
-                        }
-                    );
+                    Err(err) => {
+                        let (code, err) = insertion_error("Inline", macro_name, err, (event.data_location.0, macro_location.start));
+                        report(diagnostics, collect_errors, code, err, Some((event.data_location.0, macro_location.end)))?;
+                        continue 'events;
+                    }
+                }
+            }
+            segments.push(DataSegment {data: event_data.len()..event_data.len() + event.data.len() - rest_start, macro_name: None, source: event.data_location.1 + rest_start..data_end.1});
+            event_data.push_str(&event.data[rest_start..]);
+            if event_data.len() > limits.max_event_length {
+                report(diagnostics, collect_errors, DiagnosticCode::LimitExceeded, ParseError::new_with_pos_source("Event data too long!", event.data_location, LimitError::new(Limit::EventLength, limits.max_event_length)), Some(data_end))?;
+                continue;
+            }
+            // Parse objects and locate errors in source
+            let mut errors = vec![];
+            let mut unknown_tag_errors = vec![];
+            let objects = match parse_objects(&event_data, Some(&mut errors).filter(|_| collect_errors ), Some(&mut unknown_tag_errors).filter(|_| options.lenient ), custom_tags, limits) {
+                Ok(objects) => objects,
+                Err(error) => {errors.push(error); vec![]}
+            };
+            let locate_error = |(code, err, range): ObjectError| {
+                let (source, origin) = locate(&segments, range);
+                let pos = (event.data_location.0, source.start);
+                let err = match origin {
+                    Some((macro_name, offset)) => ParseError::new_with_pos_source(&format!("Invalid event data from macro '{}' (offset {})!", macro_name, offset), pos, err)
+                        .with_macro_origin(macro_name, offset),
+                    None => ParseError::new_with_pos_source("Invalid event data!", pos, err)
+                };
+                (code, err, Some((event.data_location.0, source.end)))
+            };
+            for (code, err, end) in errors.into_iter().map(locate_error) {
+                report(diagnostics, collect_errors, code, err, end)?;
+            }
+            if options.lenient {
+                diagnostics.extend(unknown_tag_errors.into_iter().map(locate_error).map(|(code, err, end)| Diagnostic {
+                    severity: Severity::Warning,
+                    ..Diagnostic::from_error(code, &err, end)
+                } ));
+            }
+            events.push(
+                EventRender {
+                    trigger: event.trigger,
+                    objects
+                }
+            );
+        }
+        // Check fonts (in stable order to skip the same ones)
+        let resource_limit_error = || LimitError::new(Limit::ResourceBytes, limits.max_resource_bytes);
+        let mut fonts = HashMap::with_capacity(data.fonts.len());
+        let mut resource_bytes = 0;
+        let mut font_entries = data.fonts.into_iter().collect::<Vec<_>>();
+        font_entries.sort_by_cached_key(|(face, _)| (face.family.clone(), face.style.to_string()));
+        for (face, data) in font_entries {
+            if resource_bytes + data.len() > limits.max_resource_bytes {
+                report(diagnostics, collect_errors, DiagnosticCode::LimitExceeded, ParseError::new_with_source(&format!("Font data for '{}' too large!", face), resource_limit_error()), None)?;
+            } else {
+                resource_bytes += data.len();
+                fonts.insert(face, data);
+            }
+        }
+        // Load textures
+        let mut textures = HashMap::with_capacity(data.textures.len());
+        let mut texture_entries = data.textures.into_iter().collect::<Vec<_>>();
+        texture_entries.sort_by(|(name, _), (other_name, _)| name.cmp(other_name) );
+        for (texture_name, texture_data) in texture_entries {
+            match texture_data {
+                TextureDataVariant::Raw(data) => if resource_bytes + data.len() > limits.max_resource_bytes {
+                    report(diagnostics, collect_errors, DiagnosticCode::LimitExceeded, ParseError::new_with_source(&format!("Texture data for '{}' too large!", texture_name), resource_limit_error()), None)?;
+                } else {
+                    resource_bytes += data.len();
+                    textures.insert(texture_name, data);
                 }
-                events
-            },
-            fonts: data.fonts,
-            textures: {
-                let mut textures = HashMap::with_capacity(data.textures.len());
-                for (texture_name, texture_data) in data.textures {
-                    textures.insert(
-                        texture_name.clone(),
-                        match texture_data {
-                            TextureDataVariant::Raw(data) => data,
-                            TextureDataVariant::Url(url) => std::fs::read(&url).map_err(|err| {
-                                ParseError::new_with_source(
-                                    &format!("Texture data for '{}' not loadable from file '{}'!", texture_name, url),
-                                    err
-                                )
-                            })?
+                TextureDataVariant::Url(url) => {
+                    // Read one byte more than allowed to detect exceeding
+                    let mut data = vec![];
+                    match resolver.open(&url).and_then(|reader| reader.take((limits.max_resource_bytes.saturating_sub(resource_bytes) as u64).saturating_add(1)).read_to_end(&mut data) ) {
+                        Ok(_) if resource_bytes + data.len() > limits.max_resource_bytes => report(diagnostics, collect_errors, DiagnosticCode::LimitExceeded, ParseError::new_with_source(
+                            &format!("Texture data for '{}' from url '{}' too large!", texture_name, url),
+                            resource_limit_error()
+                        ), None)?,
+                        Ok(_) => {
+                            resource_bytes += data.len();
+                            textures.insert(texture_name, ResourceData::from(data));
                         }
-                    );
+                        Err(err) => report(diagnostics, collect_errors, DiagnosticCode::InvalidResource, ParseError::new_with_source(
+                            &format!("Texture data for '{}' not loadable from url '{}'!", texture_name, url),
+                            err
+                        ), None)?
+                    }
                 }
-                textures
             }
+        }
+        // Build render data
+        Ok(SsbRender {
+            target_width: data.target_width,
+            target_height: data.target_height,
+            target_depth: data.target_depth,
+            target_view: data.target_view,
+            target_framerate: data.target_framerate,
+            events,
+            fonts,
+            textures
         })
     }
 }
 
+/// Options of converting SSB data to render data, all disabled by default (like [SsbRender::try_from](struct.SsbRender.html#method.try_from)).
+#[derive(Default, Clone)]
+pub struct RenderOptions<'a> {
+    limits: ParseLimits,
+    resolver: Option<&'a dyn ResourceResolver>,
+    registry: Option<&'a TagRegistry>,
+    lenient: bool,
+    diagnostics: bool
+}
+impl<'a> RenderOptions<'a> {
+    /// New options with defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Use custom limits (f.e. stricter for untrusted input).
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+    /// Load texture urls by given resolver (instead of relative to the working directory).
+    pub fn resolver(mut self, resolver: &'a dyn ResourceResolver) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+    /// Parse custom tags by given registry.
+    pub fn registry(mut self, registry: &'a TagRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+    /// Keep unknown tags (f.e. of newer spec versions or extensions) as [`EventObject::TagUnknown`] with a warning for each.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+    /// Continue on errors, skipping invalid parts, and collect all found problems.
+    ///
+    /// Errors are unknown or recursive macros, invalid tags & geometries and unloadable textures.
+    /// Warnings are unused macros and events overlapping by the same id.
+    pub fn diagnostics(mut self, diagnostics: bool) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+}
+impl fmt::Debug for RenderOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RenderOptions")
+            .field("limits", &self.limits)
+            .field("resolver", &self.resolver.is_some())
+            .field("registry", &self.registry)
+            .field("lenient", &self.lenient)
+            .field("diagnostics", &self.diagnostics)
+            .finish()
+    }
+}
+
+// Collect error as diagnostic or fail with it
+fn report(diagnostics: &mut Vec<Diagnostic>, collect_errors: bool, code: DiagnosticCode, err: ParseError, end: Option<(usize, usize)>) -> Result<(), ParseError> {
+    if collect_errors {
+        diagnostics.push(Diagnostic::from_error(code, &err, end));
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+
+// Error of macro insertion by kind (base or inline)
+fn insertion_error(kind: &str, macro_name: &str, err: MacroError, pos: (usize, usize)) -> (DiagnosticCode, ParseError) {
+    match err {
+        MacroError::ArityMismatch(_, parameters_count, arguments_count) => (
+            DiagnosticCode::MacroArityMismatch,
+            ParseError::new_with_pos(&format!("{} macro '{}' requires {} arguments, got {}!", kind, macro_name, parameters_count, arguments_count), pos)
+        ),
+        MacroError::LimitExceeded(_, limit_error) => (
+            DiagnosticCode::LimitExceeded,
+            ParseError::new_with_pos_source(&format!("{} macro '{}' too large to insert!", kind, macro_name), pos, limit_error)
+        ),
+        _ => (
+            DiagnosticCode::UnknownMacro,
+            ParseError::new_with_pos(&format!("{} macro '{}' not found to insert!", kind, macro_name), pos)
+        )
+    }
+}
+
+
+// Macro insertion tracking
+struct DataSegment<'a> {
+    data: Range<usize>,
+    macro_name: Option<&'a str>,
+    source: Range<usize>
+}
+// Locate event data range in source line, inside macros by reference & offset
+fn locate<'a>(segments: &[DataSegment<'a>], range: Range<usize>) -> (Range<usize>, Option<(&'a str, usize)>) {
+    match segments.iter().rev().find(|segment| segment.data.start <= range.start ) {
+        Some(DataSegment {data, macro_name: Some(macro_name), source}) => (source.clone(), Some((macro_name, range.start - data.start))),
+        Some(DataSegment {data, macro_name: None, source}) => (
+            source.start + range.start - data.start..source.start + range.end.min(data.end).max(range.start) - data.start,
+            None
+        ),
+        None => (range, None)
+    }
+}
+
 
 // Objects parsing
-fn parse_objects(event_data: &str) -> Result<Vec<EventObject>, ParseError> {
+impl FromStr for EventObject {
+    type Err = ParseError;
+    /// Parse exactly one object from event data (see `Display`).
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let mut objects = Self::parse_many(data)?;
+        if objects.len() == 1 {
+            Ok(objects.remove(0))
+        } else {
+            Err(ParseError::new(&format!("Expected one event object, found {}!", objects.len())))
+        }
+    }
+}
+impl EventObject {
+    /// Parse objects from event data (without macros), error positions in line 0.
+    pub fn parse_many(data: &str) -> Result<Vec<Self>, ParseError> {
+        parse_objects(data, None, None, None, &ParseLimits::default())
+            .map_err(|(_, err, range)| ParseError::new_with_pos_source("Invalid event data!", (0, range.start), err) )
+    }
+}
+type ObjectError = (DiagnosticCode, ParseError, Range<usize>);
+fn parse_objects(event_data: &str, mut errors: Option<&mut Vec<ObjectError>>, mut unknown_tags: Option<&mut Vec<ObjectError>>, custom_tags: Option<&TagRegistry>, limits: &ParseLimits) -> Result<Vec<EventObject>, ObjectError> {
     let mut objects = vec![];
+    let keep_unknown = unknown_tags.is_some();
     let mut mode = Mode::default();
-    for (is_tag, data) in EscapedText::new(event_data).iter() {
+    let escaped_text = EscapedText::new(event_data);
+    let mut fail = |code, err, range: Range<usize>| {
+        let error = (code, err, escaped_text.source_offset(range.start)..escaped_text.source_offset(range.end));
+        match errors.as_mut() {
+            Some(errors) => {errors.push(error); Ok(())}
+            None => Err(error)
+        }
+    };
+    for (is_tag, data_start, data) in escaped_text.iter() {
         if is_tag {
-            parse_tags(data, &mut objects, Some(&mut mode))?;
-        } else {
-            parse_geometries(data, &mut objects, &mode)?;
+            for tag in TagsIterator::with_max_nesting(data, limits.max_tag_nesting) {
+                let (tag_start, tag_name, tag_value) = match tag {
+                    Ok(tag) => tag,
+                    Err(nesting_pos) => {
+                        fail(DiagnosticCode::LimitExceeded, ParseError::new_with_source("Tags nested too deep!", LimitError::new(Limit::TagNesting, limits.max_tag_nesting)), data_start + nesting_pos..data_start + data.len())?;
+                        break;
+                    }
+                };
+                let tag_range = data_start + tag_start..data_start + tag_start + tag_name.len() + tag_value.map_or(0, |value| value.len() + 1 /* TAG_ASSIGN */);
+                let objects_start = objects.len();
+                match parse_tag(tag_name, tag_value, &mut objects, Some(&mut mode), custom_tags, keep_unknown) {
+                    Ok(true) => if let Some(unknown_tags) = unknown_tags.as_mut() {
+                        // Kept unknown tags, maybe nested into animation
+                        let mut names = vec![];
+                        unknown_tag_names(&objects[objects_start..], &mut names);
+                        unknown_tags.extend(names.into_iter().map(|name| (
+                            DiagnosticCode::UnknownTag,
+                            ParseError::new(&format!("Unknown tag '{}' kept!", name)),
+                            escaped_text.source_offset(tag_range.start)..escaped_text.source_offset(tag_range.end)
+                        ) ));
+                    }
+                    Ok(false) => fail(DiagnosticCode::UnknownTag, ParseError::new(&format!("Invalid tag '{}'!", tag_name)), tag_range)?,
+                    Err(err) => fail(DiagnosticCode::InvalidTag, err, tag_range)?
+                }
+            }
+        } else if let Err(err) = parse_geometries(data, &mut objects, &mode) {
+            fail(DiagnosticCode::InvalidGeometry, err, data_start..data_start + data.len())?;
         }
     }
     Ok(objects)
 }
-fn parse_tags<'a>(data: &str, objects: &'a mut Vec<EventObject>, mut mode: Option<&mut Mode>) -> Result<&'a mut Vec<EventObject>, ParseError> {
-    for (tag_name, tag_value) in TagsIterator::new(data) {
-        #[allow(clippy::redundant_closure)] // Remove wrong hint because of missing lifetime on closure reduction
-        match tag_name {
-            "font" => objects.push(EventObject::TagFont(
-                tag_value.map_else_err_str(|value| Some(value.to_owned()) )
-                .map_err(|value| ParseError::new(&format!("Invalid font '{}'!", value)) )?
-            )),
-            "size" => objects.push(EventObject::TagSize(
-                tag_value.map_or_err_str(|value| value.parse() )
-                .map_err(|value| ParseError::new(&format!("Invalid size '{}'!", value)) )?
-            )),
-            "bold" => objects.push(EventObject::TagBold(
-                tag_value.map_or_err_str(|value| bool_from_str(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid bold '{}'!", value)) )?
-            )),
-            "italic" => objects.push(EventObject::TagItalic(
-                tag_value.map_or_err_str(|value| bool_from_str(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid italic '{}'!", value)) )?
-            )),
-            "underline" => objects.push(EventObject::TagUnderline(
-                tag_value.map_or_err_str(|value| bool_from_str(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid underline '{}'!", value)) )?
-            )),
-            "strikeout" => objects.push(EventObject::TagStrikeout(
-                tag_value.map_or_err_str(|value| bool_from_str(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid strikeout '{}'!", value)) )?
-            )),
-            "position" => objects.push(EventObject::TagPosition(
-                tag_value.map_else_err_str(|value| {
-                    let mut tokens = value.splitn(3, VALUE_SEPARATOR);
-                    Some(Point3D {
-                        x: tokens.next()?.parse().ok()?,
-                        y: tokens.next()?.parse().ok()?,
-                        z: tokens.next().or(Some("0")).and_then(|value| value.parse().ok())?
-                    })
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid position '{}'!", value)) )?
-            )),
-            "alignment" => objects.push(EventObject::TagAlignment(
-                tag_value.map_else_err_str(|value| {
-                    Some(
-                        if let Some(sep) = value.find(VALUE_SEPARATOR) {
-                            Alignment::Offset(Point2D {
-                                x: value[..sep].parse().ok()?,
-                                y: value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?,
-                            })
-                        } else {
-                            Alignment::Numpad(Numpad::try_from(value.parse::<u8>().ok()?).ok()?)
-                        }
-                    )
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid alignment '{}'!", value)) )?
-            )),
-            "margin" => objects.push(EventObject::TagMargin(
-                tag_value.map_else_err_str(|value| {
-                    let mut tokens = value.splitn(4, VALUE_SEPARATOR);
-                    Some(
-                        if let (Some(top), Some(right), Some(bottom), Some(left)) = (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
-                            Margin::All(
-                                top.parse().ok()?,
-                                right.parse().ok()?,
-                                bottom.parse().ok()?,
-                                left.parse().ok()?
-                            )
-                        } else {
-                            let margin = value.parse().ok()?;
-                            Margin::All(
-                                margin,
-                                margin,
-                                margin,
-                                margin
-                            )
-                        }
-                    )
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid margin '{}'!", value)) )?
-            )),
-            "margin-top" => objects.push(EventObject::TagMargin(
-                tag_value.map_else_err_str(|value| Some(Margin::Top(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid margin top '{}'!", value)) )?
-            )),
-            "margin-right" => objects.push(EventObject::TagMargin(
-                tag_value.map_else_err_str(|value| Some(Margin::Right(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid margin right '{}'!", value)) )?
-            )),
-            "margin-bottom" => objects.push(EventObject::TagMargin(
-                tag_value.map_else_err_str(|value| Some(Margin::Bottom(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid margin bottom '{}'!", value)) )?
-            )),
-            "margin-left" => objects.push(EventObject::TagMargin(
-                tag_value.map_else_err_str(|value| Some(Margin::Left(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid margin left '{}'!", value)) )?
-            )),
-            "wrap-style" => objects.push(EventObject::TagWrapStyle(
-                tag_value.map_or_err_str(|value| WrapStyle::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid wrap style '{}'!", value)) )?
-            )),
-            "direction" => objects.push(EventObject::TagDirection(
-                tag_value.map_or_err_str(|value| Direction::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid direction '{}'!", value)) )?
-            )),
-            "space" => objects.push(EventObject::TagSpace(
-                tag_value.map_else_err_str(|value| {
-                    Some(
-                        if let Some(sep) = value.find(VALUE_SEPARATOR) {
-                            Space::All(
-                                value[..sep].parse().ok()?,
-                                value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
-                            )
-                        } else {
-                            let space = value.parse().ok()?;
-                            Space::All(space, space)
-                        }
-                    )
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid space '{}'!", value)) )?
-            )),
-            "space-h" => objects.push(EventObject::TagSpace(
-                tag_value.map_else_err_str(|value| Some(Space::Horizontal(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid space horizontal '{}'!", value)) )?
-            )),
-            "space-v" => objects.push(EventObject::TagSpace(
-                tag_value.map_else_err_str(|value| Some(Space::Vertical(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid space vertical '{}'!", value)) )?
-            )),
-            "rotate-x" => objects.push(EventObject::TagRotate(
-                tag_value.map_else_err_str(|value| Some(Rotate::X(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid rotate x '{}'!", value)) )?
-            )),
-            "rotate-y" => objects.push(EventObject::TagRotate(
-                tag_value.map_else_err_str(|value| Some(Rotate::Y(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid rotate y '{}'!", value)) )?
-            )),
-            "rotate-z" => objects.push(EventObject::TagRotate(
-                tag_value.map_else_err_str(|value| Some(Rotate::Z(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid rotate z '{}'!", value)) )?
-            )),
-            "scale" => objects.push(EventObject::TagScale(
-                tag_value.map_else_err_str(|value| {
-                    let mut tokens = value.splitn(3, VALUE_SEPARATOR);
-                    Some(Scale::All(
-                        tokens.next()?.parse().ok()?,
-                        tokens.next()?.parse().ok()?,
-                        tokens.next()?.parse().ok()?
-                    ))
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid scale '{}'!", value)) )?
-            )),
-            "scale-x" => objects.push(EventObject::TagScale(
-                tag_value.map_else_err_str(|value| Some(Scale::X(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid scale x '{}'!", value)) )?
-            )),
-            "scale-y" => objects.push(EventObject::TagScale(
-                tag_value.map_else_err_str(|value| Some(Scale::Y(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid scale y '{}'!", value)) )?
-            )),
-            "scale-z" => objects.push(EventObject::TagScale(
-                tag_value.map_else_err_str(|value| Some(Scale::Z(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid scale z '{}'!", value)) )?
-            )),
-            "translate" => objects.push(EventObject::TagTranslate(
-                tag_value.map_else_err_str(|value| {
-                    let mut tokens = value.splitn(3, VALUE_SEPARATOR);
-                    Some(Translate::All(
-                        tokens.next()?.parse().ok()?,
-                        tokens.next()?.parse().ok()?,
-                        tokens.next()?.parse().ok()?
-                    ))
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid translate '{}'!", value)) )?
-            )),
-            "translate-x" => objects.push(EventObject::TagTranslate(
-                tag_value.map_else_err_str(|value| Some(Translate::X(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid translate x '{}'!", value)) )?
-            )),
-            "translate-y" => objects.push(EventObject::TagTranslate(
-                tag_value.map_else_err_str(|value| Some(Translate::Y(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid translate y '{}'!", value)) )?
-            )),
-            "translate-z" => objects.push(EventObject::TagTranslate(
-                tag_value.map_else_err_str(|value| Some(Translate::Z(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid translate z '{}'!", value)) )?
-            )),
-            "shear" => objects.push(EventObject::TagShear(
-                tag_value.map_else_err_str(|value| {
-                    let sep = value.find(VALUE_SEPARATOR)?;
-                    Some(Shear::All(
-                        value[..sep].parse().ok()?,
-                        value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
-                    ))
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid shear '{}'!", value)) )?
-            )),
-            "shear-x" => objects.push(EventObject::TagShear(
-                tag_value.map_else_err_str(|value| Some(Shear::X(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid shear x '{}'!", value)) )?
-            )),
-            "shear-y" => objects.push(EventObject::TagShear(
-                tag_value.map_else_err_str(|value| Some(Shear::Y(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid shear y '{}'!", value)) )?
-            )),
-            "matrix" => objects.push(EventObject::TagMatrix(
-                tag_value.map_else_err_str(|value| {
-                    let mut tokens = value.splitn(16, VALUE_SEPARATOR).filter_map(|value| value.parse().ok() );
-                    Some(Box::new([
-                        tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?,
-                        tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?,
-                        tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?,
-                        tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?
-                    ]))
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid matrix '{}'!", value)) )?
-            )),
-            "reset" => objects.push(Some(EventObject::TagReset)
-                .filter(|_| tag_value.is_none() )
-                .ok_or_else(|| ParseError::new("Reset must have no value!") )?
-            ),
-            "mode" if mode.is_some() => **mode.as_mut().expect("Impossible :O Checked right before!") =
-                tag_value.map_or_err_str(|value| Mode::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid mode '{}'!", value)) )?,
-            "border" => objects.push(EventObject::TagBorder(
-                tag_value.map_else_err_str(|value| {
-                    Some(
-                        if let Some(sep) = value.find(VALUE_SEPARATOR) {
-                            Border::All(
-                                value[..sep].parse().ok()?,
-                                value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
-                            )
-                        } else {
-                            let border = value.parse().ok()?;
-                            Border::All(border, border)
-                        }
-                    )
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid border '{}'!", value)) )?
-            )),
-            "border-h" => objects.push(EventObject::TagBorder(
-                tag_value.map_else_err_str(|value| Some(Border::Horizontal(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid border horizontal '{}'!", value)) )?
-            )),
-            "border-v" => objects.push(EventObject::TagBorder(
-                tag_value.map_else_err_str(|value| Some(Border::Vertical(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid border vertical '{}'!", value)) )?
-            )),
-            "join" => objects.push(EventObject::TagJoin(
-                tag_value.map_or_err_str(|value| Join::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid join '{}'!", value)) )?
-            )),
-            "cap" => objects.push(EventObject::TagCap(
-                tag_value.map_or_err_str(|value| Cap::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid cap '{}'!", value)) )?
-            )),
-            "texture" => objects.push(EventObject::TagTexture(
-                tag_value.map(ToOwned::to_owned).unwrap_or_else(|| "".to_owned() )
-            )),
-            "texfill" => objects.push(
-                tag_value.map_else_err_str(|value| {
-                    let mut tokens = value.splitn(5, VALUE_SEPARATOR);
-                    Some(EventObject::TagTexFill {
-                        x0: tokens.next()?.parse().ok()?,
-                        y0: tokens.next()?.parse().ok()?,
-                        x1: tokens.next()?.parse().ok()?,
-                        y1: tokens.next()?.parse().ok()?,
-                        wrap: TextureWrapping::try_from(tokens.next()?).ok()?
-                    })
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid texture filling '{}'!", value)) )?
-            ),
-            "color" | "bordercolor" => objects.push({
-                let color = tag_value.map_or_err_str(|value| {
-                    let mut tokens = value.splitn(5, VALUE_SEPARATOR);
-                    Ok(match (tokens.next(), tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
-                        (Some(color1), Some(color2), Some(color3), Some(color4), Some(color5)) =>
-                            Color::CornersWithStop([
-                                rgb_from_str(color1)?,
-                                rgb_from_str(color2)?,
-                                rgb_from_str(color3)?,
-                                rgb_from_str(color4)?,
-                                rgb_from_str(color5)?
-                            ]),
-                        (Some(color1), Some(color2), Some(color3), Some(color4), None) =>
-                            Color::Corners([
-                                rgb_from_str(color1)?,
-                                rgb_from_str(color2)?,
-                                rgb_from_str(color3)?,
-                                rgb_from_str(color4)?
-                            ]),
-                        (Some(color1), Some(color2), Some(color3), None, None) =>
-                            Color::LinearWithStop([
-                                rgb_from_str(color1)?,
-                                rgb_from_str(color2)?,
-                                rgb_from_str(color3)?
-                            ]),
-                        (Some(color1), Some(color2), None, None, None) =>
-                            Color::Linear([
-                                rgb_from_str(color1)?,
-                                rgb_from_str(color2)?
-                            ]),
-                        (Some(color1), None, None, None, None) =>
-                            Color::Mono(
-                                rgb_from_str(color1)?
-                            ),
-                        _ => return Err(())
-                    })
-                } );
-                if tag_name == "color" {
-                    EventObject::TagColor(
-                        color.map_err(|value| ParseError::new(&format!("Invalid color '{}'!", value)) )?
-                    )
-                } else {
-                    EventObject::TagBorderColor(
-                        color.map_err(|value| ParseError::new(&format!("Invalid border color '{}'!", value)) )?
-                    )
-                }
-            }),
-            "alpha" | "borderalpha" => objects.push({
-                let alpha = tag_value.map_or_err_str(|value| {
-                    let mut tokens = value.splitn(5, VALUE_SEPARATOR);
-                    Ok(match (tokens.next(), tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
-                        (Some(alpha1), Some(alpha2), Some(alpha3), Some(alpha4), Some(alpha5)) =>
-                            Alpha::CornersWithStop([
-                                alpha_from_str(alpha1)?,
-                                alpha_from_str(alpha2)?,
-                                alpha_from_str(alpha3)?,
-                                alpha_from_str(alpha4)?,
-                                alpha_from_str(alpha5)?
-                            ]),
-                        (Some(alpha1), Some(alpha2), Some(alpha3), Some(alpha4), None) =>
-                            Alpha::Corners([
-                                alpha_from_str(alpha1)?,
-                                alpha_from_str(alpha2)?,
-                                alpha_from_str(alpha3)?,
-                                alpha_from_str(alpha4)?
-                            ]),
-                        (Some(alpha1), Some(alpha2), Some(alpha3), None, None) =>
-                            Alpha::LinearWithStop([
-                                alpha_from_str(alpha1)?,
-                                alpha_from_str(alpha2)?,
-                                alpha_from_str(alpha3)?
-                            ]),
-                        (Some(alpha1), Some(alpha2), None, None, None) =>
-                            Alpha::Linear([
-                                alpha_from_str(alpha1)?,
-                                alpha_from_str(alpha2)?
-                            ]),
-                        (Some(alpha1), None, None, None, None) =>
-                            Alpha::Mono(
-                                alpha_from_str(alpha1)?
-                            ),
-                        _ => return Err(())
-                    })
-                } );
-                if tag_name == "alpha" {
-                    EventObject::TagAlpha(
-                        alpha.map_err(|value| ParseError::new(&format!("Invalid alpha '{}'!", value)) )?
-                    )
-                } else {
-                    EventObject::TagBorderAlpha(
-                        alpha.map_err(|value| ParseError::new(&format!("Invalid border coloalphar '{}'!", value)) )?
-                    )
-                }
-            }),
-            "blur" => objects.push(EventObject::TagBlur(
-                tag_value.map_else_err_str(|value| {
-                    Some(
-                        if let Some(sep) = value.find(VALUE_SEPARATOR) {
-                            Blur::All(
-                                value[..sep].parse().ok()?,
-                                value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
-                            )
-                        } else {
-                            let blur = value.parse().ok()?;
-                            Blur::All(blur, blur)
-                        }
-                    )
-                } )
-                .map_err(|value| ParseError::new(&format!("Invalid blur '{}'!", value)) )?
-            )),
-            "blur-h" => objects.push(EventObject::TagBlur(
-                tag_value.map_else_err_str(|value| Some(Blur::Horizontal(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid blur horizontal '{}'!", value)) )?
-            )),
-            "blur-v" => objects.push(EventObject::TagBlur(
-                tag_value.map_else_err_str(|value| Some(Blur::Vertical(value.parse().ok()?)) )
-                .map_err(|value| ParseError::new(&format!("Invalid blur vertical '{}'!", value)) )?
-            )),
-            "blend" => objects.push(EventObject::TagBlend(
-                tag_value.map_or_err_str(|value| Blend::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid blend '{}'!", value)) )?
-            )),
-            "target" => objects.push(EventObject::TagTarget(
-                tag_value.map_or_err_str(|value| Target::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid target '{}'!", value)) )?
-            )),
-            "mask-mode" => objects.push(EventObject::TagMaskMode(
-                tag_value.map_or_err_str(|value| MaskMode::try_from(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid mask mode '{}'!", value)) )?
-            )),
-            "mask-clear" => objects.push(Some(EventObject::TagMaskClear)
-                .filter(|_| tag_value.is_none() )
-                .ok_or_else(|| ParseError::new("Mask clear must have no value!") )?
-            ),
-            "animate" if mode.is_some() => objects.push(EventObject::TagAnimate(
-                tag_value.map_or(Err(("", None)), |value| {
-                    let captures = ANIMATE_PATTERN.captures(value).ok_or_else(|| (value, None) )?;
-                    Ok(Box::new(Animate {
-                        time: match (captures.name("S"), captures.name("E")) {
-                            (Some(start_time), Some(end_time)) => Some((
-                                start_time.as_str().parse().map_err(|_| (value, None) )?,
-                                end_time.as_str().parse().map_err(|_| (value, None) )?
-                            )),
-                            _ => None
-                        },
-                        formula: captures.name("F").map(|value| value.as_str().to_owned() ),
-                        tags: {
-                            let mut tags = vec![];
-                            parse_tags(captures.name("T").ok_or_else(|| (value, None) )?.as_str(), &mut tags, None).map_err(|err| (value, Some(err)) )?;
-                            tags
-                        }
-                    }))
-                })
-                .map_err(|(value,err)| {
-                    let value = format!("Invalid animate '{}'!", value);
-                    err.map(|err| ParseError::new_with_source(&value, err) ).unwrap_or_else(|| ParseError::new(&value) )
-                } )?
-            )),
-            "k" => objects.push(EventObject::TagKaraoke(
-                tag_value.map_or_err_str(|value| value.parse() )
-                .map_err(|value| ParseError::new(&format!("Invalid karaoke '{}'!", value)) )?
-            )),
-            "kset" => objects.push(EventObject::TagKaraokeSet(
-                tag_value.map_or_err_str(|value| value.parse() )
-                .map_err(|value| ParseError::new(&format!("Invalid karaoke set '{}'!", value)) )?
-            )),
-            "kcolor" => objects.push(EventObject::TagKaraokeColor(
-                tag_value.map_or_err_str(|value| rgb_from_str(value) )
-                .map_err(|value| ParseError::new(&format!("Invalid karaoke color '{}'!", value)) )?
-            )),
-            _ => return Err(ParseError::new(&format!("Invalid tag '{}'!", tag_name)))
+fn unknown_tag_names<'a>(objects: &'a [EventObject], names: &mut Vec<&'a str>) {
+    for object in objects {
+        match object {
+            EventObject::TagUnknown{name, ..} => names.push(name),
+            EventObject::TagAnimate(animate) => unknown_tag_names(&animate.tags, names),
+            _ => ()
+        }
+    }
+}
+fn parse_tags<'a>(data: &str, objects: &'a mut Vec<EventObject>, mut mode: Option<&mut Mode>, custom_tags: Option<&TagRegistry>, keep_unknown: bool) -> Result<&'a mut Vec<EventObject>, ParseError> {
+    for (_, tag_name, tag_value) in TagsIterator::new(data).flatten() {
+        if !parse_tag(tag_name, tag_value, objects, mode.as_deref_mut(), custom_tags, keep_unknown)? {
+            return Err(ParseError::new(&format!("Invalid tag '{}'!", tag_name)));
         }
     }
     Ok(objects)
 }
+fn parse_tag(tag_name: &str, tag_value: Option<&str>, objects: &mut Vec<EventObject>, mut mode: Option<&mut Mode>, custom_tags: Option<&TagRegistry>, keep_unknown: bool) -> Result<bool, ParseError> {
+    #[allow(clippy::redundant_closure)] // Remove wrong hint because of missing lifetime on closure reduction
+    match tag_name {
+        "font" => objects.push(EventObject::TagFont(
+            tag_value.map_else_err_str(|value| Some(value.to_owned()) )
+            .map_err(|value| ParseError::new(&format!("Invalid font '{}'!", value)) )?
+        )),
+        "size" => objects.push(EventObject::TagSize(
+            tag_value.map_or_err_str(|value| value.parse() )
+            .map_err(|value| ParseError::new(&format!("Invalid size '{}'!", value)) )?
+        )),
+        "bold" => objects.push(EventObject::TagBold(
+            tag_value.map_or_err_str(|value| bool_from_str(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid bold '{}'!", value)) )?
+        )),
+        "italic" => objects.push(EventObject::TagItalic(
+            tag_value.map_or_err_str(|value| bool_from_str(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid italic '{}'!", value)) )?
+        )),
+        "underline" => objects.push(EventObject::TagUnderline(
+            tag_value.map_or_err_str(|value| bool_from_str(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid underline '{}'!", value)) )?
+        )),
+        "strikeout" => objects.push(EventObject::TagStrikeout(
+            tag_value.map_or_err_str(|value| bool_from_str(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid strikeout '{}'!", value)) )?
+        )),
+        "position" => objects.push(EventObject::TagPosition(
+            tag_value.map_else_err_str(|value| {
+                let mut tokens = value.splitn(3, VALUE_SEPARATOR);
+                Some(Point3D {
+                    x: tokens.next()?.parse().ok()?,
+                    y: tokens.next()?.parse().ok()?,
+                    z: tokens.next().or(Some("0")).and_then(|value| value.parse().ok())?
+                })
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid position '{}'!", value)) )?
+        )),
+        "alignment" => objects.push(EventObject::TagAlignment(
+            tag_value.map_else_err_str(|value| {
+                Some(
+                    if let Some(sep) = value.find(VALUE_SEPARATOR) {
+                        Alignment::Offset(Point2D {
+                            x: value[..sep].parse().ok()?,
+                            y: value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?,
+                        })
+                    } else {
+                        Alignment::Numpad(Numpad::try_from(value.parse::<u8>().ok()?).ok()?)
+                    }
+                )
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid alignment '{}'!", value)) )?
+        )),
+        "margin" => objects.push(EventObject::TagMargin(
+            tag_value.map_else_err_str(|value| {
+                let mut tokens = value.splitn(4, VALUE_SEPARATOR);
+                Some(
+                    if let (Some(top), Some(right), Some(bottom), Some(left)) = (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+                        Margin::All(
+                            top.parse().ok()?,
+                            right.parse().ok()?,
+                            bottom.parse().ok()?,
+                            left.parse().ok()?
+                        )
+                    } else {
+                        let margin = value.parse().ok()?;
+                        Margin::All(
+                            margin,
+                            margin,
+                            margin,
+                            margin
+                        )
+                    }
+                )
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid margin '{}'!", value)) )?
+        )),
+        "margin-top" => objects.push(EventObject::TagMargin(
+            tag_value.map_else_err_str(|value| Some(Margin::Top(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid margin top '{}'!", value)) )?
+        )),
+        "margin-right" => objects.push(EventObject::TagMargin(
+            tag_value.map_else_err_str(|value| Some(Margin::Right(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid margin right '{}'!", value)) )?
+        )),
+        "margin-bottom" => objects.push(EventObject::TagMargin(
+            tag_value.map_else_err_str(|value| Some(Margin::Bottom(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid margin bottom '{}'!", value)) )?
+        )),
+        "margin-left" => objects.push(EventObject::TagMargin(
+            tag_value.map_else_err_str(|value| Some(Margin::Left(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid margin left '{}'!", value)) )?
+        )),
+        "wrap-style" => objects.push(EventObject::TagWrapStyle(
+            tag_value.map_or_err_str(|value| WrapStyle::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid wrap style '{}'!", value)) )?
+        )),
+        "direction" => objects.push(EventObject::TagDirection(
+            tag_value.map_or_err_str(|value| Direction::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid direction '{}'!", value)) )?
+        )),
+        "space" => objects.push(EventObject::TagSpace(
+            tag_value.map_else_err_str(|value| {
+                Some(
+                    if let Some(sep) = value.find(VALUE_SEPARATOR) {
+                        Space::All(
+                            value[..sep].parse().ok()?,
+                            value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
+                        )
+                    } else {
+                        let space = value.parse().ok()?;
+                        Space::All(space, space)
+                    }
+                )
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid space '{}'!", value)) )?
+        )),
+        "space-h" => objects.push(EventObject::TagSpace(
+            tag_value.map_else_err_str(|value| Some(Space::Horizontal(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid space horizontal '{}'!", value)) )?
+        )),
+        "space-v" => objects.push(EventObject::TagSpace(
+            tag_value.map_else_err_str(|value| Some(Space::Vertical(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid space vertical '{}'!", value)) )?
+        )),
+        "rotate-x" => objects.push(EventObject::TagRotate(
+            tag_value.map_else_err_str(|value| Some(Rotate::X(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid rotate x '{}'!", value)) )?
+        )),
+        "rotate-y" => objects.push(EventObject::TagRotate(
+            tag_value.map_else_err_str(|value| Some(Rotate::Y(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid rotate y '{}'!", value)) )?
+        )),
+        "rotate-z" => objects.push(EventObject::TagRotate(
+            tag_value.map_else_err_str(|value| Some(Rotate::Z(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid rotate z '{}'!", value)) )?
+        )),
+        "scale" => objects.push(EventObject::TagScale(
+            tag_value.map_else_err_str(|value| {
+                let mut tokens = value.splitn(3, VALUE_SEPARATOR);
+                Some(Scale::All(
+                    tokens.next()?.parse().ok()?,
+                    tokens.next()?.parse().ok()?,
+                    tokens.next()?.parse().ok()?
+                ))
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid scale '{}'!", value)) )?
+        )),
+        "scale-x" => objects.push(EventObject::TagScale(
+            tag_value.map_else_err_str(|value| Some(Scale::X(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid scale x '{}'!", value)) )?
+        )),
+        "scale-y" => objects.push(EventObject::TagScale(
+            tag_value.map_else_err_str(|value| Some(Scale::Y(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid scale y '{}'!", value)) )?
+        )),
+        "scale-z" => objects.push(EventObject::TagScale(
+            tag_value.map_else_err_str(|value| Some(Scale::Z(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid scale z '{}'!", value)) )?
+        )),
+        "translate" => objects.push(EventObject::TagTranslate(
+            tag_value.map_else_err_str(|value| {
+                let mut tokens = value.splitn(3, VALUE_SEPARATOR);
+                Some(Translate::All(
+                    tokens.next()?.parse().ok()?,
+                    tokens.next()?.parse().ok()?,
+                    tokens.next()?.parse().ok()?
+                ))
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid translate '{}'!", value)) )?
+        )),
+        "translate-x" => objects.push(EventObject::TagTranslate(
+            tag_value.map_else_err_str(|value| Some(Translate::X(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid translate x '{}'!", value)) )?
+        )),
+        "translate-y" => objects.push(EventObject::TagTranslate(
+            tag_value.map_else_err_str(|value| Some(Translate::Y(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid translate y '{}'!", value)) )?
+        )),
+        "translate-z" => objects.push(EventObject::TagTranslate(
+            tag_value.map_else_err_str(|value| Some(Translate::Z(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid translate z '{}'!", value)) )?
+        )),
+        "shear" => objects.push(EventObject::TagShear(
+            tag_value.map_else_err_str(|value| {
+                let sep = value.find(VALUE_SEPARATOR)?;
+                Some(Shear::All(
+                    value[..sep].parse().ok()?,
+                    value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
+                ))
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid shear '{}'!", value)) )?
+        )),
+        "shear-x" => objects.push(EventObject::TagShear(
+            tag_value.map_else_err_str(|value| Some(Shear::X(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid shear x '{}'!", value)) )?
+        )),
+        "shear-y" => objects.push(EventObject::TagShear(
+            tag_value.map_else_err_str(|value| Some(Shear::Y(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid shear y '{}'!", value)) )?
+        )),
+        "matrix" => objects.push(EventObject::TagMatrix(
+            tag_value.map_else_err_str(|value| {
+                let mut tokens = value.splitn(16, VALUE_SEPARATOR).filter_map(|value| value.parse().ok() );
+                Some(Box::new([
+                    tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?,
+                    tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?,
+                    tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?,
+                    tokens.next()?, tokens.next()?, tokens.next()?, tokens.next()?
+                ]))
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid matrix '{}'!", value)) )?
+        )),
+        "reset" => objects.push(Some(EventObject::TagReset)
+            .filter(|_| tag_value.is_none() )
+            .ok_or_else(|| ParseError::new("Reset must have no value!") )?
+        ),
+        "mode" if mode.is_some() => **mode.as_mut().expect("Impossible :O Checked right before!") =
+            tag_value.map_or_err_str(|value| Mode::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid mode '{}'!", value)) )?,
+        "border" => objects.push(EventObject::TagBorder(
+            tag_value.map_else_err_str(|value| {
+                Some(
+                    if let Some(sep) = value.find(VALUE_SEPARATOR) {
+                        Border::All(
+                            value[..sep].parse().ok()?,
+                            value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
+                        )
+                    } else {
+                        let border = value.parse().ok()?;
+                        Border::All(border, border)
+                    }
+                )
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid border '{}'!", value)) )?
+        )),
+        "border-h" => objects.push(EventObject::TagBorder(
+            tag_value.map_else_err_str(|value| Some(Border::Horizontal(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid border horizontal '{}'!", value)) )?
+        )),
+        "border-v" => objects.push(EventObject::TagBorder(
+            tag_value.map_else_err_str(|value| Some(Border::Vertical(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid border vertical '{}'!", value)) )?
+        )),
+        "join" => objects.push(EventObject::TagJoin(
+            tag_value.map_or_err_str(|value| Join::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid join '{}'!", value)) )?
+        )),
+        "cap" => objects.push(EventObject::TagCap(
+            tag_value.map_or_err_str(|value| Cap::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid cap '{}'!", value)) )?
+        )),
+        "texture" => objects.push(EventObject::TagTexture(
+            tag_value.map(ToOwned::to_owned).unwrap_or_else(|| "".to_owned() )
+        )),
+        "texfill" => objects.push(
+            tag_value.map_else_err_str(|value| {
+                let mut tokens = value.splitn(5, VALUE_SEPARATOR);
+                Some(EventObject::TagTexFill {
+                    x0: tokens.next()?.parse().ok()?,
+                    y0: tokens.next()?.parse().ok()?,
+                    x1: tokens.next()?.parse().ok()?,
+                    y1: tokens.next()?.parse().ok()?,
+                    wrap: TextureWrapping::try_from(tokens.next()?).ok()?
+                })
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid texture filling '{}'!", value)) )?
+        ),
+        "color" | "bordercolor" => objects.push({
+            let color = tag_value.map_or_err_str(|value| {
+                let mut tokens = value.splitn(5, VALUE_SEPARATOR);
+                Ok(match (tokens.next(), tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+                    (Some(color1), Some(color2), Some(color3), Some(color4), Some(color5)) =>
+                        Color::CornersWithStop([
+                            rgb_from_str(color1)?,
+                            rgb_from_str(color2)?,
+                            rgb_from_str(color3)?,
+                            rgb_from_str(color4)?,
+                            rgb_from_str(color5)?
+                        ]),
+                    (Some(color1), Some(color2), Some(color3), Some(color4), None) =>
+                        Color::Corners([
+                            rgb_from_str(color1)?,
+                            rgb_from_str(color2)?,
+                            rgb_from_str(color3)?,
+                            rgb_from_str(color4)?
+                        ]),
+                    (Some(color1), Some(color2), Some(color3), None, None) =>
+                        Color::LinearWithStop([
+                            rgb_from_str(color1)?,
+                            rgb_from_str(color2)?,
+                            rgb_from_str(color3)?
+                        ]),
+                    (Some(color1), Some(color2), None, None, None) =>
+                        Color::Linear([
+                            rgb_from_str(color1)?,
+                            rgb_from_str(color2)?
+                        ]),
+                    (Some(color1), None, None, None, None) =>
+                        Color::Mono(
+                            rgb_from_str(color1)?
+                        ),
+                    _ => return Err(())
+                })
+            } );
+            if tag_name == "color" {
+                EventObject::TagColor(
+                    color.map_err(|value| ParseError::new(&format!("Invalid color '{}'!", value)) )?
+                )
+            } else {
+                EventObject::TagBorderColor(
+                    color.map_err(|value| ParseError::new(&format!("Invalid border color '{}'!", value)) )?
+                )
+            }
+        }),
+        "alpha" | "borderalpha" => objects.push({
+            let alpha = tag_value.map_or_err_str(|value| {
+                let mut tokens = value.splitn(5, VALUE_SEPARATOR);
+                Ok(match (tokens.next(), tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+                    (Some(alpha1), Some(alpha2), Some(alpha3), Some(alpha4), Some(alpha5)) =>
+                        Alpha::CornersWithStop([
+                            alpha_from_str(alpha1)?,
+                            alpha_from_str(alpha2)?,
+                            alpha_from_str(alpha3)?,
+                            alpha_from_str(alpha4)?,
+                            alpha_from_str(alpha5)?
+                        ]),
+                    (Some(alpha1), Some(alpha2), Some(alpha3), Some(alpha4), None) =>
+                        Alpha::Corners([
+                            alpha_from_str(alpha1)?,
+                            alpha_from_str(alpha2)?,
+                            alpha_from_str(alpha3)?,
+                            alpha_from_str(alpha4)?
+                        ]),
+                    (Some(alpha1), Some(alpha2), Some(alpha3), None, None) =>
+                        Alpha::LinearWithStop([
+                            alpha_from_str(alpha1)?,
+                            alpha_from_str(alpha2)?,
+                            alpha_from_str(alpha3)?
+                        ]),
+                    (Some(alpha1), Some(alpha2), None, None, None) =>
+                        Alpha::Linear([
+                            alpha_from_str(alpha1)?,
+                            alpha_from_str(alpha2)?
+                        ]),
+                    (Some(alpha1), None, None, None, None) =>
+                        Alpha::Mono(
+                            alpha_from_str(alpha1)?
+                        ),
+                    _ => return Err(())
+                })
+            } );
+            if tag_name == "alpha" {
+                EventObject::TagAlpha(
+                    alpha.map_err(|value| ParseError::new(&format!("Invalid alpha '{}'!", value)) )?
+                )
+            } else {
+                EventObject::TagBorderAlpha(
+                    alpha.map_err(|value| ParseError::new(&format!("Invalid border coloalphar '{}'!", value)) )?
+                )
+            }
+        }),
+        "blur" => objects.push(EventObject::TagBlur(
+            tag_value.map_else_err_str(|value| {
+                Some(
+                    if let Some(sep) = value.find(VALUE_SEPARATOR) {
+                        Blur::All(
+                            value[..sep].parse().ok()?,
+                            value[sep + 1 /* VALUE_SEPARATOR */..].parse().ok()?
+                        )
+                    } else {
+                        let blur = value.parse().ok()?;
+                        Blur::All(blur, blur)
+                    }
+                )
+            } )
+            .map_err(|value| ParseError::new(&format!("Invalid blur '{}'!", value)) )?
+        )),
+        "blur-h" => objects.push(EventObject::TagBlur(
+            tag_value.map_else_err_str(|value| Some(Blur::Horizontal(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid blur horizontal '{}'!", value)) )?
+        )),
+        "blur-v" => objects.push(EventObject::TagBlur(
+            tag_value.map_else_err_str(|value| Some(Blur::Vertical(value.parse().ok()?)) )
+            .map_err(|value| ParseError::new(&format!("Invalid blur vertical '{}'!", value)) )?
+        )),
+        "blend" => objects.push(EventObject::TagBlend(
+            tag_value.map_or_err_str(|value| Blend::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid blend '{}'!", value)) )?
+        )),
+        "target" => objects.push(EventObject::TagTarget(
+            tag_value.map_or_err_str(|value| Target::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid target '{}'!", value)) )?
+        )),
+        "mask-mode" => objects.push(EventObject::TagMaskMode(
+            tag_value.map_or_err_str(|value| MaskMode::try_from(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid mask mode '{}'!", value)) )?
+        )),
+        "mask-clear" => objects.push(Some(EventObject::TagMaskClear)
+            .filter(|_| tag_value.is_none() )
+            .ok_or_else(|| ParseError::new("Mask clear must have no value!") )?
+        ),
+        "animate" if mode.is_some() => objects.push(EventObject::TagAnimate(
+            tag_value.map_or(Err(("", None)), |value| {
+                let captures = ANIMATE_PATTERN.captures(value).ok_or_else(|| (value, None) )?;
+                Ok(Box::new(Animate {
+                    time: match (captures.name("S"), captures.name("E")) {
+                        (Some(start_time), Some(end_time)) => Some((
+                            start_time.as_str().parse().map_err(|_| (value, None) )?,
+                            end_time.as_str().parse().map_err(|_| (value, None) )?
+                        )),
+                        _ => None
+                    },
+                    formula: captures.name("F").map(|formula| Formula::new(formula.as_str()) ).transpose().map_err(|err| (value, Some(err)) )?,
+                    tags: {
+                        let mut tags = vec![];
+                        parse_tags(captures.name("T").ok_or_else(|| (value, None) )?.as_str(), &mut tags, None, custom_tags, keep_unknown).map_err(|err| (value, Some(err)) )?;
+                        tags
+                    }
+                }))
+            })
+            .map_err(|(value,err)| {
+                let value = format!("Invalid animate '{}'!", value);
+                err.map(|err| ParseError::new_with_source(&value, err) ).unwrap_or_else(|| ParseError::new(&value) )
+            } )?
+        )),
+        "k" => objects.push(EventObject::TagKaraoke(
+            tag_value.map_or_err_str(|value| value.parse() )
+            .map_err(|value| ParseError::new(&format!("Invalid karaoke '{}'!", value)) )?
+        )),
+        "kset" => objects.push(EventObject::TagKaraokeSet(
+            tag_value.map_or_err_str(|value| value.parse() )
+            .map_err(|value| ParseError::new(&format!("Invalid karaoke set '{}'!", value)) )?
+        )),
+        "kcolor" => objects.push(EventObject::TagKaraokeColor(
+            tag_value.map_or_err_str(|value| rgb_from_str(value) )
+            .map_err(|value| ParseError::new(&format!("Invalid karaoke color '{}'!", value)) )?
+        )),
+        // Built-in tag out of context (f.e. nested into animation), neither custom nor unknown
+        "mode" | "animate" => return Ok(false),
+        // Custom or unknown tag
+        _ => match custom_tags.and_then(|custom_tags| custom_tags.parse(tag_name, tag_value) ) {
+            Some(custom_tag) => objects.push(custom_tag?),
+            None if keep_unknown => objects.push(EventObject::TagUnknown{
+                name: tag_name.into(),
+                value: tag_value.map(Into::into)
+            }),
+            None => return Ok(false)
+        }
+    }
+    Ok(true)
+}
 fn parse_geometries<'a>(data: &str, objects: &'a mut Vec<EventObject>, mode: &Mode) -> Result<&'a mut Vec<EventObject>, ParseError> {
     match mode {
         Mode::Text => objects.push(EventObject::GeometryText(data.to_owned())),
@@ -641,20 +981,32 @@ fn parse_geometries<'a>(data: &str, objects: &'a mut Vec<EventObject>, mode: &Mo
 // Tests
 #[cfg(test)]
 mod tests {
-    use super::{parse_tags, Mode, parse_geometries};
+    use super::{parse_tags, Mode, parse_geometries, EventObject};
 
     #[test]
     fn invalid_tag() {
         assert_eq!(
-            parse_tags("font=Arial;dummy", &mut vec![], None).map_err(|err| err.to_string() ),
+            parse_tags("font=Arial;dummy", &mut vec![], None, None, false).map_err(|err| err.to_string() ),
             Err("Invalid tag 'dummy'!".to_owned())
         );
         assert_eq!(
-            parse_tags("animate=500,-500,t,[abc]", &mut vec![], Some(&mut Mode::default())).map_err(|err| err.to_string() ),
+            parse_tags("animate=500,-500,t,[abc]", &mut vec![], Some(&mut Mode::default()), None, false).map_err(|err| err.to_string() ),
             Err("Invalid animate '500,-500,t,[abc]'!\nInvalid tag 'abc'!".to_owned())
         );
     }
 
+    #[test]
+    fn unknown_tag() {
+        assert_eq!(
+            parse_tags("font=Arial;dummy;glow=5,red", &mut vec![], None, None, true).map(|objects| objects.clone() ).map_err(|err| err.to_string() ),
+            Ok(vec![
+                EventObject::TagFont("Arial".to_owned()),
+                EventObject::TagUnknown{name: "dummy".into(), value: None},
+                EventObject::TagUnknown{name: "glow".into(), value: Some("5,red".into())}
+            ])
+        );
+    }
+
     #[test]
     fn invalid_geometry() {
         assert_eq!(
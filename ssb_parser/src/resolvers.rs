@@ -0,0 +1,193 @@
+// Imports
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self,Cursor,Read},
+    path::{Path,PathBuf}
+};
+
+
+/// Source of resource data referenced by url (like texture files).
+pub trait ResourceResolver {
+    /// Open data of resource by url (reading can be limited by caller).
+    fn open(&self, url: &str) -> io::Result<Box<dyn Read + '_>>;
+}
+impl<F> ResourceResolver for F
+    where F: Fn(&str) -> io::Result<Vec<u8>> {
+    fn open(&self, url: &str) -> io::Result<Box<dyn Read + '_>> {
+        Ok(Box::new(Cursor::new(self(url)?)))
+    }
+}
+
+/// Files relative to a directory (default is the current working directory).
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct DirectoryResolver {
+    pub directory: PathBuf
+}
+impl DirectoryResolver {
+    /// New resolver by directory.
+    pub fn new<P>(directory: P) -> Self
+        where P: Into<PathBuf> {
+        Self {
+            directory: directory.into()
+        }
+    }
+    /// New resolver by directory of a script file.
+    pub fn for_script<P>(script_path: P) -> Self
+        where P: AsRef<Path> {
+        Self::new(script_path.as_ref().parent().unwrap_or_else(|| Path::new("")))
+    }
+}
+impl ResourceResolver for DirectoryResolver {
+    fn open(&self, url: &str) -> io::Result<Box<dyn Read + '_>> {
+        Ok(Box::new(File::open(self.directory.join(url))?))
+    }
+}
+
+/// Files relative to the first of directories containing them.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SearchPathResolver {
+    pub directories: Vec<PathBuf>
+}
+impl SearchPathResolver {
+    /// New resolver by directories to search in order.
+    pub fn new(directories: Vec<PathBuf>) -> Self {
+        Self {
+            directories
+        }
+    }
+}
+impl ResourceResolver for SearchPathResolver {
+    fn open(&self, url: &str) -> io::Result<Box<dyn Read + '_>> {
+        self.directories.iter()
+            .find_map(|directory| File::open(directory.join(url)).ok() )
+            .map(|file| Box::new(file) as Box<dyn Read> )
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("'{}' not found in search path!", url)) )
+    }
+}
+
+/// Data in memory by url.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct MemoryResolver {
+    pub resources: HashMap<String, Vec<u8>>
+}
+impl MemoryResolver {
+    /// New resolver without resources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add resource data by url (replaces previous one).
+    pub fn insert(&mut self, url: &str, data: Vec<u8>) -> &mut Self {
+        self.resources.insert(url.to_owned(), data);
+        self
+    }
+}
+impl From<HashMap<String, Vec<u8>>> for MemoryResolver {
+    fn from(resources: HashMap<String, Vec<u8>>) -> Self {
+        Self {
+            resources
+        }
+    }
+}
+impl ResourceResolver for MemoryResolver {
+    fn open(&self, url: &str) -> io::Result<Box<dyn Read + '_>> {
+        self.resources.get(url)
+            .map(|data| Box::new(data.as_slice()) as Box<dyn Read> )
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("'{}' not in memory!", url)) )
+    }
+}
+
+/// Data embedded into url (`data:[<mediatype>][;base64],<data>`).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct DataUriResolver;
+impl ResourceResolver for DataUriResolver {
+    fn open(&self, url: &str) -> io::Result<Box<dyn Read + '_>> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("'{}' {}!", url, msg));
+        let (header, data) = url.strip_prefix("data:")
+            .and_then(|uri| uri.find(',').map(|separator| (&uri[..separator], &uri[separator + 1 /* , */..])) )
+            .ok_or_else(|| invalid("isn't a data uri") )?;
+        let data = if header.ends_with(";base64") {
+            base64::decode(data).map_err(|_| invalid("contains invalid base64") )?
+        } else {
+            percent_decode(data).ok_or_else(|| invalid("contains invalid percent-encoding") )?
+        };
+        Ok(Box::new(Cursor::new(data)))
+    }
+}
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            data.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            data.push(byte);
+        }
+    }
+    Some(data)
+}
+
+/// Resolvers tried in order until one succeeds.
+#[derive(Default)]
+pub struct ResolverChain {
+    pub resolvers: Vec<Box<dyn ResourceResolver>>
+}
+impl ResolverChain {
+    /// New chain of resolvers.
+    pub fn new(resolvers: Vec<Box<dyn ResourceResolver>>) -> Self {
+        Self {
+            resolvers
+        }
+    }
+}
+impl ResourceResolver for ResolverChain {
+    fn open(&self, url: &str) -> io::Result<Box<dyn Read + '_>> {
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, format!("'{}' not resolvable without resolvers!", url));
+        for resolver in &self.resolvers {
+            match resolver.open(url) {
+                Ok(reader) => return Ok(reader),
+                Err(err) => last_err = err
+            }
+        }
+        Err(last_err)
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{ResourceResolver, DirectoryResolver, SearchPathResolver, MemoryResolver, DataUriResolver, ResolverChain};
+    use std::io::Read;
+
+    fn read(resolver: &dyn ResourceResolver, url: &str) -> Option<Vec<u8>> {
+        let mut data = vec![];
+        resolver.open(url).ok()?.read_to_end(&mut data).ok()?;
+        Some(data)
+    }
+
+    #[test]
+    fn files() {
+        let manifest_directory = env!("CARGO_MANIFEST_DIR");
+        assert!(read(&DirectoryResolver::for_script(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.ssb")), "cute.png").is_some());
+        assert!(read(&DirectoryResolver::new(manifest_directory), "cute.png").is_none());
+        assert!(read(&SearchPathResolver::new(vec![manifest_directory.into(), (manifest_directory.to_owned() + "/tests").into()]), "cute.png").is_some());
+    }
+
+    #[test]
+    fn memory_and_data() {
+        let mut memory = MemoryResolver::new();
+        memory.insert("a", vec![1, 2, 3]);
+        assert_eq!(read(&memory, "a"), Some(vec![1, 2, 3]));
+        assert_eq!(read(&memory, "b"), None);
+        assert_eq!(read(&DataUriResolver, "data:text/plain;base64,SGk="), Some(b"Hi".to_vec()));
+        assert_eq!(read(&DataUriResolver, "data:,A%20b"), Some(b"A b".to_vec()));
+        assert_eq!(read(&DataUriResolver, "data:,%2"), None);
+        assert_eq!(read(&DataUriResolver, "cute.png"), None);
+        let chain = ResolverChain::new(vec![Box::new(DataUriResolver), Box::new(memory), Box::new(|url: &str| Ok(url.as_bytes().to_vec()) )]);
+        assert_eq!(read(&chain, "a"), Some(vec![1, 2, 3]));
+        assert_eq!(read(&chain, "data:,x"), Some(b"x".to_vec()));
+        assert_eq!(read(&chain, "z"), Some(b"z".to_vec()));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,321 @@
+// Imports
+use crate::{
+    state::error::ParseError,
+    objects::{
+        ssb_objects::{Event,EventTrigger},
+        event_objects::EventObject
+    },
+    parsers::{
+        ssb::Ssb,
+        ssb_render::SsbRender
+    },
+    utils::{
+        pattern::{ANIMATE_PATTERN,MACRO_PATTERN,MACRO_INLINE_START},
+        functions::event_iter::{EscapedText,TagsIterator}
+    }
+};
+use std::{
+    convert::TryFrom,
+    ops::Range
+};
+
+
+/// Mapping of times (in milliseconds) for retiming events, piecewise linear and never decreasing.
+///
+/// Event start times below zero get clamped to zero, relative times (animations, karaoke) keep pointing to the same mapped times.
+/// Animation times counting from event start mustn't fall before the clamped start. Events ending at or before zero and times out of range are errors, leaving the data unchanged.
+/// Frame- and timecode-triggered events are bound to video frames, so they stay untouched.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TimeMap {
+    // Anchors (source, target) by ascending source
+    points: Vec<(f64, f64)>,
+    // Slopes before first & after last anchor
+    slopes: (f64, f64)
+}
+impl TimeMap {
+    /// Move all times by offset.
+    pub fn shift(offset_ms: i64) -> Self {
+        Self {
+            points: vec![(0.0, offset_ms as f64)],
+            slopes: (1.0, 1.0)
+        }
+    }
+    /// Scale all times (and durations) by factor, which has to be positive.
+    pub fn stretch(factor: f64) -> Result<Self, ParseError> {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(ParseError::new(&format!("Stretch factor '{}' must be positive!", factor)));
+        }
+        Ok(Self {
+            points: vec![(0.0, 0.0)],
+            slopes: (factor, factor)
+        })
+    }
+    /// Convert timing of frames from one framerate to another (f.e. 23.976 to 25 fps).
+    pub fn framerate(from_fps: f64, to_fps: f64) -> Result<Self, ParseError> {
+        if !(from_fps.is_finite() && from_fps > 0.0 && to_fps.is_finite() && to_fps > 0.0) {
+            return Err(ParseError::new(&format!("Framerates '{}' and '{}' must be positive!", from_fps, to_fps)));
+        }
+        Self::stretch(from_fps / to_fps)
+    }
+    /// Map times by anchors (source time, target time), f.e. from an edit decision list.
+    ///
+    /// Times between anchors get interpolated linearly, times outside keep the offset of the nearest anchor.
+    /// Source times have to ascend, target times mustn't descend.
+    pub fn piecewise(points: &[(u32, u32)]) -> Result<Self, ParseError> {
+        if points.is_empty() {
+            return Err(ParseError::new("Time map requires at least one anchor!"));
+        }
+        if let Some(pair) = points.windows(2).find(|pair| pair[0].0 >= pair[1].0 || pair[0].1 > pair[1].1 ) {
+            return Err(ParseError::new(&format!("Time map anchor {:?} doesn't follow {:?} in order!", pair[1], pair[0])));
+        }
+        Ok(Self {
+            points: points.iter().map(|(source, target)| (f64::from(*source), f64::from(*target)) ).collect(),
+            slopes: (1.0, 1.0)
+        })
+    }
+    /// Mapped time.
+    pub fn map(&self, ms: i64) -> i64 {
+        let ms = ms as f64;
+        let first = self.points[0];
+        let last = self.points[self.points.len() - 1];
+        (if ms <= first.0 {
+            first.1 + (ms - first.0) * self.slopes.0
+        } else if ms >= last.0 {
+            last.1 + (ms - last.0) * self.slopes.1
+        } else {
+            let next_index = self.points.iter().position(|(source, _)| *source > ms ).expect("Time is inside anchors!");
+            let (previous, next) = (self.points[next_index - 1], self.points[next_index]);
+            previous.1 + (ms - previous.0) * (next.1 - previous.1) / (next.0 - previous.0)
+        }).round() as i64
+    }
+    // Relative times stay the same?
+    fn is_shift(&self) -> bool {
+        self.points.len() == 1 && self.slopes == (1.0, 1.0)
+    }
+    // Mapped event time range, start clamped to zero
+    fn map_trigger(&self, (start_ms, end_ms): (u32, u32)) -> Result<(u32, u32), String> {
+        let (start, end) = (self.map(i64::from(start_ms)).max(0), self.map(i64::from(end_ms)));
+        if end <= 0 {
+            return Err(format!("Event {}-{} ends before zero by retiming!", start_ms, end_ms));
+        }
+        u32::try_from(end)
+            .map(|end| (start as u32, end) )
+            .map_err(|_| format!("Event {}-{} ends too late by retiming!", start_ms, end_ms) )
+    }
+}
+
+
+impl Ssb {
+    /// Retime time-triggered events by map, including relative times in animate and karaoke tags of event data.
+    ///
+    /// Macros containing relative times can't be retimed (they're shared between events), so only shifts support them.
+    pub fn retime(&mut self, map: &TimeMap) -> Result<(), ParseError> {
+        let has_relative_times = |macro_name: &str| self.macros.get(macro_name)
+            .map_or(false, |value| !matches!(retime_data(value, &mut Retimer::new(map, (0, 0), (0, 0))), Ok(replacements) if replacements.is_empty()) );
+        if !map.is_shift() {
+            let mut macro_names = self.macros.keys().collect::<Vec<_>>();
+            macro_names.sort();
+            if let Some(macro_name) = macro_names.into_iter().find(|macro_name| has_relative_times(macro_name) ) {
+                return Err(ParseError::new(&format!("Macro '{}' contains relative times, not retimable!", macro_name)));
+            }
+        }
+        let mut retimed = Vec::with_capacity(self.events.len());
+        for (event_index, event) in self.events.iter().enumerate() {
+            if let EventTrigger::Time(times) = event.trigger {
+                let new_times = map.map_trigger(times).map_err(|msg| ParseError::new_with_pos(&msg, (event.data_location.0, 0)) )?;
+                let mut data = event.data.clone();
+                // Shifts keep relative times, unless the event start got clamped
+                let clamped = i64::from(new_times.0) != map.map(i64::from(times.0));
+                if !map.is_shift() || clamped {
+                    if let Some(macro_name) = self.used_macros(event).into_iter().find(|macro_name| has_relative_times(macro_name) ) {
+                        return Err(ParseError::new_with_pos(&format!("Macro '{}' contains relative times, not retimable!", macro_name), (event.data_location.0, 0)));
+                    }
+                    let replacements = retime_data(&event.data, &mut Retimer::new(map, times, new_times))
+                        .map_err(|(value, offset)| ParseError::new_with_pos(&format!("Time '{}' not retimable!", value), (event.data_location.0, event.data_location.1 + offset)) )?;
+                    for (range, value) in replacements.into_iter().rev() {
+                        data.replace_range(range, &value);
+                    }
+                }
+                retimed.push((event_index, new_times, data));
+            }
+        }
+        for (event_index, new_times, data) in retimed {
+            let event = &mut self.events[event_index];
+            event.trigger = EventTrigger::Time(new_times);
+            event.data = data;
+        }
+        Ok(())
+    }
+    // Names of macros used by event, directly or by other macros
+    fn used_macros<'a>(&'a self, event: &'a Event) -> Vec<&'a str> {
+        let mut macro_names = event.macro_name.as_deref().into_iter()
+            .chain(MACRO_PATTERN.captures_iter(&event.data).filter_map(|captures| captures.get(1) ).map(|name| name.as_str() ))
+            .collect::<Vec<_>>();
+        let mut index = 0;
+        while let Some(macro_name) = macro_names.get(index).copied() {
+            for name in self.macros.get(macro_name).into_iter().flat_map(|value| MACRO_PATTERN.captures_iter(value).filter_map(|captures| captures.get(1) ).map(|name| name.as_str() ) ) {
+                if !macro_names.contains(&name) {
+                    macro_names.push(name);
+                }
+            }
+            index += 1;
+        }
+        macro_names
+    }
+}
+// Replacements of relative times in event data (source ranges ascending) or invalid value with source offset
+type Replacement = (Range<usize>, String);
+fn retime_data(data: &str, retimer: &mut Retimer) -> Result<Vec<Replacement>, (String, usize)> {
+    let escaped_text = EscapedText::new(data);
+    let mut replacements = vec![];
+    for (is_tag, data_start, tags) in escaped_text.iter() {
+        if !is_tag {
+            continue;
+        }
+        for (tag_start, tag_name, tag_value) in TagsIterator::new(tags).flatten() {
+            let value_start = data_start + tag_start + tag_name.len() + 1 /* TAG_ASSIGN */;
+            let mut replace = |range: Range<usize>, value: String| replacements.push((
+                escaped_text.source_offset(value_start + range.start)..escaped_text.source_offset(value_start + range.end),
+                value
+            ));
+            let invalid = |value: &str| (value.to_owned(), escaped_text.source_offset(value_start));
+            match (tag_name, tag_value) {
+                ("k", Some(value)) => replace(
+                    0..value.len(),
+                    value.parse().ok().and_then(|duration| retimer.karaoke(duration) ).ok_or_else(|| invalid(value) )?.to_string()
+                ),
+                ("kset", Some(value)) => replace(
+                    0..value.len(),
+                    value.parse().ok().and_then(|offset| retimer.karaoke_set(offset) ).ok_or_else(|| invalid(value) )?.to_string()
+                ),
+                ("animate", Some(value)) => if let Some(captures) = ANIMATE_PATTERN.captures(value) {
+                    if let (Some(start_time), Some(end_time)) = (captures.name("S"), captures.name("E")) {
+                        let (new_start, new_end) = start_time.as_str().parse().ok()
+                            .and_then(|start| Some((start, end_time.as_str().parse().ok()?)) )
+                            .and_then(|times| retimer.animate(times) )
+                            .ok_or_else(|| invalid(value) )?;
+                        replace(start_time.range(), new_start.to_string());
+                        replace(end_time.range(), new_end.to_string());
+                    }
+                } else if value.contains(MACRO_INLINE_START) {
+                    return Err(invalid(value));
+                }
+                _ => ()
+            }
+        }
+    }
+    Ok(replacements)
+}
+
+
+impl SsbRender {
+    /// Retime time-triggered events by map, including relative times of animate and karaoke tags.
+    pub fn retime(&mut self, map: &TimeMap) -> Result<(), ParseError> {
+        let mut events = self.events.clone();
+        for (event_index, event) in events.iter_mut().enumerate() {
+            if let EventTrigger::Time(times) = event.trigger {
+                let new_times = map.map_trigger(times).map_err(|msg| ParseError::new(&format!("Event #{}: {}", event_index, msg)) )?;
+                let mut retimer = Retimer::new(map, times, new_times);
+                for object in &mut event.objects {
+                    let retimed = match object {
+                        EventObject::TagKaraoke(duration) => retimer.karaoke(*duration).map(|new_duration| *duration = new_duration ),
+                        EventObject::TagKaraokeSet(offset) => retimer.karaoke_set(*offset).map(|new_offset| *offset = new_offset ),
+                        EventObject::TagAnimate(animate) => match animate.time {
+                            Some(times) => retimer.animate(times).map(|new_times| animate.time = Some(new_times) ),
+                            None => Some(())
+                        }
+                        _ => Some(())
+                    };
+                    if retimed.is_none() {
+                        return Err(ParseError::new(&format!("Event #{}: {:?} not retimable!", event_index, object)));
+                    }
+                }
+                event.trigger = EventTrigger::Time(new_times);
+            }
+        }
+        self.events = events;
+        Ok(())
+    }
+}
+
+
+// Mapping of times relative to one event
+struct Retimer<'a> {
+    map: &'a TimeMap,
+    start_ms: i64,
+    end_ms: i64,
+    new_start_ms: i64,
+    new_end_ms: i64,
+    karaoke_ms: i64
+}
+impl<'a> Retimer<'a> {
+    fn new(map: &'a TimeMap, (start_ms, end_ms): (u32, u32), (new_start_ms, new_end_ms): (u32, u32)) -> Self {
+        Self {
+            map,
+            start_ms: i64::from(start_ms),
+            end_ms: i64::from(end_ms),
+            new_start_ms: i64::from(new_start_ms),
+            new_end_ms: i64::from(new_end_ms),
+            karaoke_ms: 0
+        }
+    }
+    fn relative(&self, ms: i64) -> i64 {
+        self.map.map(self.start_ms + ms) - self.new_start_ms
+    }
+    fn karaoke(&mut self, duration: u32) -> Option<u32> {
+        let (start, end) = (self.karaoke_ms, self.karaoke_ms + i64::from(duration));
+        self.karaoke_ms = end;
+        u32::try_from(self.relative(end) - self.relative(start)).ok()
+    }
+    fn karaoke_set(&mut self, offset: i32) -> Option<i32> {
+        self.karaoke_ms = i64::from(offset);
+        i32::try_from(self.relative(self.karaoke_ms)).ok()
+    }
+    fn animate(&self, (start, end): (i32, i32)) -> Option<(i32, i32)> {
+        Some((self.animate_time(start)?, self.animate_time(end)?))
+    }
+    fn animate_time(&self, time: i32) -> Option<i32> {
+        let time = i64::from(time);
+        let new_time = if time < 0 {
+            // Counting from event end, unless mapped onto it
+            let mapped = self.map.map(self.end_ms + time);
+            if mapped < self.new_end_ms {mapped - self.new_end_ms} else {mapped - self.new_start_ms}
+        } else {
+            // Counting from event start, which can't be undercut
+            Some(self.relative(time)).filter(|new_time| *new_time >= 0 )?
+        };
+        i32::try_from(new_time).ok()
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::TimeMap;
+
+    #[test]
+    fn time_map() {
+        assert_eq!(TimeMap::shift(-500).map(1200), 700);
+        assert_eq!(TimeMap::stretch(2.0).unwrap().map(1200), 2400);
+        assert!(TimeMap::stretch(0.0).is_err());
+        assert_eq!(TimeMap::framerate(25.0, 23.976).unwrap().map(1000), 1043);
+        assert!(TimeMap::framerate(25.0, -1.0).is_err());
+        let edl = TimeMap::piecewise(&[(1000, 1000), (2000, 1500), (3000, 3500)]).unwrap();
+        assert_eq!(edl.map(0), 0);
+        assert_eq!(edl.map(1500), 1250);
+        assert_eq!(edl.map(2500), 2500);
+        assert_eq!(edl.map(4000), 4500);
+        assert!(!edl.is_shift() && TimeMap::piecewise(&[(0, 100)]).unwrap().is_shift());
+        assert!(TimeMap::piecewise(&[]).is_err());
+        assert!(TimeMap::piecewise(&[(1000, 1000), (1000, 2000)]).is_err());
+        assert!(TimeMap::piecewise(&[(1000, 1000), (2000, 500)]).is_err());
+    }
+
+    #[test]
+    fn triggers() {
+        assert_eq!(TimeMap::shift(-500).map_trigger((200, 1000)), Ok((0, 500)));
+        assert!(TimeMap::shift(-500).map_trigger((200, 500)).is_err());
+        assert!(TimeMap::shift(i64::from(u32::MAX)).map_trigger((0, 1)).is_err());
+    }
+}
\ No newline at end of file
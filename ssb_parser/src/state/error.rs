@@ -14,6 +14,7 @@ use std::{
 pub struct ParseError {
     msg: String,
     pos: Option<(usize, usize)>,
+    macro_origin: Option<(String, usize)>,
     src: Option<Box<dyn Error>>
 }
 impl ParseError {
@@ -22,6 +23,7 @@ impl ParseError {
         Self {
             msg: msg.to_owned(),
             pos: None,
+            macro_origin: None,
             src: None
         }
     }
@@ -30,6 +32,7 @@ impl ParseError {
         Self {
             msg: msg.to_owned(),
             pos: Some(pos),
+            macro_origin: None,
             src: None
         }
     }
@@ -39,6 +42,7 @@ impl ParseError {
         Self {
             msg: msg.to_owned(),
             pos: None,
+            macro_origin: None,
             src: Some(Box::new(src))
         }
     }
@@ -48,9 +52,27 @@ impl ParseError {
         Self {
             msg: msg.to_owned(),
             pos: Some(pos),
+            macro_origin: None,
             src: Some(Box::new(src))
         }
     }
+    /// Set macro the error originates from, with offset in its (flattened) value.
+    pub(crate) fn with_macro_origin(mut self, macro_name: &str, offset: usize) -> Self {
+        self.macro_origin = Some((macro_name.to_owned(), offset));
+        self
+    }
+    /// Message (without position and source).
+    pub fn msg(&self) -> &str {
+        &self.msg
+    }
+    /// Position in input as line & column (starting at 0), for errors from macros the position of the macro insertion.
+    pub fn pos(&self) -> Option<(usize, usize)> {
+        self.pos
+    }
+    /// Macro name & offset in its (flattened) value, if the error originates from a macro insertion.
+    pub fn macro_origin(&self) -> Option<(&str, usize)> {
+        self.macro_origin.as_ref().map(|(macro_name, offset)| (macro_name.as_str(), *offset) )
+    }
 }
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -70,11 +92,141 @@ impl From<std::io::Error> for ParseError {
     }
 }
 
+/// Warning about a construct which couldn't be converted (completely) between formats.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConversionWarning {
+    pub msg: String,
+    pub construct: String,
+    pub pos: Option<(usize, usize)>
+}
+impl ConversionWarning {
+    /// New warning with message, affected construct and optional position.
+    pub(crate) fn new(msg: &str, construct: &str, pos: Option<(usize, usize)>) -> Self {
+        Self {
+            msg: msg.to_owned(),
+            construct: construct.to_owned(),
+            pos
+        }
+    }
+}
+impl Display for ConversionWarning {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{} '{}'", self.msg, self.construct)
+        .and_then(|_| self.pos.map_or(Ok(()), |pos| write!(f, " <{}:{}>", pos.0, pos.1)))
+    }
+}
+
+
+/// Severity of a diagnostic.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    Error,
+    Warning
+}
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning"
+        })
+    }
+}
+/// Kind of a diagnostic, stable for tools to filter by.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DiagnosticCode {
+    InvalidInput,
+    InvalidEntry,
+    DuplicateMacro,
+    DuplicateResource,
+    UnknownMacro,
+    RecursiveMacro,
+    MacroArityMismatch,
+    UnusedMacro,
+    UnknownTag,
+    InvalidTag,
+    InvalidGeometry,
+    DuplicateId,
+    InvalidResource,
+    LimitExceeded
+}
+impl Display for DiagnosticCode {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(match self {
+            Self::InvalidInput => "invalid-input",
+            Self::InvalidEntry => "invalid-entry",
+            Self::DuplicateMacro => "duplicate-macro",
+            Self::DuplicateResource => "duplicate-resource",
+            Self::UnknownMacro => "unknown-macro",
+            Self::RecursiveMacro => "recursive-macro",
+            Self::MacroArityMismatch => "macro-arity-mismatch",
+            Self::UnusedMacro => "unused-macro",
+            Self::UnknownTag => "unknown-tag",
+            Self::InvalidTag => "invalid-tag",
+            Self::InvalidGeometry => "invalid-geometry",
+            Self::DuplicateId => "duplicate-id",
+            Self::InvalidResource => "invalid-resource",
+            Self::LimitExceeded => "limit-exceeded"
+        })
+    }
+}
+/// Source range by start and end position (line & column, end exclusive).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Span {
+    pub start: (usize, usize),
+    pub end: (usize, usize)
+}
+impl Span {
+    /// New span of one line from start to end column.
+    pub(crate) fn new(line: usize, start: usize, end: usize) -> Self {
+        Self {
+            start: (line, start),
+            end: (line, end)
+        }
+    }
+}
+/// Problem found in SSB data, collected instead of failing.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub msg: String,
+    pub span: Option<Span>
+}
+impl Diagnostic {
+    /// New diagnostic with message and optional span.
+    pub(crate) fn new(severity: Severity, code: DiagnosticCode, msg: &str, span: Option<Span>) -> Self {
+        Self {
+            severity,
+            code,
+            msg: msg.to_owned(),
+            span
+        }
+    }
+    /// New error diagnostic from parse error, spanning from its position to given end (or nothing).
+    pub(crate) fn from_error(code: DiagnosticCode, err: &ParseError, end: Option<(usize, usize)>) -> Self {
+        Self {
+            severity: Severity::Error,
+            code,
+            msg: err.msg.clone() + &err.source().map_or(String::new(), |src| format!("\n{}", src)),
+            span: err.pos.map(|start| Span {
+                start,
+                end: end.filter(|end| *end >= start).unwrap_or(start)
+            })
+        }
+    }
+}
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.msg)
+        .and_then(|_| self.span.map_or(Ok(()), |span| write!(f, " <{}:{}-{}:{}>", span.start.0, span.start.1, span.end.0, span.end.1)))
+    }
+}
+
 
 // Tests
 #[cfg(test)]
 mod tests {
-    use super::ParseError;
+    use super::{ParseError, ConversionWarning, Diagnostic, DiagnosticCode, Severity, Span};
 
     #[test]
     fn parse_error() {
@@ -96,9 +248,32 @@ mod tests {
         assert_eq!(ParseError::new_with_pos_source("test", (42, 26), ParseError::new("sourcy")).to_string(), "test <42:26>\nsourcy");
     }
 
+    #[test]
+    fn parse_error_accessors() {
+        let err = ParseError::new_with_pos_source("test", (42, 26), ParseError::new("sourcy")).with_macro_origin("m", 3);
+        assert_eq!((err.msg(), err.pos(), err.macro_origin()), ("test", Some((42, 26)), Some(("m", 3))));
+        assert_eq!(ParseError::new("simple").macro_origin(), None);
+    }
+
     #[test]
     fn parse_error_from_io() {
         use std::io::{Error, ErrorKind};
         assert_eq!(ParseError::from(Error::new(ErrorKind::NotFound, "Freddy not found!")).to_string(), "IO error!\nFreddy not found!".to_owned());
     }
+
+    #[test]
+    fn conversion_warning() {
+        assert_eq!(ConversionWarning::new("Unsupported tag", "\\shad2", Some((3, 14))).to_string(), "Unsupported tag '\\shad2' <3:14>");
+        assert_eq!(ConversionWarning::new("Skipped section", "[Aegisub Project Garbage]", None).to_string(), "Skipped section '[Aegisub Project Garbage]'");
+    }
+
+    #[test]
+    fn diagnostic() {
+        assert_eq!(Diagnostic::new(Severity::Warning, DiagnosticCode::UnusedMacro, "Macro 'foo' unused!", None).to_string(), "warning[unused-macro]: Macro 'foo' unused!");
+        assert_eq!(
+            Diagnostic::from_error(DiagnosticCode::InvalidEntry, &ParseError::new_with_pos_source("Invalid entry!", (2, 4), ParseError::new("reason")), Some((2, 9))).to_string(),
+            "error[invalid-entry]: Invalid entry!\nreason <2:4-2:9>"
+        );
+        assert_eq!(Diagnostic::from_error(DiagnosticCode::InvalidTag, &ParseError::new_with_pos("Bad!", (1, 1)), None).span, Some(Span::new(1, 1, 1)));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+// Imports
+use std::{
+    error::Error,
+    fmt::{
+        Display,
+        Formatter,
+        Result
+    }
+};
+
+
+/// Limits for processing SSB data, guarding against hostile inputs exhausting memory or stack.
+///
+/// Defaults are generous enough for real scripts, untrusted inputs should get stricter values.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseLimits {
+    /// Maximum length of event data (and macro values) after macro insertion.
+    pub max_event_length: usize,
+    /// Maximum depth of macros inserted into macros.
+    pub max_macro_depth: usize,
+    /// Maximum number of events.
+    pub max_events: usize,
+    /// Maximum bytes of all fonts & textures.
+    pub max_resource_bytes: usize,
+    /// Maximum depth of tags nested into tags.
+    pub max_tag_nesting: usize,
+    /// Maximum depth of files included into files (zero disables includes).
+    pub max_include_depth: usize
+}
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_event_length: 1 << 20,
+            max_macro_depth: 64,
+            max_events: 1 << 22,
+            max_resource_bytes: 1 << 30,
+            max_tag_nesting: 32,
+            max_include_depth: 16
+        }
+    }
+}
+
+/// Kind of a parse limit.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Limit {
+    EventLength,
+    MacroDepth,
+    Events,
+    ResourceBytes,
+    TagNesting,
+    IncludeDepth
+}
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(match self {
+            Self::EventLength => "event length",
+            Self::MacroDepth => "macro depth",
+            Self::Events => "events",
+            Self::ResourceBytes => "resource bytes",
+            Self::TagNesting => "tag nesting",
+            Self::IncludeDepth => "include depth"
+        })
+    }
+}
+/// Exceeded parse limit, source of the reporting [`ParseError`](crate::ParseError).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LimitError {
+    pub limit: Limit,
+    pub max: usize
+}
+impl LimitError {
+    /// New error of limit with its maximum.
+    pub(crate) fn new(limit: Limit, max: usize) -> Self {
+        Self {
+            limit,
+            max
+        }
+    }
+}
+impl Display for LimitError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "Limit of {} exceeded (maximum: {})!", self.limit, self.max)
+    }
+}
+impl Error for LimitError {}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{LimitError, Limit};
+
+    #[test]
+    fn limit_error() {
+        assert_eq!(LimitError::new(Limit::TagNesting, 32).to_string(), "Limit of tag nesting exceeded (maximum: 32)!");
+    }
+}
\ No newline at end of file
@@ -1,4 +1,6 @@
 // Error types.
 pub mod error;
+// Limits against hostile inputs.
+pub mod limits;
 // State of SSB processing.
 pub mod ssb_state;
\ No newline at end of file
@@ -1,5 +1,8 @@
 // Imports
-use std::convert::TryFrom;
+use std::{
+    convert::TryFrom,
+    fmt
+};
 
 
 // Enums
@@ -24,6 +27,17 @@ impl TryFrom<&str> for Section {
         }
     }
 }
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "#INFO",
+            Self::Target => "#TARGET",
+            Self::Macros => "#MACROS",
+            Self::Events => "#EVENTS",
+            Self::Resources => "#RESOURCES"
+        })
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 pub enum Mode {
     Text,
@@ -61,6 +75,24 @@ impl Default for ShapeSegmentType {
 }
 
 
+// Structures
+#[derive(Debug, Default)]
+pub(crate) struct ParseState {
+    // Section of following lines
+    pub section: Option<Section>,
+    // Sum of embedded resource data (for limit checks without recounting)
+    pub resource_bytes: usize
+}
+impl ParseState {
+    pub fn new(resource_bytes: usize) -> Self {
+        Self {
+            section: None,
+            resource_bytes
+        }
+    }
+}
+
+
 // Tests
 #[cfg(test)]
 mod tests {
@@ -69,6 +101,7 @@ mod tests {
         use super::{Section, Mode, TryFrom};
         assert_eq!(Section::try_from("#EVENTS"), Ok(Section::Events));
         assert_eq!(Section::try_from("#EVENT"), Err(()));
+        assert_eq!(Section::try_from(Section::Macros.to_string().as_ref()), Ok(Section::Macros));
         assert_eq!(Mode::try_from("shape"), Ok(Mode::Shape));
         assert_eq!(Mode::try_from("lines"), Err(()));
     }
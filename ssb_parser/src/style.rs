@@ -0,0 +1,581 @@
+// Imports
+use crate::{
+    objects::{
+        ssb_objects::EventRender,
+        event_objects::{EventObject,Animate,Point2D,Point3D,ShapeSegment,Alignment,Numpad,Margin,WrapStyle,Direction,Space,Rotate,Scale,Translate,Shear,Border,Join,Cap,TextureWrapping,Color,Alpha,Blur,Blend,Target,MaskMode,Coordinate,Degree,Rgb}
+    },
+    tags::CustomTag
+};
+use std::{
+    collections::VecDeque,
+    slice::Iter
+};
+
+
+/// Complete style of geometries after applying all preceding tags of an event.
+///
+/// Defaults are the initial values of the SSB specification, `TagReset` returns to them (karaoke timing continues).
+/// Animations only get applied when resolving at a time.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StyleState {
+    pub font: String,
+    pub size: f32,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikeout: bool,
+    pub position: Option<Point3D>,
+    pub alignment: Alignment,
+    /// Top, right, bottom, left.
+    pub margin: [Coordinate;4],
+    pub wrap_style: WrapStyle,
+    pub direction: Direction,
+    /// Horizontal, vertical.
+    pub space: [Coordinate;2],
+    /// Transformations in order of appearance (to apply on geometry in reverse).
+    pub transforms: Vec<Transform>,
+    /// Horizontal, vertical.
+    pub border: [Coordinate;2],
+    pub join: Join,
+    pub cap: Cap,
+    pub texture: Option<String>,
+    pub tex_fill: TexFill,
+    pub color: Color,
+    pub border_color: Color,
+    pub alpha: Alpha,
+    pub border_alpha: Alpha,
+    /// Horizontal, vertical.
+    pub blur: [Coordinate;2],
+    /// None for normal (over) blending.
+    pub blend: Option<Blend>,
+    pub target: Target,
+    pub mask_mode: MaskMode,
+    /// Mask got cleared since previous geometry.
+    pub mask_clear: bool,
+    /// Start time & duration of current syllable, relative to event start.
+    pub karaoke: Option<(i32, u32)>,
+    pub karaoke_color: Rgb
+}
+impl Default for StyleState {
+    fn default() -> Self {
+        Self {
+            font: "Arial".to_owned(),
+            size: 30.0,
+            bold: false,
+            italic: false,
+            underline: false,
+            strikeout: false,
+            position: None,
+            alignment: Alignment::Numpad(Numpad::BottomCenter),
+            margin: [0.0;4],
+            wrap_style: WrapStyle::Space,
+            direction: Direction::LeftToRight,
+            space: [0.0;2],
+            transforms: vec![],
+            border: [0.0;2],
+            join: Join::Round,
+            cap: Cap::Round,
+            texture: None,
+            tex_fill: TexFill::default(),
+            color: Color::Mono([255;3]),
+            border_color: Color::Mono([0;3]),
+            alpha: Alpha::Mono(255),
+            border_alpha: Alpha::Mono(255),
+            blur: [0.0;2],
+            blend: None,
+            target: Target::Frame,
+            mask_mode: MaskMode::Normal,
+            mask_clear: false,
+            karaoke: None,
+            karaoke_color: [255, 0, 0]
+        }
+    }
+}
+impl StyleState {
+    /// Update state by tag, geometries, animations, unknown & custom tags get ignored.
+    pub fn apply(&mut self, object: &EventObject) {
+        match object {
+            EventObject::GeometryShape(_) | EventObject::GeometryPoints(_) | EventObject::GeometryText(_) | EventObject::TagAnimate(_) | EventObject::TagUnknown{..} | EventObject::TagCustom(_) => (),
+            EventObject::TagFont(font) => self.font = font.clone(),
+            EventObject::TagSize(size) => self.size = *size,
+            EventObject::TagBold(bold) => self.bold = *bold,
+            EventObject::TagItalic(italic) => self.italic = *italic,
+            EventObject::TagUnderline(underline) => self.underline = *underline,
+            EventObject::TagStrikeout(strikeout) => self.strikeout = *strikeout,
+            EventObject::TagPosition(position) => self.position = Some(position.clone()),
+            EventObject::TagAlignment(alignment) => self.alignment = alignment.clone(),
+            EventObject::TagMargin(margin) => match *margin {
+                Margin::All(top, right, bottom, left) => self.margin = [top, right, bottom, left],
+                Margin::Top(top) => self.margin[0] = top,
+                Margin::Right(right) => self.margin[1] = right,
+                Margin::Bottom(bottom) => self.margin[2] = bottom,
+                Margin::Left(left) => self.margin[3] = left
+            }
+            EventObject::TagWrapStyle(wrap_style) => self.wrap_style = wrap_style.clone(),
+            EventObject::TagDirection(direction) => self.direction = direction.clone(),
+            EventObject::TagSpace(space) => match *space {
+                Space::All(horizontal, vertical) => self.space = [horizontal, vertical],
+                Space::Horizontal(horizontal) => self.space[0] = horizontal,
+                Space::Vertical(vertical) => self.space[1] = vertical
+            }
+            EventObject::TagRotate(rotate) => self.transforms.push(Transform::Rotate(rotate.clone())),
+            EventObject::TagScale(scale) => self.transforms.push(Transform::Scale(scale.clone())),
+            EventObject::TagTranslate(translate) => self.transforms.push(Transform::Translate(translate.clone())),
+            EventObject::TagShear(shear) => self.transforms.push(Transform::Shear(shear.clone())),
+            EventObject::TagMatrix(matrix) => self.transforms.push(Transform::Matrix(matrix.clone())),
+            EventObject::TagReset => *self = Self {
+                mask_clear: self.mask_clear,
+                karaoke: self.karaoke,
+                ..Self::default()
+            },
+            EventObject::TagBorder(border) => match *border {
+                Border::All(horizontal, vertical) => self.border = [horizontal, vertical],
+                Border::Horizontal(horizontal) => self.border[0] = horizontal,
+                Border::Vertical(vertical) => self.border[1] = vertical
+            }
+            EventObject::TagJoin(join) => self.join = join.clone(),
+            EventObject::TagCap(cap) => self.cap = cap.clone(),
+            EventObject::TagTexture(texture) => self.texture = Some(texture.clone()).filter(|texture| !texture.is_empty()),
+            EventObject::TagTexFill{x0, y0, x1, y1, wrap} => self.tex_fill = TexFill {
+                x0: *x0, y0: *y0,
+                x1: *x1, y1: *y1,
+                wrap: wrap.clone()
+            },
+            EventObject::TagColor(color) => self.color = color.clone(),
+            EventObject::TagBorderColor(color) => self.border_color = color.clone(),
+            EventObject::TagAlpha(alpha) => self.alpha = alpha.clone(),
+            EventObject::TagBorderAlpha(alpha) => self.border_alpha = alpha.clone(),
+            EventObject::TagBlur(blur) => match *blur {
+                Blur::All(horizontal, vertical) => self.blur = [horizontal, vertical],
+                Blur::Horizontal(horizontal) => self.blur[0] = horizontal,
+                Blur::Vertical(vertical) => self.blur[1] = vertical
+            }
+            EventObject::TagBlend(blend) => self.blend = Some(blend.clone()),
+            EventObject::TagTarget(target) => self.target = target.clone(),
+            EventObject::TagMaskMode(mask_mode) => self.mask_mode = mask_mode.clone(),
+            EventObject::TagMaskClear => self.mask_clear = true,
+            EventObject::TagKaraoke(duration) => {
+                let start = self.karaoke.map_or(0, |(start, duration)| start.saturating_add(duration as i32));
+                self.karaoke = Some((start, *duration));
+            }
+            // Next syllable starts at set time
+            EventObject::TagKaraokeSet(time) => self.karaoke = Some((*time, 0)),
+            EventObject::TagKaraokeColor(rgb) => self.karaoke_color = *rgb
+        }
+    }
+    /// Update state by tag of animation with given progress (0 to 1, formula applied) and linear progress.
+    ///
+    /// Numeric values get interpolated from current state (transformations from identity),
+    /// others switch once the animation completed.
+    pub fn apply_animated(&mut self, object: &EventObject, progress: f64, linear_progress: f64) {
+        let done = linear_progress >= 1.0;
+        let mut target = self.clone();
+        target.apply(object);
+        match object {
+            EventObject::TagSize(_) => self.size = lerp(self.size, target.size, progress),
+            EventObject::TagPosition(_) => match (&mut self.position, target.position) {
+                (Some(from), Some(to)) => *from = lerp_point(from, &to, progress),
+                (from, to) => if done {
+                    *from = to;
+                }
+            }
+            EventObject::TagAlignment(_) => match (&mut self.alignment, target.alignment) {
+                (Alignment::Offset(from), Alignment::Offset(to)) => *from = Point2D {
+                    x: lerp(from.x, to.x, progress),
+                    y: lerp(from.y, to.y, progress)
+                },
+                (from, to) => if done {
+                    *from = to;
+                }
+            }
+            EventObject::TagMargin(_) => lerp_slice(&mut self.margin, &target.margin, progress),
+            EventObject::TagSpace(_) => lerp_slice(&mut self.space, &target.space, progress),
+            EventObject::TagBorder(_) => lerp_slice(&mut self.border, &target.border, progress),
+            EventObject::TagBlur(_) => lerp_slice(&mut self.blur, &target.blur, progress),
+            EventObject::TagRotate(rotate) => self.transforms.push(Transform::Rotate(match *rotate {
+                Rotate::X(angle) => Rotate::X(lerp(0.0, angle, progress)),
+                Rotate::Y(angle) => Rotate::Y(lerp(0.0, angle, progress)),
+                Rotate::Z(angle) => Rotate::Z(lerp(0.0, angle, progress))
+            })),
+            EventObject::TagScale(scale) => self.transforms.push(Transform::Scale(match *scale {
+                Scale::All(x, y, z) => Scale::All(lerp(1.0, x, progress), lerp(1.0, y, progress), lerp(1.0, z, progress)),
+                Scale::X(x) => Scale::X(lerp(1.0, x, progress)),
+                Scale::Y(y) => Scale::Y(lerp(1.0, y, progress)),
+                Scale::Z(z) => Scale::Z(lerp(1.0, z, progress))
+            })),
+            EventObject::TagTranslate(translate) => self.transforms.push(Transform::Translate(match *translate {
+                Translate::All(x, y, z) => Translate::All(lerp(0.0, x, progress), lerp(0.0, y, progress), lerp(0.0, z, progress)),
+                Translate::X(x) => Translate::X(lerp(0.0, x, progress)),
+                Translate::Y(y) => Translate::Y(lerp(0.0, y, progress)),
+                Translate::Z(z) => Translate::Z(lerp(0.0, z, progress))
+            })),
+            EventObject::TagShear(shear) => self.transforms.push(Transform::Shear(match *shear {
+                Shear::All(x, y) => Shear::All(lerp(0.0, x, progress), lerp(0.0, y, progress)),
+                Shear::X(x) => Shear::X(lerp(0.0, x, progress)),
+                Shear::Y(y) => Shear::Y(lerp(0.0, y, progress))
+            })),
+            EventObject::TagMatrix(matrix) => {
+                let mut interpolated = matrix.clone();
+                for (index, value) in interpolated.iter_mut().enumerate() {
+                    // Identity has ones on diagonal
+                    *value = lerp(if index % 5 == 0 {1.0} else {0.0}, *value, progress);
+                }
+                self.transforms.push(Transform::Matrix(interpolated));
+            }
+            EventObject::TagTexFill{..} => self.tex_fill = TexFill {
+                x0: lerp(self.tex_fill.x0, target.tex_fill.x0, progress),
+                y0: lerp(self.tex_fill.y0, target.tex_fill.y0, progress),
+                x1: lerp(self.tex_fill.x1, target.tex_fill.x1, progress),
+                y1: lerp(self.tex_fill.y1, target.tex_fill.y1, progress),
+                wrap: if done {target.tex_fill.wrap} else {self.tex_fill.wrap.clone()}
+            },
+            EventObject::TagColor(_) => self.color = lerp_color(&self.color, target.color, progress, done),
+            EventObject::TagBorderColor(_) => self.border_color = lerp_color(&self.border_color, target.border_color, progress, done),
+            EventObject::TagAlpha(_) => self.alpha = lerp_alpha(&self.alpha, target.alpha, progress, done),
+            EventObject::TagBorderAlpha(_) => self.border_alpha = lerp_alpha(&self.border_alpha, target.border_alpha, progress, done),
+            EventObject::TagKaraokeColor(_) => self.karaoke_color = lerp_rgb(self.karaoke_color, target.karaoke_color, progress),
+            _ => if done {
+                *self = target;
+            }
+        }
+    }
+}
+/// Progress of animation (formula applied) and linear progress, both from 0 to 1.
+///
+/// Animation times are relative to event start, negative ones relative to event end.
+/// Animations without times last the whole event.
+pub fn animation_progress(animate: &Animate, event_time: (u32, u32), current_ms: u32) -> (f64, f64) {
+    let duration = i64::from(event_time.1) - i64::from(event_time.0);
+    let resolve = |time: i32| if time < 0 {duration + i64::from(time)} else {i64::from(time)};
+    let (start, end) = animate.time.map_or((0, duration), |(start, end)| (resolve(start), resolve(end)) );
+    let elapsed = i64::from(current_ms) - i64::from(event_time.0);
+    let linear_progress = if end > start {
+        ((elapsed - start) as f64 / (end - start) as f64).max(0.0).min(1.0)
+    } else if elapsed >= end {
+        1.0
+    } else {
+        0.0
+    };
+    (animate.formula.as_ref().map_or(linear_progress, |formula| formula.evaluate(linear_progress) ), linear_progress)
+}
+// Interpolations
+fn lerp(from: f32, to: f32, progress: f64) -> f32 {
+    (f64::from(from) + (f64::from(to) - f64::from(from)) * progress) as f32
+}
+fn lerp_u8(from: u8, to: u8, progress: f64) -> u8 {
+    lerp(f32::from(from), f32::from(to), progress).round().max(0.0).min(255.0) as u8
+}
+fn lerp_rgb(from: Rgb, to: Rgb, progress: f64) -> Rgb {
+    [lerp_u8(from[0], to[0], progress), lerp_u8(from[1], to[1], progress), lerp_u8(from[2], to[2], progress)]
+}
+fn lerp_slice(values: &mut [Coordinate], to: &[Coordinate], progress: f64) {
+    for (value, to) in values.iter_mut().zip(to.iter()) {
+        *value = lerp(*value, *to, progress);
+    }
+}
+fn lerp_point(from: &Point3D, to: &Point3D, progress: f64) -> Point3D {
+    Point3D {
+        x: lerp(from.x, to.x, progress),
+        y: lerp(from.y, to.y, progress),
+        z: lerp(from.z, to.z, progress)
+    }
+}
+// Gradients interpolate stop-wise, mono ones expand to all stops of target
+fn lerp_stops<T: Copy>(from: &[T], to: &mut [T], interpolate: impl Fn(T, T) -> T) -> bool {
+    if from.len() != 1 && from.len() != to.len() {
+        return false;
+    }
+    for (index, value) in to.iter_mut().enumerate() {
+        *value = interpolate(from[index.min(from.len() - 1)], *value);
+    }
+    true
+}
+fn lerp_color(from: &Color, mut to: Color, progress: f64, done: bool) -> Color {
+    let from_stops = match from {
+        Color::Mono(rgb) => std::slice::from_ref(rgb),
+        Color::Linear(rgbs) => &rgbs[..],
+        Color::LinearWithStop(rgbs) => &rgbs[..],
+        Color::Corners(rgbs) => &rgbs[..],
+        Color::CornersWithStop(rgbs) => &rgbs[..]
+    };
+    let to_stops = match &mut to {
+        Color::Mono(rgb) => std::slice::from_mut(rgb),
+        Color::Linear(rgbs) => &mut rgbs[..],
+        Color::LinearWithStop(rgbs) => &mut rgbs[..],
+        Color::Corners(rgbs) => &mut rgbs[..],
+        Color::CornersWithStop(rgbs) => &mut rgbs[..]
+    };
+    if lerp_stops(from_stops, to_stops, |from, to| lerp_rgb(from, to, progress) ) || done {to} else {from.clone()}
+}
+fn lerp_alpha(from: &Alpha, mut to: Alpha, progress: f64, done: bool) -> Alpha {
+    let from_stops = match from {
+        Alpha::Mono(alpha) => std::slice::from_ref(alpha),
+        Alpha::Linear(alphas) => &alphas[..],
+        Alpha::LinearWithStop(alphas) => &alphas[..],
+        Alpha::Corners(alphas) => &alphas[..],
+        Alpha::CornersWithStop(alphas) => &alphas[..]
+    };
+    let to_stops = match &mut to {
+        Alpha::Mono(alpha) => std::slice::from_mut(alpha),
+        Alpha::Linear(alphas) => &mut alphas[..],
+        Alpha::LinearWithStop(alphas) => &mut alphas[..],
+        Alpha::Corners(alphas) => &mut alphas[..],
+        Alpha::CornersWithStop(alphas) => &mut alphas[..]
+    };
+    if lerp_stops(from_stops, to_stops, |from, to| lerp_u8(from, to, progress) ) || done {to} else {from.clone()}
+}
+/// Transformation tag, kept unresolved for renderer-specific matrix composition.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Transform {
+    Rotate(Rotate),
+    Scale(Scale),
+    Translate(Translate),
+    Shear(Shear),
+    Matrix(Box<[Degree;16]>)
+}
+/// Texture coordinates of geometry corners (left-top & right-bottom) with wrapping outside.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TexFill {
+    pub x0: Degree,
+    pub y0: Degree,
+    pub x1: Degree,
+    pub y1: Degree,
+    pub wrap: TextureWrapping
+}
+impl Default for TexFill {
+    fn default() -> Self {
+        Self {
+            x0: 0.0, y0: 0.0,
+            x1: 1.0, y1: 1.0,
+            wrap: TextureWrapping::Clamp
+        }
+    }
+}
+/// Drawable event object.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Geometry<'a> {
+    Shape(&'a [ShapeSegment]),
+    Points(&'a [Point2D]),
+    Text(&'a str),
+    /// Custom tag for a painter, inside animations with their progress (0 to 1, formula applied), otherwise complete.
+    Custom{
+        tag: &'a dyn CustomTag,
+        progress: f64
+    }
+}
+
+
+/// Iterator over geometries of event objects, each with its resolved style.
+pub struct StyleResolver<'a> {
+    objects: Iter<'a, EventObject>,
+    state: StyleState,
+    time: Option<((u32, u32), u32)>,
+    animated_custom_tags: VecDeque<(StyleState, Geometry<'a>)>
+}
+impl<'a> StyleResolver<'a> {
+    /// New resolver over objects, starting with default style.
+    pub fn new(objects: &'a [EventObject]) -> Self {
+        Self::with_state(objects, StyleState::default())
+    }
+    /// New resolver over objects, starting with given style.
+    pub fn with_state(objects: &'a [EventObject], state: StyleState) -> Self {
+        Self {
+            objects: objects.iter(),
+            state,
+            time: None,
+            animated_custom_tags: VecDeque::new()
+        }
+    }
+    /// New resolver over event objects with animations evaluated at current time (event time is the resolved trigger range).
+    pub fn at_time(event: &'a EventRender, event_time: (u32, u32), current_ms: u32) -> Self {
+        Self {
+            time: Some((event_time, current_ms)),
+            ..Self::new(&event.objects)
+        }
+    }
+    /// Style after all consumed objects.
+    pub fn state(&self) -> &StyleState {
+        &self.state
+    }
+}
+impl<'a> Iterator for StyleResolver<'a> {
+    type Item = (StyleState, Geometry<'a>);
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(custom_tag) = self.animated_custom_tags.pop_front() {
+            return Some(custom_tag);
+        }
+        for object in self.objects.by_ref() {
+            let geometry = match object {
+                EventObject::GeometryShape(segments) => Geometry::Shape(segments),
+                EventObject::GeometryPoints(points) => Geometry::Points(points),
+                EventObject::GeometryText(text) => Geometry::Text(text),
+                // Custom tags don't count as geometries for masking
+                EventObject::TagCustom(tag) => return Some((self.state.clone(), Geometry::Custom {tag: tag.as_ref(), progress: 1.0})),
+                EventObject::TagAnimate(animate) => {
+                    if let Some((event_time, current_ms)) = self.time {
+                        let (progress, linear_progress) = animation_progress(animate, event_time, current_ms);
+                        for tag in &animate.tags {
+                            match tag {
+                                EventObject::TagCustom(custom_tag) => self.animated_custom_tags.push_back((self.state.clone(), Geometry::Custom {tag: custom_tag.as_ref(), progress})),
+                                tag => self.state.apply_animated(tag, progress, linear_progress)
+                            }
+                        }
+                        if let Some(custom_tag) = self.animated_custom_tags.pop_front() {
+                            return Some(custom_tag);
+                        }
+                    }
+                    continue;
+                }
+                tag => {
+                    self.state.apply(tag);
+                    continue;
+                }
+            };
+            let state = self.state.clone();
+            self.state.mask_clear = false;
+            return Some((state, geometry));
+        }
+        None
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{StyleResolver,StyleState,Geometry,Transform,animation_progress};
+    use crate::{
+        objects::{
+            ssb_objects::{EventRender,EventTrigger},
+            event_objects::{EventObject,Margin,Rotate,Color,Alpha,Point3D,Animate,Formula}
+        },
+        tags::tests::Glow
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn runs() {
+        let objects = vec![
+            EventObject::TagBold(true),
+            EventObject::TagMargin(Margin::All(1.0, 2.0, 3.0, 4.0)),
+            EventObject::TagKaraoke(100),
+            EventObject::GeometryText("A".to_owned()),
+            EventObject::TagMargin(Margin::Left(5.0)),
+            EventObject::TagRotate(Rotate::Z(45.0)),
+            EventObject::TagMaskClear,
+            EventObject::TagKaraoke(200),
+            EventObject::GeometryText("B".to_owned()),
+            EventObject::TagReset,
+            EventObject::TagColor(Color::Mono([1, 2, 3])),
+            EventObject::TagKaraokeSet(50),
+            EventObject::TagKaraoke(300),
+            EventObject::GeometryPoints(vec![]),
+            EventObject::TagSize(12.0)
+        ];
+        let mut resolver = StyleResolver::new(&objects);
+        let (style, geometry) = resolver.next().unwrap();
+        assert_eq!(geometry, Geometry::Text("A"));
+        assert!(style.bold && !style.mask_clear);
+        assert_eq!(style.margin, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(style.karaoke, Some((0, 100)));
+        let (style, geometry) = resolver.next().unwrap();
+        assert_eq!(geometry, Geometry::Text("B"));
+        assert!(style.bold && style.mask_clear);
+        assert_eq!(style.margin, [1.0, 2.0, 3.0, 5.0]);
+        assert_eq!(style.transforms, vec![Transform::Rotate(Rotate::Z(45.0))]);
+        assert_eq!(style.karaoke, Some((100, 200)));
+        let (style, geometry) = resolver.next().unwrap();
+        assert_eq!(geometry, Geometry::Points(&[]));
+        assert_eq!(style, StyleState {
+            color: Color::Mono([1, 2, 3]),
+            karaoke: Some((50, 300)),
+            ..StyleState::default()
+        });
+        assert_eq!(resolver.next(), None);
+        assert_eq!(resolver.state().size, 12.0);
+    }
+
+    #[test]
+    fn animations() {
+        let animate = |time, formula: Option<&str>, tags| EventObject::TagAnimate(Box::new(Animate {
+            time,
+            formula: formula.map(|formula| Formula::new(formula).unwrap() ),
+            tags
+        }));
+        let event = EventRender {
+            trigger: EventTrigger::Time((1000, 3000)),
+            objects: vec![
+                EventObject::TagPosition(Point3D {x: 0.0, y: 0.0, z: 0.0}),
+                animate(None, None, vec![
+                    EventObject::TagSize(40.0),
+                    EventObject::TagPosition(Point3D {x: 100.0, y: 50.0, z: 0.0}),
+                    EventObject::TagColor(Color::Linear([[0, 0, 0], [255, 255, 255]])),
+                    EventObject::TagRotate(Rotate::Z(90.0)),
+                    EventObject::TagBold(true)
+                ]),
+                animate(Some((-1000, -500)), Some("t^2"), vec![
+                    EventObject::TagAlpha(Alpha::Mono(55))
+                ]),
+                EventObject::GeometryText("A".to_owned())
+            ]
+        };
+        let style_at = |current_ms| StyleResolver::at_time(&event, (1000, 3000), current_ms).next().unwrap().0;
+        let style = style_at(2000);
+        assert_eq!(style.size, 35.0);
+        assert_eq!(style.position, Some(Point3D {x: 50.0, y: 25.0, z: 0.0}));
+        assert_eq!(style.color, Color::Linear([[128, 128, 128], [255, 255, 255]]));
+        assert_eq!(style.transforms, vec![Transform::Rotate(Rotate::Z(45.0))]);
+        assert!(!style.bold);
+        assert_eq!(style.alpha, Alpha::Mono(255));
+        let style = style_at(2250);
+        assert_eq!(style.alpha, Alpha::Mono(205));
+        assert_eq!(style.color, Color::Linear([[96, 96, 96], [255, 255, 255]]));
+        let style = style_at(5000);
+        assert_eq!(style.size, 40.0);
+        assert!(style.bold);
+        assert_eq!(style.alpha, Alpha::Mono(55));
+        assert_eq!(style_at(0), StyleState {
+            position: Some(Point3D {x: 0.0, y: 0.0, z: 0.0}),
+            color: Color::Linear([[255, 255, 255], [255, 255, 255]]),
+            transforms: vec![Transform::Rotate(Rotate::Z(0.0))],
+            ..StyleState::default()
+        });
+        // Animations get ignored without time
+        assert_eq!(StyleResolver::new(&event.objects).next().unwrap().0.size, 30.0);
+        if let EventObject::TagAnimate(animate) = &event.objects[2] {
+            assert_eq!(animation_progress(animate, (1000, 3000), 2250), (0.25, 0.5));
+        }
+    }
+
+    #[test]
+    fn custom_tags() {
+        let event = EventRender {
+            trigger: EventTrigger::Time((0, 1000)),
+            objects: vec![
+                EventObject::TagCustom(Arc::new(Glow(1.0))),
+                EventObject::TagAnimate(Box::new(Animate {
+                    time: None,
+                    formula: None,
+                    tags: vec![
+                        EventObject::TagSize(40.0),
+                        EventObject::TagCustom(Arc::new(Glow(2.0))),
+                        EventObject::TagCustom(Arc::new(Glow(3.0)))
+                    ]
+                })),
+                EventObject::GeometryText("A".to_owned())
+            ]
+        };
+        let items = StyleResolver::at_time(&event, (0, 1000), 250).map(|(style, geometry)| match geometry {
+            Geometry::Custom {tag, progress} => (style.size, tag.value(), progress),
+            _ => (style.size, None, 1.0)
+        } ).collect::<Vec<_>>();
+        assert_eq!(items, vec![
+            (30.0, Some("1".to_owned()), 1.0),
+            (32.5, Some("2".to_owned()), 0.25),
+            (32.5, Some("3".to_owned()), 0.25),
+            (32.5, None, 1.0)
+        ]);
+        // Animations get ignored without time
+        assert_eq!(StyleResolver::new(&event.objects).count(), 2);
+    }
+}
@@ -0,0 +1,182 @@
+// Imports
+use crate::{
+    state::error::ParseError,
+    objects::event_objects::EventObject
+};
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    sync::Arc
+};
+
+
+/// Typed payload of a custom tag, produced by its registered parser.
+///
+/// Payloads get compared and written as SSB by name & value.
+pub trait CustomTag: Any + fmt::Debug + Send + Sync {
+    /// Tag name as registered.
+    fn name(&self) -> &str;
+    /// Tag value (unescaped), `None` for tags without value.
+    fn value(&self) -> Option<String>;
+    /// Payload for downcasting (simply `self`).
+    fn as_any(&self) -> &dyn Any;
+}
+impl dyn CustomTag {
+    /// Payload as concrete type.
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+        where T: CustomTag {
+        self.as_any().downcast_ref()
+    }
+}
+impl PartialEq for dyn CustomTag {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name() && self.value() == other.value()
+    }
+}
+
+/// Parser of custom tag values into payloads, failing with a reason.
+pub trait TagParser: Send + Sync {
+    /// Parse value of tag (`None` for tags without value).
+    fn parse(&self, value: Option<&str>) -> Result<Arc<dyn CustomTag>, String>;
+}
+impl<F> TagParser for F
+    where F: Fn(Option<&str>) -> Result<Arc<dyn CustomTag>, String> + Send + Sync {
+    fn parse(&self, value: Option<&str>) -> Result<Arc<dyn CustomTag>, String> {
+        self(value)
+    }
+}
+
+/// Custom tags by name, consulted for tags unknown to SSB.
+///
+/// Built-in tags take precedence, so they can't be overridden.
+#[derive(Default, Clone)]
+pub struct TagRegistry {
+    parsers: HashMap<String, Arc<dyn TagParser>>
+}
+impl TagRegistry {
+    /// New registry without tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add parser by tag name (replaces previous one).
+    pub fn register<P>(&mut self, name: &str, parser: P) -> &mut Self
+        where P: TagParser + 'static {
+        self.parsers.insert(name.to_owned(), Arc::new(parser));
+        self
+    }
+    /// Registered tag names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.parsers.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+    // Parse tag by registered parser, if any
+    pub(crate) fn parse(&self, name: &str, value: Option<&str>) -> Option<Result<EventObject, ParseError>> {
+        self.parsers.get(name).map(|parser| parser.parse(value)
+            .map(EventObject::TagCustom)
+            .map_err(|reason| ParseError::new_with_source(&format!("Invalid {} '{}'!", name, value.unwrap_or("")), ParseError::new(&reason)) )
+        )
+    }
+}
+impl fmt::Debug for TagRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TagRegistry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{CustomTag,TagRegistry};
+    use crate::{
+        objects::event_objects::EventObject,
+        parsers::{
+            ssb::Ssb,
+            ssb_render::{SsbRender,RenderOptions}
+        }
+    };
+    use std::{
+        any::Any,
+        io::Cursor,
+        sync::Arc
+    };
+
+    // Custom tag fixture (shared with other unit tests)
+    #[derive(Debug, PartialEq)]
+    pub(crate) struct Glow(pub f32);
+    impl CustomTag for Glow {
+        fn name(&self) -> &str {
+            "glow"
+        }
+        fn value(&self) -> Option<String> {
+            Some(self.0.to_string())
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    pub(crate) fn parse_glow(value: Option<&str>) -> Result<Arc<dyn CustomTag>, String> {
+        value.and_then(|value| value.parse().ok() )
+            .map(|strength| Arc::new(Glow(strength)) as Arc<dyn CustomTag> )
+            .ok_or_else(|| "Strength expected!".to_owned() )
+    }
+    fn render(data: &str, registry: &TagRegistry) -> Result<Vec<EventObject>, String> {
+        let ssb = Ssb::default().parse_owned(Cursor::new(format!("#EVENTS\n0-1.0|||{}", data))).map_err(|err| err.to_string() )?;
+        SsbRender::from_with_options(ssb, &RenderOptions::new().registry(registry)).map(|(mut ssb_render, _)| ssb_render.events.remove(0).objects ).map_err(|err| err.to_string() )
+    }
+
+    #[test]
+    fn registry() {
+        let mut registry = TagRegistry::new();
+        registry.register("glow", parse_glow);
+        assert_eq!(registry.names(), vec!["glow"]);
+        assert!(registry.parse("shadow", None).is_none());
+        match registry.parse("glow", Some("2.5")) {
+            Some(Ok(EventObject::TagCustom(tag))) => assert_eq!(tag.downcast_ref::<Glow>(), Some(&Glow(2.5))),
+            result => panic!("Glow expected, got: {:?}", result)
+        }
+        assert_eq!(
+            registry.parse("glow", Some("strong")).map(|result| result.map_err(|err| err.to_string() ) ),
+            Some(Err("Invalid glow 'strong'!\nStrength expected!".to_owned()))
+        );
+    }
+
+    #[test]
+    fn builtin_precedence() {
+        let mut registry = TagRegistry::new();
+        registry.register("bold", parse_glow).register("glow", parse_glow);
+        assert_eq!(render("[bold=y]", &registry), Ok(vec![EventObject::TagBold(true)]));
+        assert_eq!(render("[bold=2]", &registry), Err("Invalid event data! <1:9>\nInvalid bold '2'!".to_owned()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let mut registry = TagRegistry::new();
+        registry.register("glow", parse_glow);
+        assert_eq!(render("Hi[glow=bright]", &registry), Err("Invalid event data! <1:11>\nInvalid glow 'bright'!\nStrength expected!".to_owned()));
+        assert_eq!(render("[animate=[glow]]", &registry), Err("Invalid event data! <1:9>\nInvalid animate '[glow]'!\nInvalid glow ''!\nStrength expected!".to_owned()));
+    }
+
+    #[test]
+    fn format() {
+        let mut registry = TagRegistry::new();
+        registry.register("glow", parse_glow);
+        let objects = render("[glow=2.5]Hi", &registry).unwrap();
+        assert_eq!(objects[0].to_string(), "[glow=2.5]");
+        let data = EventObject::format_many(&objects).unwrap();
+        assert_eq!(render(&data, &registry), Ok(objects));
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn serialization_skip() {
+        let glow = EventObject::TagCustom(Arc::new(Glow(1.0)));
+        assert!(serde_json::to_string(&glow).is_err());
+        assert_eq!(serde_json::to_string(&vec![EventObject::TagBold(true)]).ok().as_deref(), Some(r#"[{"TagBold":true}]"#));
+    }
+}
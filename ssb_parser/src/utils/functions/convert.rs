@@ -1,12 +1,13 @@
-use crate::utils::pattern::TIMESTAMP_PATTERN;
+use crate::utils::pattern::{TIMESTAMP_PATTERN,TAG_START,TAG_END,MACRO_INLINE_START};
 
 
+// Milliseconds factors
+const MS_2_MS: u32 = 1;
+const S_2_MS: u32 = MS_2_MS * 1000;
+const M_2_MS: u32 = S_2_MS * 60;
+const H_2_MS: u32 = M_2_MS * 60;
+
 pub fn parse_timestamp(timestamp: &str) -> Result<u32,()> {
-    // Milliseconds factors
-    const MS_2_MS: u32 = 1;
-    const S_2_MS: u32 = MS_2_MS * 1000;
-    const M_2_MS: u32 = S_2_MS * 60;
-    const H_2_MS: u32 = M_2_MS * 60;
     // Calculate time in milliseconds
     let mut ms = 0u32;
     let captures = TIMESTAMP_PATTERN.captures(timestamp).ok_or_else(|| ())?;
@@ -20,6 +21,16 @@ pub fn parse_timestamp(timestamp: &str) -> Result<u32,()> {
     // Return time
     Ok(ms)
 }
+pub fn format_timestamp(ms: u32) -> Result<String,()> {
+    // Split time into units
+    let (hours, minutes, seconds, milliseconds) = (ms / H_2_MS, ms % H_2_MS / M_2_MS, ms % M_2_MS / S_2_MS, ms % S_2_MS);
+    // Format with hours only when required (at most two digits)
+    match hours {
+        0 => Ok(format!("{}:{:02}.{:03}", minutes, seconds, milliseconds)),
+        1..=99 => Ok(format!("{}:{:02}:{:02}.{:03}", hours, minutes, seconds, milliseconds)),
+        _ => Err(())
+    }
+}
 
 pub fn bool_from_str(text: &str) -> Result<bool,()> {
     match text {
@@ -42,14 +53,22 @@ pub fn rgb_from_str(text: &str) -> Result<[u8;3],()> {
     }
 }
 
+pub fn escape_text(text: &str) -> String {
+    // Backslashes first to keep inserted escapes, macro references broken by escaped brace
+    text.replace('\\', "\\\\").replace(TAG_START, &("\\".to_owned() + TAG_START)).replace(TAG_END, &("\\".to_owned() + TAG_END)).replace('\n', "\\n")
+        .replace(MACRO_INLINE_START, &MACRO_INLINE_START.replace('{', "\\{"))
+}
+
 
 #[cfg(test)]
 mod tests {
     use super::{
         parse_timestamp,
+        format_timestamp,
         bool_from_str,
         alpha_from_str,
-        rgb_from_str
+        rgb_from_str,
+        escape_text
     };
 
     #[test]
@@ -60,6 +79,16 @@ mod tests {
         assert_eq!(parse_timestamp("1::.1"), Ok(3_600_001));
     }
 
+    #[test]
+    fn format_timestamp_various() {
+        assert_eq!(format_timestamp(0), Ok("0:00.000".to_owned()));
+        assert_eq!(format_timestamp(62_003), Ok("1:02.003".to_owned()));
+        assert_eq!(format_timestamp(3_600_001), Ok("1:00:00.001".to_owned()));
+        assert_eq!(format_timestamp(359_999_999), Ok("99:59:59.999".to_owned()));
+        assert_eq!(format_timestamp(360_000_000), Err(()));
+        assert_eq!(parse_timestamp(&format_timestamp(12_345_678).unwrap()), Ok(12_345_678));
+    }
+
     #[test]
     fn parse_bool() {
         assert_eq!(bool_from_str("y"), Ok(true));
@@ -79,4 +108,11 @@ mod tests {
         assert_eq!(rgb_from_str("FFFF01"), Ok([255, 255, 1]));
         assert_eq!(rgb_from_str("1FFFFFF"), Err(()));
     }
+
+    #[test]
+    fn escape_text_various() {
+        assert_eq!(escape_text("plain"), "plain");
+        assert_eq!(escape_text("[a]\\b\nc"), "\\[a\\]\\\\b\\nc");
+        assert_eq!(escape_text("${a}"), "$\\{a}");
+    }
 }
\ No newline at end of file
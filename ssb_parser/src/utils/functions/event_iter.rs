@@ -3,16 +3,42 @@ use crate::utils::pattern::*;
 
 pub struct EscapedText {
     text: String,
-    tag_starts_ends: Vec<(usize,char)>
+    tag_starts_ends: Vec<(usize,char)>,
+    escapes: Vec<usize>
 }
 impl EscapedText {
     pub fn new(source: &str) -> Self {
-        let text = source.replace("\\\\", "\x1B").replace(&("\\".to_owned() + TAG_START), "\x02").replace(&("\\".to_owned() + TAG_END), "\x03").replace("\\n", "\n").replace('\x1B', "\\");
+        let mut text = String::with_capacity(source.len());
+        let mut tag_starts_ends = vec![];
+        let mut escapes = vec![];
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.peek().copied() {
+                    Some(next @ '\\') | Some(next @ TAG_START_CHAR) | Some(next @ TAG_END_CHAR) | Some(next @ '{') | Some(next @ 'n') => {
+                        escapes.push(text.len());
+                        text.push(if next == 'n' {'\n'} else {next});
+                        chars.next();
+                    }
+                    _ => text.push(c)
+                }
+                TAG_START_CHAR | TAG_END_CHAR => {
+                    tag_starts_ends.push((text.len(), c));
+                    text.push(c);
+                }
+                _ => text.push(c)
+            }
+        }
         Self {
-            text: text.replace('\x02', TAG_START).replace('\x03', TAG_END),
-            tag_starts_ends: text.char_indices().filter(|(_,c)| *c == TAG_START_CHAR || *c == TAG_END_CHAR).collect()
+            text,
+            tag_starts_ends,
+            escapes
         }
     }
+    /// Offset in source text for offset in escaped text (escapes shrink by one byte).
+    pub fn source_offset(&self, offset: usize) -> usize {
+        offset + self.escapes.iter().take_while(|escape| **escape < offset).count()
+    }
     pub fn iter(&self) -> TagGeometryIterator {
         TagGeometryIterator {
             source: self,
@@ -25,7 +51,7 @@ pub struct TagGeometryIterator<'src> {
     pos: usize
 }
 impl<'src> Iterator for TagGeometryIterator<'src> {
-    type Item = (bool, &'src str);
+    type Item = (bool, usize, &'src str);
     fn next(&mut self) -> Option<Self::Item> {
         // End of source reached?
         if self.pos == self.source.text.len() {
@@ -36,6 +62,7 @@ impl<'src> Iterator for TagGeometryIterator<'src> {
         // Match tag or geometry
         let is_tag;
         let text_chunk;
+        let chunk_start;
         if tag_start.filter(|pos| *pos == self.pos).is_some() {
             is_tag = true;
             // Till tag end (considers nested tags)
@@ -46,15 +73,18 @@ impl<'src> Iterator for TagGeometryIterator<'src> {
                 TAG_END_CHAR => if tag_open_count == 0 {true} else {tag_open_count-=1; false}
                 _ => false
             }).map(|(pos,_)| *pos) {
+                chunk_start = self.pos + TAG_START.len();
                 text_chunk = &self.source.text[self.pos + TAG_START.len()..end_pos];
                 self.pos = end_pos + TAG_END.len();
             // Till end
             } else {
+                chunk_start = self.pos + TAG_START.len();
                 text_chunk = &self.source.text[self.pos + TAG_START.len()..];
                 self.pos = self.source.text.len();
             }
         } else {
             is_tag = false;
+            chunk_start = self.pos;
             // Till tag start
             if let Some(tag_start) = tag_start {
                 text_chunk = &self.source.text[self.pos..tag_start];
@@ -66,24 +96,30 @@ impl<'src> Iterator for TagGeometryIterator<'src> {
             }
         }
         // Return tag or geometry
-        Some((is_tag, text_chunk))
+        Some((is_tag, chunk_start, text_chunk))
     }
 }
 
 pub struct TagsIterator<'src> {
     text: &'src str,
-    pos: usize
+    pos: usize,
+    max_nesting: usize
 }
 impl<'src> TagsIterator<'src> {
     pub fn new(text: &'src str) -> Self {
+        Self::with_max_nesting(text, usize::MAX)
+    }
+    pub fn with_max_nesting(text: &'src str, max_nesting: usize) -> Self {
         Self {
             text,
-            pos: 0
+            pos: 0,
+            max_nesting
         }
     }
 }
 impl<'src> Iterator for TagsIterator<'src> {
-    type Item = (&'src str, Option<&'src str>);
+    // Tag position, name & value or position of too deep nesting
+    type Item = Result<(usize, &'src str, Option<&'src str>), usize>;
     fn next(&mut self) -> Option<Self::Item> {
         // End of source reached?
         if self.pos == self.text.len() {
@@ -91,14 +127,22 @@ impl<'src> Iterator for TagsIterator<'src> {
         }
         // Find next tag separator (considers nested tags)
         let mut tag_open_count = 0usize;
+        let max_nesting = self.max_nesting;
         let tag_sep = self.text.char_indices().skip(self.pos).find(|(_,c)| match *c {
-            TAG_START_CHAR => {tag_open_count+=1; false}
+            TAG_START_CHAR => {tag_open_count+=1; tag_open_count > max_nesting}
             TAG_END_CHAR => {if tag_open_count > 0 {tag_open_count-=1} false}
             TAG_SEPARATOR if tag_open_count == 0 => true,
             _ => false
         }).map(|(index,_)| index);
+        // Stop on too deep nesting
+        if tag_open_count > max_nesting {
+            let nesting_pos = tag_sep.unwrap_or(self.pos);
+            self.pos = self.text.len();
+            return Some(Err(nesting_pos));
+        }
         // Match till separator or end
         let tag_token;
+        let tag_start = self.pos;
         if let Some(tag_sep) = tag_sep {
             tag_token = &self.text[self.pos..tag_sep];
             self.pos = tag_sep + 1 /* TAG_SEPARATOR */;
@@ -108,9 +152,9 @@ impl<'src> Iterator for TagsIterator<'src> {
         }
         // Split into name+value and return
         if let Some(tag_assign) = tag_token.find(TAG_ASSIGN) {
-            Some((&tag_token[..tag_assign], Some(&tag_token[tag_assign + 1 /* TAG_ASSIGN */..])))
+            Some(Ok((tag_start, &tag_token[..tag_assign], Some(&tag_token[tag_assign + 1 /* TAG_ASSIGN */..]))))
         } else {
-            Some((tag_token, None))
+            Some(Ok((tag_start, tag_token, None)))
         }
     }
 }
@@ -124,23 +168,28 @@ mod tests {
     fn tag_geometry_iter() {
         let text = EscapedText::new("[tag1][tag2=[inner_tag]]geometry1\\[geometry1_continue\\\\[tag3]geometry2\\n[tag4");
         let mut iter = text.iter();
-        assert_eq!(iter.next(), Some((true, "tag1")));
-        assert_eq!(iter.next(), Some((true, "tag2=[inner_tag]")));
-        assert_eq!(iter.next(), Some((false, "geometry1[geometry1_continue\\")));
-        assert_eq!(iter.next(), Some((true, "tag3")));
-        assert_eq!(iter.next(), Some((false, "geometry2\n")));
-        assert_eq!(iter.next(), Some((true, "tag4")));
+        assert_eq!(iter.next(), Some((true, 1, "tag1")));
+        assert_eq!(iter.next(), Some((true, 7, "tag2=[inner_tag]")));
+        assert_eq!(iter.next(), Some((false, 24, "geometry1[geometry1_continue\\")));
+        assert_eq!(iter.next(), Some((true, 54, "tag3")));
+        assert_eq!(iter.next(), Some((false, 59, "geometry2\n")));
+        assert_eq!(iter.next(), Some((true, 70, "tag4")));
         assert_eq!(iter.next(), None);
+        assert_eq!(text.source_offset(59), 61);
     }
 
     #[test]
     fn tags_iter() {
         let mut iter = TagsIterator::new("mode=points;reset;animate=0,-500,[position=200,100.5];color=ff00ff;mask-clear");
-        assert_eq!(iter.next(), Some(("mode", Some("points"))));
-        assert_eq!(iter.next(), Some(("reset", None)));
-        assert_eq!(iter.next(), Some(("animate", Some("0,-500,[position=200,100.5]"))));
-        assert_eq!(iter.next(), Some(("color", Some("ff00ff"))));
-        assert_eq!(iter.next(), Some(("mask-clear", None)));
+        assert_eq!(iter.next(), Some(Ok((0, "mode", Some("points")))));
+        assert_eq!(iter.next(), Some(Ok((12, "reset", None))));
+        assert_eq!(iter.next(), Some(Ok((18, "animate", Some("0,-500,[position=200,100.5]")))));
+        assert_eq!(iter.next(), Some(Ok((54, "color", Some("ff00ff")))));
+        assert_eq!(iter.next(), Some(Ok((67, "mask-clear", None))));
+        assert_eq!(iter.next(), None);
+        let mut iter = TagsIterator::with_max_nesting("bold=y;animate=[animate=[italic=y]]", 1);
+        assert_eq!(iter.next(), Some(Ok((0, "bold", Some("y")))));
+        assert_eq!(iter.next(), Some(Err(24)));
         assert_eq!(iter.next(), None);
     }
 }
\ No newline at end of file
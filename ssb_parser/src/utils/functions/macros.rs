@@ -1,33 +1,51 @@
-use crate::utils::pattern::*;
+use crate::{
+    state::limits::{ParseLimits,Limit,LimitError},
+    utils::pattern::*
+};
+use regex::Captures;
 use std::collections::{HashMap,HashSet};
 
 
-pub fn flatten_macro<'a>(macro_name: &str, history: &mut HashSet<&'a str>, macros: &'a HashMap<String, String>, flat_macros: &mut HashMap<&'a str, String>) -> Result<(), MacroError> {
+pub fn flatten_macro<'a>(macro_name: &str, history: &mut HashSet<&'a str>, macros: &'a HashMap<String, String>, flat_macros: &mut HashMap<&'a str, String>, limits: &ParseLimits) -> Result<(), MacroError> {
     // Macro already flattened?
     if flat_macros.contains_key(macro_name) {
         return Ok(());
     }
     // Macro exists?
-    let (macro_name, mut flat_macro_value) = macros.get_key_value(macro_name)
-        .map(|(key,value)| (key.as_str(), value.to_owned()))
+    let (macro_name, macro_value) = macros.get_key_value(macro_name)
+        .map(|(key,value)| (key.as_str(), value.as_str()))
         .ok_or_else(|| MacroError::NotFound(macro_name.to_owned()))?;
     // Macro already in history (avoid infinite loop!)
     if history.contains(macro_name) {
         return Err(MacroError::InfiniteLoop(macro_name.to_owned()));
+    } else if history.len() >= limits.max_macro_depth {
+        return Err(MacroError::LimitExceeded(macro_name.to_owned(), LimitError::new(Limit::MacroDepth, limits.max_macro_depth)));
     } else {
         history.insert(macro_name);
     }
     // Process macro value
-    while let Some(found) = MACRO_PATTERN.find(&flat_macro_value) {
+    let mut flat_macro_value = String::with_capacity(macro_value.len());
+    let mut rest_start = 0;
+    for captures in MACRO_PATTERN.captures_iter(macro_value) {
+        let found = captures.get(0).expect("Pattern match is always captured!");
+        flat_macro_value.push_str(&macro_value[rest_start..found.start()]);
+        // Keep parameter for insertion
+        if parameter_index(&captures[1]).is_some() {
+            flat_macro_value.push_str(found.as_str());
         // Insert sub-macro
-        let sub_macro_name = &flat_macro_value[found.start()+MACRO_INLINE_START.len()..found.end()-MACRO_INLINE_END.len()];
-        if !flat_macros.contains_key(sub_macro_name) {
-            flatten_macro(sub_macro_name, history, macros, flat_macros)?;
+        } else {
+            flatten_macro(&captures[1], history, macros, flat_macros, limits)?;
+            flat_macro_value.push_str(&expand_macro(&captures[1], captures.get(2).map(|arguments| arguments.as_str() ), flat_macros, limits)?);
+        }
+        rest_start = found.end();
+        // Stop growth early
+        if flat_macro_value.len() > limits.max_event_length {
+            return Err(MacroError::LimitExceeded(macro_name.to_owned(), LimitError::new(Limit::EventLength, limits.max_event_length)));
         }
-        let sub_macro_location = found.start()..found.end();
-        let sub_macro_value = flat_macros.get(sub_macro_name).ok_or_else(|| MacroError::NotFound(sub_macro_name.to_owned()))?;
-        flat_macro_value.replace_range(sub_macro_location, sub_macro_value);
     }
+    flat_macro_value.push_str(&macro_value[rest_start..]);
+    // Leave history for siblings (only ancestors could cause a loop)
+    history.remove(macro_name);
     // Register flat macro
     flat_macros.insert(
         macro_name,
@@ -37,16 +55,70 @@ pub fn flatten_macro<'a>(macro_name: &str, history: &mut HashSet<&'a str>, macro
     Ok(())
 }
 
+pub fn expand_macro(macro_name: &str, arguments: Option<&str>, flat_macros: &HashMap<&str, String>, limits: &ParseLimits) -> Result<String, MacroError> {
+    // Macro flattened?
+    let flat_macro_value = flat_macros.get(macro_name).ok_or_else(|| MacroError::NotFound(macro_name.to_owned()))?;
+    // Arguments match parameters? (highest placeholder number)
+    let arguments = arguments.filter(|arguments| !arguments.is_empty() ).map_or_else(Vec::new, split_arguments);
+    let parameters_count = MACRO_PATTERN.captures_iter(flat_macro_value).filter_map(|captures| parameter_index(&captures[1]) ).max().unwrap_or(0);
+    if arguments.len() != parameters_count {
+        return Err(MacroError::ArityMismatch(macro_name.to_owned(), parameters_count, arguments.len()));
+    }
+    // Insert arguments (growth by longest argument for each placeholder)
+    if flat_macro_value.len() + MACRO_PATTERN.find_iter(flat_macro_value).count() * arguments.iter().map(|argument| argument.len() ).max().unwrap_or(0) > limits.max_event_length {
+        return Err(MacroError::LimitExceeded(macro_name.to_owned(), LimitError::new(Limit::EventLength, limits.max_event_length)));
+    }
+    Ok(
+        if parameters_count == 0 {
+            flat_macro_value.to_owned()
+        } else {
+            MACRO_PATTERN.replace_all(flat_macro_value, |captures: &Captures| match parameter_index(&captures[1]) {
+                Some(index) => arguments[index - 1].clone(),
+                None => captures[0].to_owned()
+            }).into_owned()
+        }
+    )
+}
+
+// Split arguments by separators, except escaped ones (other escapes are kept for event data)
+fn split_arguments(arguments: &str) -> Vec<String> {
+    let mut split_arguments = vec![String::new()];
+    let mut chars = arguments.chars();
+    while let Some(c) = chars.next() {
+        let argument = split_arguments.last_mut().expect("At least one argument exists!");
+        match c {
+            '\\' => match chars.next() {
+                Some(VALUE_SEPARATOR) => argument.push(VALUE_SEPARATOR),
+                Some(next) => {
+                    argument.push(c);
+                    argument.push(next);
+                }
+                None => argument.push(c)
+            }
+            VALUE_SEPARATOR => split_arguments.push(String::new()),
+            _ => argument.push(c)
+        }
+    }
+    split_arguments
+}
+
+fn parameter_index(name: &str) -> Option<usize> {
+    name.parse().ok().filter(|index| *index > 0)
+}
+
 #[derive(Debug, PartialEq)]
 pub enum MacroError {
     NotFound(String),
-    InfiniteLoop(String)
+    InfiniteLoop(String),
+    // Name, parameters & arguments count
+    ArityMismatch(String, usize, usize),
+    LimitExceeded(String, LimitError)
 }
 
 
 #[cfg(test)]
 mod tests {
-    use super::{flatten_macro,HashMap,HashSet,MacroError};
+    use super::{flatten_macro,expand_macro,HashMap,HashSet,MacroError,ParseLimits,Limit,LimitError};
 
     #[test]
     fn flatten_macro_success() {
@@ -57,7 +129,7 @@ mod tests {
         macros.insert("c".to_owned(), "om".to_owned());
         let mut flat_macros = HashMap::new();
         // Test execution
-        flatten_macro("a", &mut HashSet::new(), &macros, &mut flat_macros).unwrap();
+        flatten_macro("a", &mut HashSet::new(), &macros, &mut flat_macros, &ParseLimits::default()).unwrap();
         assert_eq!(flat_macros.get("a").unwrap(), "Hello from test!");
     }
     #[test]
@@ -67,11 +139,43 @@ mod tests {
         macros.insert("a".to_owned(), "foo ${b}".to_owned());
         macros.insert("b".to_owned(), "${a} bar".to_owned());
         // Test execution
-        assert_eq!(flatten_macro("a", &mut HashSet::new(), &macros, &mut HashMap::new()).unwrap_err(), MacroError::InfiniteLoop("a".to_owned()));
+        assert_eq!(flatten_macro("a", &mut HashSet::new(), &macros, &mut HashMap::new(), &ParseLimits::default()).unwrap_err(), MacroError::InfiniteLoop("a".to_owned()));
+    }
+    #[test]
+    fn flatten_macro_parameters() {
+        // Test data
+        let mut macros = HashMap::new();
+        macros.insert("a".to_owned(), "[color=${1}]${b(5)}".to_owned());
+        macros.insert("b".to_owned(), "[size=${1}${1}]".to_owned());
+        macros.insert("c".to_owned(), "${b}".to_owned());
+        let mut flat_macros = HashMap::new();
+        // Test execution
+        flatten_macro("a", &mut HashSet::new(), &macros, &mut flat_macros, &ParseLimits::default()).unwrap();
+        assert_eq!(flat_macros.get("a").unwrap(), "[color=${1}][size=55]");
+        assert_eq!(expand_macro("a", Some("ff0000"), &flat_macros, &ParseLimits::default()), Ok("[color=ff0000][size=55]".to_owned()));
+        assert_eq!(expand_macro("b", Some("4"), &flat_macros, &ParseLimits::default()), Ok("[size=44]".to_owned()));
+        assert_eq!(expand_macro("b", Some("4,2"), &flat_macros, &ParseLimits::default()), Err(MacroError::ArityMismatch("b".to_owned(), 1, 2)));
+        assert_eq!(expand_macro("b", Some("4\\,2"), &flat_macros, &ParseLimits::default()), Ok("[size=4,24,2]".to_owned()));
+        assert_eq!(expand_macro("a", Some("\\\\"), &flat_macros, &ParseLimits::default()), Ok("[color=\\\\][size=55]".to_owned()));
+        assert_eq!(flatten_macro("c", &mut HashSet::new(), &macros, &mut flat_macros, &ParseLimits::default()), Err(MacroError::ArityMismatch("b".to_owned(), 1, 0)));
+    }
+    #[test]
+    fn flatten_macro_limits() {
+        // Test data
+        let mut macros = HashMap::new();
+        macros.insert("a".to_owned(), "${b}${b}".to_owned());
+        macros.insert("b".to_owned(), "${c}${c}".to_owned());
+        macros.insert("c".to_owned(), "0123456789".to_owned());
+        let limits = ParseLimits {max_event_length: 30, ..ParseLimits::default()};
+        // Test execution
+        assert_eq!(flatten_macro("b", &mut HashSet::new(), &macros, &mut HashMap::new(), &limits), Ok(()));
+        assert_eq!(flatten_macro("a", &mut HashSet::new(), &macros, &mut HashMap::new(), &limits), Err(MacroError::LimitExceeded("a".to_owned(), LimitError::new(Limit::EventLength, 30))));
+        let limits = ParseLimits {max_macro_depth: 2, ..ParseLimits::default()};
+        assert_eq!(flatten_macro("a", &mut HashSet::new(), &macros, &mut HashMap::new(), &limits), Err(MacroError::LimitExceeded("c".to_owned(), LimitError::new(Limit::MacroDepth, 2))));
     }
     #[test]
     fn flatten_macro_notfound() {
-        assert_eq!(flatten_macro("x", &mut HashSet::new(), &HashMap::new(), &mut HashMap::new()).unwrap_err(), MacroError::NotFound("x".to_owned()));
+        assert_eq!(flatten_macro("x", &mut HashSet::new(), &HashMap::new(), &mut HashMap::new(), &ParseLimits::default()).unwrap_err(), MacroError::NotFound("x".to_owned()));
     }
 
     #[test]
@@ -4,6 +4,7 @@ use regex::{Regex,escape};
 
 
 // Constants
+pub const INCLUDE_PREFIX: &str = "#INCLUDE ";
 pub const INFO_TITLE_KEY: &str = "Title: ";
 pub const INFO_AUTHOR_KEY: &str = "Author: ";
 pub const INFO_DESCRIPTION_KEY: &str = "Description: ";
@@ -13,6 +14,7 @@ pub const TARGET_WIDTH_KEY: &str = "Width: ";
 pub const TARGET_HEIGHT_KEY: &str = "Height: ";
 pub const TARGET_DEPTH_KEY: &str = "Depth: ";
 pub const TARGET_VIEW_KEY: &str = "View: ";
+pub const TARGET_FRAMERATE_KEY: &str = "Framerate: ";
 pub const RESOURCES_FONT_KEY: &str = "Font: ";
 pub const RESOURCES_TEXTURE_KEY: &str = "Texture: ";
 pub const MACRO_INLINE_START: &str = "${";
@@ -20,6 +22,7 @@ pub const MACRO_INLINE_END: &str = "}";
 pub const VALUE_SEPARATOR: char = ',';
 pub const EVENT_SEPARATOR: char = '|';
 pub const TRIGGER_SEPARATOR: char = '-';
+pub const FRAME_SUFFIX: char = 'f';
 pub const TAG_START: &str = "[";
 pub const TAG_START_CHAR: char = '[';
 pub const TAG_END: &str = "]";
@@ -29,7 +32,9 @@ pub const TAG_ASSIGN: char = '=';
 
 // Statics
 lazy_static! {
-    pub static ref MACRO_PATTERN: Regex = Regex::new(&(escape(MACRO_INLINE_START) + "([a-zA-Z0-9_-]+)" + &escape(MACRO_INLINE_END))).unwrap();
+    // Inline macro with optional arguments, f.e. `${name}` or `${name(a,b)}`.
+    // Arguments are separated by commas (`\,` for a comma inside) and can't contain parentheses or braces, so no macros either.
+    pub static ref MACRO_PATTERN: Regex = Regex::new(&(escape(MACRO_INLINE_START) + "([a-zA-Z0-9_-]+)(?:\\(([^(){}]*)\\))?" + &escape(MACRO_INLINE_END))).unwrap();
     pub static ref TIMESTAMP_PATTERN: Regex = Regex::new("^(?:(?:(?P<H>\\d{0,2}):(?P<HM>[0-5]?\\d?):)|(?:(?P<M>[0-5]?\\d?):))?(?:(?P<S>[0-5]?\\d?)\\.)?(?P<MS>\\d{0,3})$").unwrap();
     pub static ref ANIMATE_PATTERN: Regex = Regex::new(&format!("^(?:(?P<S>-?\\d+){0}(?P<E>-?\\d+){0})?(?:(?P<F>.+?){0})?{1}(?P<T>.*?){2}$", escape(&VALUE_SEPARATOR.to_string()), escape(TAG_START), escape(TAG_END))).unwrap();
 }
\ No newline at end of file
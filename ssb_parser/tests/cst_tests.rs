@@ -0,0 +1,63 @@
+mod cst_tests {
+    // Imports
+    use ssb_parser::{
+        objects::cst_objects::*,
+        Ssb,
+        SsbCst
+    };
+    use std::{
+        convert::TryFrom,
+        fs::{File,read_to_string},
+        io::{BufReader, Cursor}
+    };
+
+
+    // Tester
+    #[test]
+    fn test_cst_simple() {
+        let text = "// Header comment\r\n#INFO\r\nAuthor: Youka\r\n\r\n#EVENTS\n0-1.|||Hi\n?\nlast line without ending";
+        let cst = SsbCst::parse(Cursor::new(text)).unwrap();
+        assert_eq!(cst.lines, vec![
+            CstLine {kind: CstLineKind::Comment(" Header comment".to_owned()), ending: Some(LineEnding::CrLf), span: Some((0, 17))},
+            CstLine {kind: CstLineKind::Section("#INFO".to_owned()), ending: Some(LineEnding::CrLf), span: Some((19, 24))},
+            CstLine {kind: CstLineKind::Entry {key: "Author".to_owned(), value: "Youka".to_owned()}, ending: Some(LineEnding::CrLf), span: Some((26, 39))},
+            CstLine {kind: CstLineKind::Empty, ending: Some(LineEnding::CrLf), span: Some((41, 41))},
+            CstLine {kind: CstLineKind::Section("#EVENTS".to_owned()), ending: Some(LineEnding::Lf), span: Some((43, 50))},
+            CstLine {kind: CstLineKind::Event {trigger: "0-1.".to_owned(), macro_name: "".to_owned(), note: "".to_owned(), data: "Hi".to_owned()}, ending: Some(LineEnding::Lf), span: Some((51, 60))},
+            CstLine {kind: CstLineKind::Other("?".to_owned()), ending: Some(LineEnding::Lf), span: Some((61, 62))},
+            CstLine {kind: CstLineKind::Other("last line without ending".to_owned()), ending: None, span: Some((63, 87))}
+        ]);
+        assert_eq!(cst.to_string(), text);
+        assert_eq!(
+            Ssb::try_from(&cst).map_err(|err| err.to_string()),
+            Err("Invalid events entry! <6:0>".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_cst_complex() {
+        // Parse file to syntax tree
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.ssb");
+        let mut cst = SsbCst::parse(
+            BufReader::new(
+                File::open(path)
+                .expect("Test SSB file must exist!")
+            )
+        ).unwrap_or_else(|exception| panic!("SSB syntax tree parsing error: {}", exception) );
+        // Print byte-for-byte
+        let mut output = vec![];
+        cst.write(&mut output).expect("Writing into memory mustn't fail!");
+        assert_eq!(String::from_utf8(output).expect("Output must be UTF-8!"), read_to_string(path).expect("Test SSB file must be readable!"));
+        // Derive same data as parsing text
+        let ssb = Ssb::default().parse_owned(BufReader::new(File::open(path).expect("Test SSB file must exist!"))).unwrap();
+        assert_eq!(Ssb::try_from(&cst).unwrap(), ssb);
+        // Edit
+        let author_line = cst.lines.iter_mut().find(|line| matches!(&line.kind, CstLineKind::Entry {key, ..} if key == "Author") ).expect("Author entry expected!");
+        author_line.kind = CstLineKind::Entry {key: "Author".to_owned(), value: "Somebody else".to_owned()};
+        cst.lines.insert(0, CstLine::new(CstLineKind::Comment(" Edited".to_owned())));
+        let edited_text = cst.to_string();
+        assert!(edited_text.starts_with("// Edited\n// Test script for ssb development\n"));
+        assert!(edited_text.contains("// Documentation is important!\nTitle: test\nAuthor: Somebody else\n"));
+        assert_eq!(Ssb::try_from(&cst).unwrap().info_author, Some("Somebody else".to_owned()));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+mod custom_tag_tests {
+    // Imports
+    use ssb_parser::{
+        tags::{CustomTag,TagRegistry},
+        RenderOptions,
+        Ssb,
+        SsbRender
+    };
+    use std::{
+        convert::TryFrom,
+        io::Cursor,
+        sync::Arc
+    };
+
+
+    // Tester
+    #[test]
+    fn test_custom_tags() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#EVENTS\n0-1.0|||Hi\n'error'|||[animate=[glow=strong]]")).unwrap();
+        // Without registry
+        assert_eq!(
+            SsbRender::try_from(ssb.clone()).map_err(|err| err.to_string() ),
+            Err("Invalid event data! <2:11>\nInvalid animate '[glow=strong]'!\nInvalid tag 'glow'!".to_owned())
+        );
+        // With registry
+        let mut registry = TagRegistry::new();
+        registry.register("glow", |_: Option<&str>| Err::<Arc<dyn CustomTag>, _>("Strength expected!".to_owned()) );
+        assert_eq!(
+            SsbRender::from_with_options(ssb, &RenderOptions::new().registry(&registry)).map(|_| () ).map_err(|err| err.to_string() ),
+            Err("Invalid event data! <2:11>\nInvalid animate '[glow=strong]'!\nInvalid glow 'strong'!\nStrength expected!".to_owned())
+        );
+    }
+}
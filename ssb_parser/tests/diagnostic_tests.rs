@@ -0,0 +1,149 @@
+mod diagnostic_tests {
+    // Imports
+    use ssb_parser::{
+        objects::event_objects::{EventObject,Animate},
+        RenderOptions,
+        Ssb,
+        SsbRender
+    };
+    use std::{
+        convert::TryFrom,
+        io::Cursor
+    };
+
+
+    // Test data
+    const SCRIPT: &str = "#INFO
+Title: Diagnostics
+Broken info line
+#MACROS
+a: [bold=y]
+a: [bold=n]
+b: [${c}]
+unused: [italic=y]
+loop: [${loop}]
+#EVENTS
+0-1.0|a||[size=abc;dummy=1]Hi
+0-1.0|missing||Text
+'x'|||[mode=points]0 0 1
+'x'|||${b}
+bad line
+#RESOURCES
+Font: Rabi,bold,UmFiaS1SaWJp
+Font: Rabi,bold,UmFiaS1SaWJp
+Texture: t,url,not/existing.png";
+    const MACRO_SCRIPT: &str = "#MACROS
+big: [size=20;bold=maybe]
+#EVENTS
+0-1.0|big|note|\\[x\\][italic=?]${big}";
+    const UNKNOWN_TAGS_SCRIPT: &str = "#MACROS
+glowing: [glow=5]
+#EVENTS
+0-1.0|glowing||[bold=y;shadow][animate=0,500,[glow=10]]Hi";
+
+
+    // Tester
+    #[test]
+    fn test_diagnostics() {
+        // Parse
+        let (ssb, diagnostics) = Ssb::default().parse_with_diagnostics(Cursor::new(SCRIPT));
+        assert_eq!(ssb.events.len(), 4);
+        assert_eq!(
+            diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "error[invalid-entry]: Invalid info entry! <2:0-2:16>",
+                "warning[duplicate-macro]: Macro defined again, overriding previous definition! <5:0-5:1>",
+                "error[invalid-entry]: Invalid events entry! <14:0-14:8>",
+                "warning[duplicate-resource]: Resource defined again, overriding previous definition! <17:0-17:10>"
+            ]
+        );
+        // Render
+        let (ssb_render, diagnostics) = SsbRender::from_with_options(ssb, &RenderOptions::new().diagnostics(true)).unwrap();
+        assert_eq!(ssb_render.events.iter().map(|event| event.objects.clone() ).collect::<Vec<_>>(), vec![vec![EventObject::TagBold(false), EventObject::GeometryText("Hi".to_owned())], vec![]]);
+        let mut diagnostics = diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>();
+        // Last error message depends on OS
+        assert!(diagnostics.pop().expect("Texture diagnostic expected!").starts_with("error[invalid-resource]: Texture data for 't' not loadable from url 'not/existing.png'!\n"));
+        assert_eq!(
+            diagnostics,
+            vec![
+                "error[unknown-macro]: Flattening macro 'b' caused error: NotFound(\"c\")",
+                "error[recursive-macro]: Flattening macro 'loop' caused error: InfiniteLoop(\"loop\")",
+                "warning[unused-macro]: Macro 'unused' unused!",
+                "warning[duplicate-id]: Event id 'x' already used, events overlap! <13:0-13:3>",
+                "error[invalid-tag]: Invalid event data!\nInvalid size 'abc'! <10:10-10:18>",
+                "error[unknown-tag]: Invalid event data!\nInvalid tag 'dummy'! <10:19-10:26>",
+                "error[unknown-macro]: Base macro 'missing' not found to insert! <11:0-11:15>",
+                "error[invalid-geometry]: Invalid event data!\nPoints incomplete (leftover: '1')! <12:19-12:24>",
+                "error[unknown-macro]: Inline macro 'b' not found to insert! <13:6-13:10>"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_macro_spans() {
+        let ssb = Ssb::default().parse_owned(Cursor::new(MACRO_SCRIPT)).expect("Script should be valid!");
+        let (_, diagnostics) = SsbRender::from_with_options(ssb.clone(), &RenderOptions::new().diagnostics(true)).unwrap();
+        assert_eq!(
+            diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "error[invalid-tag]: Invalid event data from macro 'big' (offset 9)!\nInvalid bold 'maybe'! <3:6-3:9>",
+                "error[invalid-tag]: Invalid event data!\nInvalid italic '?'! <3:21-3:29>",
+                "error[invalid-tag]: Invalid event data from macro 'big' (offset 9)!\nInvalid bold 'maybe'! <3:30-3:36>"
+            ]
+        );
+        let err = SsbRender::try_from(ssb).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid event data from macro 'big' (offset 9)! <3:6>\nInvalid bold 'maybe'!");
+        assert_eq!((err.pos(), err.macro_origin()), (Some((3, 6)), Some(("big", 9))));
+    }
+
+    #[test]
+    fn test_unknown_tags() {
+        let ssb = Ssb::default().parse_owned(Cursor::new(UNKNOWN_TAGS_SCRIPT)).unwrap();
+        assert_eq!(
+            SsbRender::try_from(ssb.clone()).map_err(|err| err.to_string() ),
+            Err("Invalid event data from macro 'glowing' (offset 1)! <3:6>\nInvalid tag 'glow'!".to_owned())
+        );
+        let (ssb_render, warnings) = SsbRender::from_with_options(ssb, &RenderOptions::new().lenient(true)).unwrap();
+        let unknown = |name: &str, value: Option<&str>| EventObject::TagUnknown{name: name.into(), value: value.map(Into::into)};
+        assert_eq!(ssb_render.events[0].objects, vec![
+            unknown("glow", Some("5")),
+            EventObject::TagBold(true),
+            unknown("shadow", None),
+            EventObject::TagAnimate(Box::new(Animate {
+                time: Some((0, 500)),
+                formula: None,
+                tags: vec![unknown("glow", Some("10"))]
+            })),
+            EventObject::GeometryText("Hi".to_owned())
+        ]);
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "warning[unknown-tag]: Invalid event data from macro 'glowing' (offset 1)!\nUnknown tag 'glow' kept! <3:6-3:13>",
+                "warning[unknown-tag]: Invalid event data!\nUnknown tag 'shadow' kept! <3:23-3:29>",
+                "warning[unknown-tag]: Invalid event data!\nUnknown tag 'glow' kept! <3:31-3:54>"
+            ]
+        );
+        assert_eq!(EventObject::format_many(&ssb_render.events[0].objects).ok().as_deref(), Some("[glow=5][bold=y][shadow][animate=0,500,[glow=10]]Hi"));
+        // Combined with diagnostics
+        let ssb = Ssb::default().parse_owned(Cursor::new("#EVENTS\n0-1.0|||[glow=5;bold=maybe]Hi")).unwrap();
+        let (ssb_render, diagnostics) = SsbRender::from_with_options(ssb, &RenderOptions::new().lenient(true).diagnostics(true)).unwrap();
+        assert_eq!(ssb_render.events[0].objects, vec![unknown("glow", Some("5")), EventObject::GeometryText("Hi".to_owned())]);
+        assert_eq!(
+            diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "error[invalid-tag]: Invalid event data!\nInvalid bold 'maybe'! <1:16-1:26>",
+                "warning[unknown-tag]: Invalid event data!\nUnknown tag 'glow' kept! <1:9-1:15>"
+            ]
+        );
+        // Known tags out of context stay invalid
+        for (data, err) in &[
+            ("[animate=[animate=[size=40]]]", "Invalid event data! <1:9>\nInvalid animate '[animate=[size=40]]'!\nInvalid tag 'animate'!"),
+            ("[animate=0,1000,[mode=shape]]", "Invalid event data! <1:9>\nInvalid animate '0,1000,[mode=shape]'!\nInvalid tag 'mode'!")
+        ] {
+            let ssb = Ssb::default().parse_owned(Cursor::new(format!("#EVENTS\n0-1.0|||{}", data))).unwrap();
+            assert_eq!(SsbRender::from_with_options(ssb, &RenderOptions::new().lenient(true)).map(|_| () ).map_err(|err| err.to_string() ), Err((*err).to_owned()));
+        }
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,271 @@
+mod format_tests {
+    // Imports
+    use ssb_parser::{
+        objects::{
+            ssb_objects::*,
+            event_objects::EventObject
+        },
+        Ssb,
+        SsbRender
+    };
+    use std::{
+        convert::TryFrom,
+        io::Cursor
+    };
+
+
+    // Test data
+    const ASS: &str = r"[Script Info]
+; Script generated by Aegisub
+Title: Test
+Original Script: Youka
+ScriptType: v4.00+
+PlayResX: 1280
+PlayResY: 720
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,-1,0,0,0,100,100,0,0,1,2,0,2,10,10,10,1
+Style: Sign Big,Arial,40,&H800000FF,&H000000FF,&H00000000,&H00000000,0,0,0,0,150,100,0,0,1,2,2,7,0,0,0,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:04.50,Default,Alice,0,0,0,,{\pos(100,200)\fad(200,300)}Hello\N[world]
+Dialogue: 0,0:00:05.00,0:00:06.00,Sign Big,,0,0,0,,{\k20}Ka{\kf30}ra{\t(0,500,\frz30)\shad3}oke{\r}!
+Dialogue: 1,0:00:07.00,0:00:08.00,Default,,0,0,0,,{\clip(0,0,100,50)\p1}m 0 0 l 10 0 10 10{\p0}
+Comment: 0,0:00:00.00,0:00:01.00,Default,,0,0,0,,Note
+
+[Fonts]
+fontname: Youka_B0.ttf
+77^V;W%
+";
+
+    const SSB: &str = r"#INFO
+Title: Export
+Author: Youka
+
+#TARGET
+Width: 640
+Height: 480
+
+#MACROS
+red: [color=FF0000]
+
+#EVENTS
+0-2.500|red|Alice, Bob|[position=10,20;rotate-z=45;alpha=80]Hi\n{you}[k=250;animate=0,500,t^2,[scale-x=2;position=1,1]][mode=shape]m 0 0 l 10 0 10 10 c
+'id'|||Hidden
+1:00.000-1:02.000|||[blend=add]Bye
+
+#RESOURCES
+Font: Rabi-Ribi,bold,UmFiaS1SaWJp
+";
+
+    const SRT: &str = "1
+00:00:01,000 --> 00:00:04,500
+<b>Hello</b> [world]!
+<font color=\"#FF0000\">Red</font>
+
+2
+00:01:00,000 --> 00:01:02,000 X1:10 X2:20 Y1:10 Y2:20
+<i>Bye</i>
+
+3
+invalid
+";
+    const VTT: &str = "WEBVTT - Test
+
+NOTE A comment
+
+STYLE
+::cue {color: red}
+
+intro
+00:01.000 --> 00:04.500 align:start line:10% position:25%
+<v Alice><b>Hello</b> &amp; <c.yellow>welcome</c></v>
+
+00:00:05.000 --> 00:00:06.000 line:-1 size:50%
+Bottom
+";
+
+
+    // Tester
+    #[test]
+    fn test_ass_import() {
+        let (ssb, warnings) = Ssb::from_ass(Cursor::new(ASS)).unwrap_or_else(|exception| panic!("ASS conversion error: {}", exception) );
+        // Script info
+        assert_eq!(ssb.info_title, Some("Test".to_owned()));
+        assert_eq!(ssb.info_author, Some("Youka".to_owned()));
+        assert_eq!(ssb.info_custom.get("ScriptType"), Some(&"v4.00+".to_owned()));
+        assert_eq!((ssb.target_width, ssb.target_height), (Some(1280), Some(720)));
+        // Styles
+        assert_eq!(
+            ssb.macros.get("Default").map(String::as_str),
+            Some("[alignment=2;alpha=FF;bold=y;border=2;borderalpha=FF;bordercolor=000000;color=FFFFFF;font=Arial;italic=n;kcolor=FF0000;margin-bottom=10;margin-left=10;margin-right=10;margin-top=10;rotate-z=0;scale-x=1;scale-y=1;size=20;space-h=0;strikeout=n;underline=n]")
+        );
+        assert!(ssb.macros.get("Sign_Big").expect("Sign style expected!").contains("alpha=7F;"));
+        // Events
+        assert_eq!(
+            ssb.events.iter().map(|event| (&event.trigger, event.macro_name.as_deref(), event.note.as_deref(), event.data.as_str()) ).collect::<Vec<_>>(),
+            vec![
+                (&EventTrigger::Time((1000, 4500)), Some("Default"), Some("Alice"), "[position=100,200;alpha=0;borderalpha=0;animate=0,200,[alpha=FF;borderalpha=FF];animate=3200,3500,[alpha=0;borderalpha=0]]Hello\\n\\[world\\]"),
+                (&EventTrigger::Time((5000, 6000)), Some("Sign_Big"), None, "[k=200]Ka[k=300]ra[animate=0,500,[rotate-z=30]]oke[reset]${Sign_Big}!"),
+                (&EventTrigger::Time((7000, 8000)), Some("Default"), None, "[mask-clear;target=mask;mode=shape]m 0 0 l 100 0 100 50 0 50 c[target=frame;mask-mode=normal;mode=text;mode=shape]m 0 0 l 10 0 10 10[mode=text]")
+            ]
+        );
+        // Fonts
+        assert_eq!(ssb.fonts.get(&FontFace {family: "Youka".to_owned(), style: FontStyle::Bold}), Some(&ResourceData::from(b"Youka".to_vec())));
+        // Warnings
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "Unsupported style field 'shadow: 2' <11:7>",
+                "Replaced karaoke effect by plain karaoke 'kf30' <16:61>",
+                "Unsupported override tag 'shad3' <16:86>",
+                "Unsupported dialogue layer '1' <17:10>",
+                "Skipped comment event 'Comment: 0,0:00:00.00,0:00:01.00,Default,,0,0,0,,Note' <18:0>"
+            ]
+        );
+        // Converted data is valid for rendering
+        SsbRender::try_from(ssb).unwrap_or_else(|exception| panic!("SSB render data error: {}", exception) );
+    }
+
+    #[test]
+    fn test_ass_export() {
+        let ssb = Ssb::default().parse_owned(Cursor::new(SSB)).unwrap_or_else(|exception| panic!("SSB parsing error: {}", exception) );
+        let mut output = vec![];
+        let warnings = ssb.write_ass(&mut output).unwrap_or_else(|exception| panic!("ASS conversion error: {}", exception) );
+        assert_eq!(
+            String::from_utf8(output.clone()).expect("ASS output should be UTF-8!"),
+r"[Script Info]
+ScriptType: v4.00+
+Title: Export
+Original Script: Youka
+PlayResX: 640
+PlayResY: 480
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,0,0,2,0,0,0,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:00.00,0:00:02.50,Default,Alice; Bob,0,0,0,,{\1c&H0000FF&\pos(10,20)\frz45\1a&H7F&}Hi\N(you){\k25\t(0,500,2,\fscx200)\p1}m 0 0 l 10 0 l 10 10
+Dialogue: 0,0:01:00.00,0:01:02.00,Default,,0,0,0,,Bye
+
+[Fonts]
+fontname: Rabi-Ribi_B0.ttf
+5G&C;3V3;7*J
+"
+        );
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "Replaced separators in dialogue name 'Alice, Bob' <12:23>",
+                "Replaced braces in text 'Hi\n{you}' <12:23>",
+                "Unsupported animated tag 'TagPosition(Point3D { x: 1.0, y: 1.0, z: 0.0 })' <12:23>",
+                "Skipped id-triggered event 'id' <13:7>",
+                "Unsupported tag 'TagBlend(Add)' <14:20>"
+            ]
+        );
+        // Output is importable again
+        let (imported, _) = Ssb::from_ass(Cursor::new(output)).unwrap_or_else(|exception| panic!("ASS conversion error: {}", exception) );
+        assert_eq!(imported.fonts, ssb.fonts);
+        assert_eq!(imported.events.iter().map(|event| event.note.as_deref() ).collect::<Vec<_>>(), vec![Some("Alice; Bob"), None]);
+    }
+
+    #[test]
+    fn test_ass_round_trip() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#EVENTS\n0-2.0|||[animate=-1000,-500,[size=40]]C:\\\\Nope\\\\h[animate=-3000,-500,[size=50]]!")).unwrap_or_else(|exception| panic!("SSB parsing error: {}", exception) );
+        let mut output = vec![];
+        let warnings = ssb.write_ass(&mut output).unwrap_or_else(|exception| panic!("ASS conversion error: {}", exception) );
+        let output = String::from_utf8(output).expect("ASS output should be UTF-8!");
+        assert!(output.ends_with("Dialogue: 0,0:00:00.00,0:00:02.00,Default,,0,0,0,,{\\t(1000,1500,\\fs40)}C:\\{}Nope\\{}h!\n"));
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["Unsupported tag 'TagAnimate(Animate { time: Some((-3000, -500)), formula: None, tags: [TagSize(50.0)] })' <1:8>"]
+        );
+        // Times & backslashes survive import
+        let (imported, _) = Ssb::from_ass(Cursor::new(output)).unwrap_or_else(|exception| panic!("ASS conversion error: {}", exception) );
+        assert_eq!(
+            imported.events.iter().map(|event| event.data.as_str() ).collect::<Vec<_>>(),
+            vec!["[animate=1000,1500,[size=40]]C:\\\\Nope\\\\h!"]
+        );
+    }
+
+    #[test]
+    fn test_srt() {
+        // Import
+        let (ssb, warnings) = Ssb::from_srt(Cursor::new(SRT)).unwrap_or_else(|exception| panic!("SRT conversion error: {}", exception) );
+        assert_eq!(
+            ssb.events.iter().map(|event| (&event.trigger, event.data.as_str(), event.data_location) ).collect::<Vec<_>>(),
+            vec![
+                (&EventTrigger::Time((1000, 4500)), "[bold=y]Hello[bold=n] \\[world\\]!\\nRed", (2, 0)),
+                (&EventTrigger::Time((60000, 62000)), "[italic=y]Bye[italic=n]", (7, 0))
+            ]
+        );
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "Skipped unsupported markup '<font color=\"#FF0000\">' <2:0>",
+                "Skipped unsupported markup '</font>' <2:0>",
+                "Skipped cue coordinates 'X1:10 X2:20 Y1:10 Y2:20' <6:0>",
+                "Skipped block without timing '3' <9:0>"
+            ]
+        );
+        // Export
+        let mut output = vec![];
+        let warnings = ssb.write_srt(&mut output).unwrap_or_else(|exception| panic!("SRT conversion error: {}", exception) );
+        assert_eq!(
+            String::from_utf8(output).expect("SRT output should be UTF-8!"),
+            "1\n00:00:01,000 --> 00:00:04,500\n<b>Hello</b> [world]!\nRed\n\n2\n00:01:00,000 --> 00:01:02,000\n<i>Bye</i>\n\n"
+        );
+        assert!(warnings.is_empty());
+        // Macro references are plain text
+        let (ssb, _) = Ssb::from_srt(Cursor::new("1\n00:00:01,000 --> 00:00:02,000\nCost: ${price}\n")).unwrap_or_else(|exception| panic!("SRT conversion error: {}", exception) );
+        assert_eq!(ssb.events[0].data, "Cost: $\\{price}");
+        let ssb_render = SsbRender::try_from(ssb).unwrap_or_else(|exception| panic!("SSB render data error: {}", exception) );
+        assert_eq!(ssb_render.events[0].objects, vec![EventObject::GeometryText("Cost: ${price}".to_owned())]);
+    }
+
+    #[test]
+    fn test_vtt() {
+        // Import
+        let (ssb, warnings) = Ssb::from_vtt(Cursor::new(VTT)).unwrap_or_else(|exception| panic!("WebVTT conversion error: {}", exception) );
+        assert_eq!((ssb.target_width, ssb.target_height), (Some(1920), Some(1080)));
+        assert_eq!(
+            ssb.events.iter().map(|event| (&event.trigger, event.note.as_deref(), event.data.as_str()) ).collect::<Vec<_>>(),
+            vec![
+                (&EventTrigger::Time((1000, 4500)), Some("intro"), "[alignment=7;position=480,108][bold=y]Hello[bold=n] & welcome"),
+                (&EventTrigger::Time((5000, 6000)), None, "[alignment=2]Bottom")
+            ]
+        );
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "Skipped unsupported block 'STYLE' <4:0>",
+                "Skipped unsupported cue setting 'size:50%' <11:0>"
+            ]
+        );
+        // Export
+        let mut output = vec![];
+        let warnings = ssb.write_vtt(&mut output).unwrap_or_else(|exception| panic!("WebVTT conversion error: {}", exception) );
+        assert_eq!(
+            String::from_utf8(output).expect("WebVTT output should be UTF-8!"),
+            "WEBVTT\n\nintro\n00:00:01.000 --> 00:00:04.500 align:start position:25% line:10%\n<b>Hello</b> &amp; welcome\n\n00:00:05.000 --> 00:00:06.000 align:center\nBottom\n"
+        );
+        assert!(warnings.is_empty());
+        // Missing signature
+        assert_eq!(
+            Ssb::from_vtt(Cursor::new("00:01.000 --> 00:02.000\nText")).map_err(|err| err.to_string()),
+            Err("Missing WebVTT signature! <0:0>".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ass_import_errors() {
+        assert_eq!(
+            Ssb::from_ass(Cursor::new(b"[Events]\n\xFF".to_vec())).map_err(|err| err.to_string()),
+            Err("IO error!\nstream did not contain valid UTF-8".to_owned())
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+mod limit_tests {
+    // Imports
+    use ssb_parser::{
+        Ssb,
+        SsbRender,
+        RenderOptions,
+        SsbEventReader,
+        ParseLimits,
+        Limit,
+        LimitError
+    };
+    use std::{
+        convert::TryFrom,
+        error::Error,
+        io::Cursor
+    };
+
+
+    // Helpers
+    fn limit_of(err: &(dyn Error + 'static)) -> Option<LimitError> {
+        // Search error chain for limit
+        err.downcast_ref::<LimitError>().copied().or_else(|| err.source().and_then(limit_of) )
+    }
+
+
+    // Testers
+    #[test]
+    fn test_parse_limits() {
+        let limits = ParseLimits {max_events: 2, max_resource_bytes: 8, max_include_depth: 0, ..ParseLimits::default()};
+        // Events
+        let err = Ssb::default().parse_with_limits(Cursor::new("#EVENTS\n0-1.|||A\n0-1.|||B\n0-1.|||C"), &limits).map(|_| () ).unwrap_err();
+        assert_eq!(err.to_string(), "Too many events! <3:0>\nLimit of events exceeded (maximum: 2)!");
+        assert_eq!(limit_of(&err), Some(LimitError {limit: Limit::Events, max: 2}));
+        // Resources
+        let err = Ssb::default().parse_with_limits(Cursor::new("#RESOURCES\nFont: a,regular,dXNhZ2k=\nTexture: b,data,dXNhZ2k="), &limits).map(|_| () ).unwrap_err();
+        assert_eq!(limit_of(&err), Some(LimitError {limit: Limit::ResourceBytes, max: 8}));
+        let ssb = Ssb::default().parse_with_limits(Cursor::new("#RESOURCES\nTexture: b,data,dXNhZ2k=\nTexture: b,data,dXNhZ2k=\nTexture: b,url,usagi.png\nFont: a,regular,dXNhZ2k=\nFont: a,regular,dXNhZ2k="), &limits).map(|ssb| ssb.clone() ).unwrap();
+        let err = SsbEventReader::with_header(ssb, Cursor::new("#RESOURCES\nTexture: c,data,dXNhZ2k=")).limits(limits.clone()).next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "Too much resource data! <1:16>\nLimit of resource bytes exceeded (maximum: 8)!");
+        // Includes
+        let err = Ssb::default().parse_file_with_limits(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/include/episode.ssb"), &limits).map(|_| () ).unwrap_err();
+        assert_eq!(limit_of(&err), Some(LimitError {limit: Limit::IncludeDepth, max: 0}));
+    }
+
+    #[test]
+    fn test_render_limits() {
+        // Macro bomb doubling per level
+        let mut script = "#MACROS\nm0: lol\n".to_owned();
+        for level in 1..40 {
+            script.push_str(&format!("m{}: ${{m{1}}}${{m{1}}}\n", level, level - 1));
+        }
+        script.push_str("#EVENTS\n0-1.|m39||");
+        let ssb = Ssb::default().parse_owned(Cursor::new(script)).unwrap();
+        let err = SsbRender::try_from(ssb.clone()).unwrap_err();
+        assert_eq!(limit_of(&err), Some(LimitError {limit: Limit::EventLength, max: 1 << 20}));
+        let err = SsbRender::from_with_options(ssb, &RenderOptions::new().limits(ParseLimits {max_macro_depth: 8, ..ParseLimits::default()})).unwrap_err();
+        assert_eq!(limit_of(&err), Some(LimitError {limit: Limit::MacroDepth, max: 8}));
+        // Embedded resources (parsed without limits)
+        let limits = ParseLimits {max_resource_bytes: 8, ..ParseLimits::default()};
+        let ssb = Ssb::default().parse_owned(Cursor::new("#RESOURCES
+Font: a,regular,dXNhZ2k=
+Texture: b,data,dXNhZ2k=")).unwrap();
+        let err = SsbRender::from_with_options(ssb.clone(), &RenderOptions::new().limits(limits)).unwrap_err();
+        assert_eq!(err.to_string(), "Texture data for 'b' too large!\nLimit of resource bytes exceeded (maximum: 8)!");
+        assert_eq!(limit_of(&err), Some(LimitError {limit: Limit::ResourceBytes, max: 8}));
+        let err = SsbRender::from_with_options(ssb, &RenderOptions::new().limits(ParseLimits {max_resource_bytes: 4, ..ParseLimits::default()})).unwrap_err();
+        assert_eq!(err.to_string(), "Font data for 'a (Regular)' too large!\nLimit of resource bytes exceeded (maximum: 4)!");
+        // Tags nesting
+        let ssb = Ssb::default().parse_owned(Cursor::new(format!("#EVENTS\n0-1.|||[animate={}bold=y{}]", "[animate=".repeat(50), "]".repeat(50)))).unwrap();
+        let (_, diagnostics) = SsbRender::from_with_options(ssb, &RenderOptions::new().diagnostics(true)).unwrap();
+        assert_eq!(
+            diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["error[limit-exceeded]: Invalid event data!\nTags nested too deep!\nLimit of tag nesting exceeded (maximum: 32)! <1:304-1:522>"]
+        );
+    }
+}
\ No newline at end of file
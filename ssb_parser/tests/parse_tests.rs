@@ -5,7 +5,12 @@ mod parse_tests {
             ssb_objects::*,
             event_objects::*
         },
+        resolvers::{MemoryResolver,DataUriResolver,ResolverChain},
+        ParseLimits,
+        RenderOptions,
         Ssb,
+        SsbCst,
+        SsbEventReader,
         SsbRender
     };
     use std::{
@@ -50,9 +55,9 @@ Texture: Fancy,data,RmFuY3k=
         let event = ssb.events.get(0).expect("One event expected!");
         assert_eq!(event.trigger, EventTrigger::Time((0, 3600000)));
         assert_eq!(event.data, "[color=123abc]Hello world!");
-        assert_eq!(ssb.fonts.get(&FontFace {family: "bar".to_owned(), style: FontStyle::Bold}), Some(&vec![117, 115, 97, 103, 105]));
+        assert_eq!(ssb.fonts.get(&FontFace {family: "bar".to_owned(), style: FontStyle::Bold}), Some(&ResourceData::from(vec![117, 115, 97, 103, 105])));
         assert_eq!(ssb.fonts.get(&FontFace {family: "".to_owned(), style: FontStyle::Regular}), None);
-        assert_eq!(ssb.textures.get("Fancy"), Some(&TextureDataVariant::Raw(vec![70, 97, 110, 99, 121])));
+        assert_eq!(ssb.textures.get("Fancy"), Some(&TextureDataVariant::Raw(ResourceData::from(vec![70, 97, 110, 99, 121]))));
         assert_eq!(ssb.textures.get("Nobody"), None);
     }
 
@@ -76,6 +81,7 @@ Texture: Fancy,data,RmFuY3k=
                 target_height: Some(720),
                 target_depth: 800,
                 target_view: View::Orthogonal,
+                target_framerate: None,
                 events: vec![
                     EventRender {
                         trigger: EventTrigger::Time((2000,300000)),
@@ -416,7 +422,7 @@ Texture: Fancy,data,RmFuY3k=
                             })),
                             EventObject::TagAnimate(Box::new(Animate {
                                 time: Some((100, -2000)),
-                                formula: Some("t^2".to_owned()),
+                                formula: Some(Formula::new("t^2").unwrap()),
                                 tags: vec![
                                     EventObject::TagSize(
                                         42.0
@@ -451,7 +457,7 @@ Texture: Fancy,data,RmFuY3k=
                             family: "Rabi-Ribi".to_owned(),
                             style: FontStyle::Bold
                         },
-                        vec![82,97,98,105,45,82,105,98,105]
+                        ResourceData::from(vec![82,97,98,105,45,82,105,98,105])
                     );
                     fonts
                 },
@@ -459,11 +465,11 @@ Texture: Fancy,data,RmFuY3k=
                     let mut textures = HashMap::new();
                     textures.insert(
                         "Jitter".to_owned(),
-                        vec![74,105,116,116,101,114]
+                        ResourceData::from(vec![74,105,116,116,101,114])
                     );
                     textures.insert(
                         "cute".to_owned(),
-                        vec![137,80,78,71,13,10,26,10,0,0,0,13,73,72,68,82,0,0,0,32,0,0,0,32,8,0,0,0,0,86,17,37,40,0,0,0,9,112,72,89,115,0,0,46,35,0,0,46,35,1,120,165,63,118,0,0,0,7,116,73,77,69,7,227,4,29,2,32,49,204,41,26,248,0,0,1,179,73,68,65,84,56,203,109,146,177,75,35,81,16,135,127,183,173,164,58,176,72,103,101,113,157,205,221,177,112,141,87,137,254,1,183,149,54,146,226,42,173,172,181,213,38,77,46,214,86,41,18,177,58,60,9,108,35,110,17,228,194,181,65,81,34,132,40,172,176,194,237,30,249,174,120,111,95,54,217,76,53,243,155,111,222,204,27,70,0,131,143,59,17,115,22,237,124,30,0,8,160,33,169,145,22,211,105,67,82,3,192,147,164,68,82,237,248,159,156,101,199,53,43,75,0,103,146,164,230,244,129,166,36,233,204,181,184,49,117,87,121,254,202,196,55,14,120,241,36,73,213,7,147,127,88,150,36,121,47,14,224,192,148,124,207,0,178,154,137,14,152,2,145,29,238,23,192,79,27,68,5,32,219,50,154,159,64,242,201,248,91,89,1,160,107,171,206,225,220,186,93,138,64,22,24,117,53,73,86,141,23,100,51,0,125,91,23,134,214,233,51,11,80,55,122,205,126,161,206,60,240,186,169,130,109,190,150,0,122,69,160,71,9,136,252,34,224,71,243,64,91,115,214,158,5,186,42,217,204,30,70,203,101,160,58,194,29,140,46,70,101,96,120,33,119,48,217,138,22,216,74,230,90,60,106,161,61,154,22,127,81,106,149,253,65,75,106,13,246,109,152,74,169,132,255,173,125,237,6,143,165,216,125,233,186,19,248,136,219,163,74,254,230,238,239,83,233,180,191,155,199,149,163,91,4,196,225,246,162,17,182,195,216,237,225,222,203,15,102,60,206,15,198,187,47,44,42,222,179,234,198,120,188,97,221,189,56,7,222,162,195,37,173,91,249,107,158,95,215,210,97,244,6,226,71,85,65,231,105,114,89,41,246,175,92,78,158,58,129,170,77,52,57,105,221,1,48,172,231,131,200,171,15,1,184,107,157,76,222,49,45,123,254,211,235,133,250,178,182,246,225,253,84,252,15,108,126,214,79,66,138,234,197,0,0,0,0,73,69,78,68,174,66,96,130]
+                        ResourceData::from(vec![137,80,78,71,13,10,26,10,0,0,0,13,73,72,68,82,0,0,0,32,0,0,0,32,8,0,0,0,0,86,17,37,40,0,0,0,9,112,72,89,115,0,0,46,35,0,0,46,35,1,120,165,63,118,0,0,0,7,116,73,77,69,7,227,4,29,2,32,49,204,41,26,248,0,0,1,179,73,68,65,84,56,203,109,146,177,75,35,81,16,135,127,183,173,164,58,176,72,103,101,113,157,205,221,177,112,141,87,137,254,1,183,149,54,146,226,42,173,172,181,213,38,77,46,214,86,41,18,177,58,60,9,108,35,110,17,228,194,181,65,81,34,132,40,172,176,194,237,30,249,174,120,111,95,54,217,76,53,243,155,111,222,204,27,70,0,131,143,59,17,115,22,237,124,30,0,8,160,33,169,145,22,211,105,67,82,3,192,147,164,68,82,237,248,159,156,101,199,53,43,75,0,103,146,164,230,244,129,166,36,233,204,181,184,49,117,87,121,254,202,196,55,14,120,241,36,73,213,7,147,127,88,150,36,121,47,14,224,192,148,124,207,0,178,154,137,14,152,2,145,29,238,23,192,79,27,68,5,32,219,50,154,159,64,242,201,248,91,89,1,160,107,171,206,225,220,186,93,138,64,22,24,117,53,73,86,141,23,100,51,0,125,91,23,134,214,233,51,11,80,55,122,205,126,161,206,60,240,186,169,130,109,190,150,0,122,69,160,71,9,136,252,34,224,71,243,64,91,115,214,158,5,186,42,217,204,30,70,203,101,160,58,194,29,140,46,70,101,96,120,33,119,48,217,138,22,216,74,230,90,60,106,161,61,154,22,127,81,106,149,253,65,75,106,13,246,109,152,74,169,132,255,173,125,237,6,143,165,216,125,233,186,19,248,136,219,163,74,254,230,238,239,83,233,180,191,155,199,149,163,91,4,196,225,246,162,17,182,195,216,237,225,222,203,15,102,60,206,15,198,187,47,44,42,222,179,234,198,120,188,97,221,189,56,7,222,162,195,37,173,91,249,107,158,95,215,210,97,244,6,226,71,85,65,231,105,114,89,41,246,175,92,78,158,58,129,170,77,52,57,105,221,1,48,172,231,131,200,171,15,1,184,107,157,76,222,49,45,123,254,211,235,133,250,178,182,246,225,253,84,252,15,108,126,214,79,66,138,234,197,0,0,0,0,73,69,78,68,174,66,96,130])
                     );
                     textures
                 }
@@ -523,5 +529,324 @@ Texture: Fancy,data,RmFuY3k=
             Ssb::default().parse(Cursor::new("#RESOURCES\nTexture: Pikachu,data,INVALID_BASE64")).map_err(|err| err.to_string()),
             Err("Texture data not in base64 format! <1:22>".to_owned())
         );
+        assert_eq!(
+            Ssb::default().parse(Cursor::new("#RESOURCES\nFont: myfont,regular,SGl=")).map_err(|err| err.to_string()),
+            Err("Font data not in base64 format! <1:21>".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ssb_event_reader() {
+        let text = "#INFO\nTitle: Stream\n#RESOURCES\nFont: bar,bold,dXNhZ2k=\nINVALID_ENTRY\n#EVENTS\n0-1.|||A\n?|||\n'x'|foo||B\n#MACROS\nfoo: bar";
+        // Events one by one
+        let mut reader = SsbEventReader::new(Cursor::new(text));
+        assert_eq!(reader.next().map(|event| event.map_err(|err| err.to_string()) ), Some(Err("Invalid resources entry! <4:0>".to_owned())));
+        let event = reader.next().expect("Event expected!").expect("Event should be valid!");
+        assert_eq!((event.trigger, event.data, event.data_location), (EventTrigger::Time((0, 1000)), "A".to_owned(), (6, 7)));
+        assert_eq!(reader.header().info_title, Some("Stream".to_owned()));
+        assert!(reader.header().events.is_empty());
+        assert_eq!(reader.next().map(|event| event.map_err(|err| err.to_string()) ), Some(Err("Invalid trigger format! <7:0>".to_owned())));
+        let event = reader.next().expect("Event expected!").expect("Event should be valid!");
+        assert_eq!((event.trigger, event.macro_name), (EventTrigger::Id("x".to_owned()), Some("foo".to_owned())));
+        assert!(reader.next().is_none());
+        let header = reader.into_header();
+        assert_eq!(header.macros.get("foo"), Some(&"bar".to_owned()));
+        assert_eq!(header.fonts.len(), 1);
+        // Skipped resources
+        let mut reader = SsbEventReader::new(Cursor::new(text)).skip_resources(true);
+        assert_eq!(reader.by_ref().filter(Result::is_ok).count(), 2);
+        assert!(reader.header().fonts.is_empty());
+    }
+
+    #[test]
+    fn test_ssb_include() {
+        // Shared macros & resources
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/include/");
+        let mut ssb = Ssb::default();
+        ssb.parse_file(directory.to_owned() + "episode.ssb").expect("Episode with series include should be valid!");
+        assert_eq!(ssb.info_title, Some("Episode 1".to_owned()));
+        assert_eq!(ssb.macros.get("big"), Some(&"[size=40]".to_owned()));
+        assert_eq!(ssb.fonts.get(&FontFace {family: "series".to_owned(), style: FontStyle::Regular}), Some(&ResourceData::from(vec![117, 115, 97, 103, 105])));
+        assert_eq!(ssb.events.len(), 1);
+        // Cycle & errors
+        let error = Ssb::default().parse_file(directory.to_owned() + "cycle_a.ssb").map(|_| () ).expect_err("Cycle should be detected!").to_string();
+        assert!(error.contains("cycle_b.ssb'! <0:9>\n"), "{}", error);
+        assert!(error.ends_with("cycle_a.ssb' includes itself! <1:9>"), "{}", error);
+        let error = Ssb::default().parse_file(directory.to_owned() + "broken.ssb").map(|_| () ).expect_err("Invalid macro should fail!").to_string();
+        assert!(error.starts_with("Invalid file '") && error.ends_with("broken.ssb'!\nInvalid macros entry! <2:0>"), "{}", error);
+        let error = Ssb::default().parse_file(directory.to_owned() + "missing.ssb").map(|_| () ).expect_err("Missing include should fail!").to_string();
+        assert!(error.contains("Included file 'not/existing.ssb' not found! <1:9>"), "{}", error);
+        // Input other than files can't include
+        let text = "#INCLUDE /etc/passwd\n#EVENTS\n0-1.|||A";
+        let rejected = "Include not resolvable here, parse file instead! <0:0>";
+        assert_eq!(Ssb::default().parse(Cursor::new(text)).map(|_| () ).map_err(|err| err.to_string() ), Err(rejected.to_owned()));
+        let (ssb, diagnostics) = Ssb::default().parse_with_diagnostics(Cursor::new(text));
+        assert_eq!((ssb.events.len(), diagnostics.iter().map(|diagnostic| diagnostic.msg.as_str() ).collect::<Vec<_>>()), (1, vec!["Include not resolvable here, parse file instead!"]));
+        assert_eq!(SsbEventReader::new(Cursor::new(text)).next().map(|event| event.map(|_| () ).map_err(|err| err.to_string() ) ), Some(Err(rejected.to_owned())));
+        let cst = SsbCst::parse(Cursor::new(text)).unwrap();
+        assert_eq!(cst.to_string(), text);
+        assert_eq!(Ssb::try_from(&cst).map(|_| () ).map_err(|err| err.to_string() ), Err(rejected.to_owned()));
+    }
+
+    #[test]
+    fn test_ssb_macro_parameters() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#MACROS\ncolored: [color=${1}]${2}\nred: ${colored(ff0000,Red)}\n#EVENTS\n0-1.|red||!\n0-1.|||${colored(00ff00,Green)}\n0-1.|||${colored(0000ff)}")).unwrap();
+        let (ssb_render, diagnostics) = SsbRender::from_with_options(ssb.clone(), &RenderOptions::new().diagnostics(true)).unwrap();
+        assert_eq!(
+            ssb_render.events.iter().map(|event| event.objects.clone() ).collect::<Vec<_>>(),
+            vec![
+                vec![EventObject::TagColor(Color::Mono([255, 0, 0])), EventObject::GeometryText("Red!".to_owned())],
+                vec![EventObject::TagColor(Color::Mono([0, 255, 0])), EventObject::GeometryText("Green".to_owned())]
+            ]
+        );
+        assert_eq!(
+            diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["error[macro-arity-mismatch]: Inline macro 'colored' requires 2 arguments, got 1! <6:7-6:25>"]
+        );
+        assert_eq!(
+            SsbRender::try_from(ssb).map_err(|err| err.to_string()),
+            Err("Inline macro 'colored' requires 2 arguments, got 1! <6:7>".to_owned())
+        );
+        // Escaped separators
+        let ssb = Ssb::default().parse_owned(Cursor::new(r"#MACROS
+pos: [position=${1}]
+#EVENTS
+0-1.|||${pos(10\,20)}")).unwrap();
+        assert_eq!(
+            SsbRender::try_from(ssb).map(|ssb_render| ssb_render.events[0].objects.clone() ).map_err(|err| err.to_string() ),
+            Ok(vec![EventObject::TagPosition(Point3D {x: 10.0, y: 20.0, z: 0.0})])
+        );
+    }
+
+    #[test]
+    fn test_ssb_unlocated_macro() {
+        let mut ssb = Ssb::default();
+        ssb.macros.insert("m".to_owned(), "[bold=maybe]".to_owned());
+        ssb.events.push(Event {
+            trigger: EventTrigger::Time((0, 1000)),
+            macro_name: Some("m".to_owned()),
+            note: None,
+            data: "Text".to_owned(),
+            data_location: (0, 0)
+        });
+        assert_eq!(
+            SsbRender::try_from(ssb.clone()).map_err(|err| err.to_string()),
+            Err("Invalid event data from macro 'm' (offset 1)! <0:0>\nInvalid bold 'maybe'!".to_owned())
+        );
+        ssb.macros.insert("m".to_owned(), "[bold=y]".to_owned());
+        assert_eq!(SsbRender::try_from(ssb).unwrap().events[0].objects, vec![EventObject::TagBold(true), EventObject::GeometryText("Text".to_owned())]);
+    }
+
+    #[test]
+    fn test_ssb_macro_escape() {
+        // Escaped brace breaks macro reference and stays as plain brace
+        let ssb = Ssb::default().parse_owned(Cursor::new(r"#MACROS
+price: 5
+#EVENTS
+0-1.|||${price} $\{price} \\{ \{")).unwrap();
+        assert_eq!(
+            SsbRender::try_from(ssb).map(|ssb_render| ssb_render.events[0].objects.clone() ).map_err(|err| err.to_string() ),
+            Ok(vec![EventObject::GeometryText(r"5 ${price} \{ {".to_owned())])
+        );
+    }
+
+    #[test]
+    fn test_ssb_frames() {
+        let script = "#TARGET\nDepth: 1000\nView: perspective\nFramerate: 30000/1001\n\n#EVENTS\n24f-48f|||A\n00:00:01:00-00:01:00;02|||B\n\n";
+        let ssb = Ssb::default().parse_owned(Cursor::new(script)).unwrap();
+        let framerate = Framerate::new(30000, 1001).unwrap();
+        assert_eq!(ssb.target_framerate, Some(framerate));
+        assert_eq!(ssb.events[0].trigger, EventTrigger::Frame((24, 48)));
+        assert_eq!(ssb.events[1].trigger, EventTrigger::Timecode((
+            Timecode {hours: 0, minutes: 0, seconds: 1, frames: 0, drop_frame: false},
+            Timecode {hours: 0, minutes: 1, seconds: 0, frames: 2, drop_frame: true}
+        )));
+        assert_eq!(ssb.events[1].trigger.time_range(Some(&framerate)), Some((1001, 60060)));
+        assert_eq!(ssb.to_string(), script);
+        let mut srt = vec![];
+        let warnings = SsbRender::try_from(Ssb {target_framerate: None, ..ssb}).unwrap().write_srt(&mut srt).unwrap();
+        assert!(srt.is_empty());
+        assert_eq!(warnings.len(), 2);
+        // Errors
+        assert_eq!(
+            Ssb::default().parse(Cursor::new("#TARGET\nFramerate: 0")).map_err(|err| err.to_string()),
+            Err("Invalid target framerate value! <1:11>".to_owned())
+        );
+        assert_eq!(
+            Ssb::default().parse(Cursor::new("#EVENTS\n24f-48|||")).map_err(|err| err.to_string()),
+            Err("End frame invalid! <1:4>".to_owned())
+        );
+        assert_eq!(
+            Ssb::default().parse(Cursor::new("#EVENTS\n00:00:02:00-00:00:01:00|||")).map_err(|err| err.to_string()),
+            Err("Start timecode greater than end timecode! <1:0>".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ssb_formulas() {
+        let ssb_render = SsbRender::try_from(Ssb::default().parse_owned(Cursor::new("#EVENTS\n0-1.0|||[animate=sqrt(t),[bold=y]]A")).unwrap()).unwrap();
+        if let Some(EventObject::TagAnimate(animate)) = ssb_render.events[0].objects.first() {
+            assert_eq!(animate.formula.as_ref().map(|formula| formula.evaluate(0.25) ), Some(0.5));
+        } else {
+            panic!("Animate tag expected!");
+        }
+        assert_eq!(
+            SsbRender::try_from(Ssb::default().parse_owned(Cursor::new("#EVENTS\n0-1.0|||A[animate=t*speed,[bold=y]]")).unwrap()).map_err(|err| err.to_string()),
+            Err("Invalid event data! <1:10>\nInvalid animate 't*speed,[bold=y]'!\nInvalid formula 't*speed'!\nEvaluation error: unknown variable `speed`.".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ssb_lazy_resources() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#RESOURCES\nFont: bar,regular,dXNhZ2k=\nTexture: Fancy,data,RmFuY3k=")).unwrap();
+        let ssb_render = SsbRender::try_from(ssb).unwrap();
+        let ssb_render2 = ssb_render.clone();
+        let (texture, texture2) = (&ssb_render.textures["Fancy"], &ssb_render2.textures["Fancy"]);
+        assert!(texture.is_shared_with(texture2));
+        assert!(!texture2.is_decoded());
+        assert_eq!(texture.data().ok(), Some(&b"Fancy"[..]));
+        assert!(texture2.is_decoded());
+        assert!(!ssb_render2.fonts.values().any(ResourceData::is_decoded));
+    }
+
+    #[test]
+    fn test_ssb_resolver() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#RESOURCES\nTexture: a,url,memory/a.png\nTexture: b,url,data:;base64,AQI=")).unwrap();
+        let mut memory = MemoryResolver::new();
+        memory.insert("memory/a.png", vec![0, 42]);
+        let resolver = ResolverChain::new(vec![Box::new(memory), Box::new(DataUriResolver)]);
+        let ssb_render = SsbRender::from_with_options(ssb.clone(), &RenderOptions::new().resolver(&resolver)).unwrap().0;
+        assert_eq!(ssb_render.textures.get("a"), Some(&ResourceData::from(vec![0, 42])));
+        assert_eq!(ssb_render.textures.get("b"), Some(&ResourceData::from(vec![1, 2])));
+        assert!(SsbRender::from_with_options(ssb.clone(), &RenderOptions::new().resolver(&DataUriResolver)).map(|_| () ).map_err(|err| err.to_string() ).unwrap_err().starts_with("Texture data for 'a' not loadable from url 'memory/a.png'!\n"));
+        assert!(SsbRender::from_with_options(ssb, &RenderOptions::new().resolver(&resolver).limits(ParseLimits {max_resource_bytes: 3, ..ParseLimits::default()})).is_err());
+    }
+
+    #[test]
+    fn test_event_object_strings() {
+        let objects = vec![
+            EventObject::TagFont("Fira [Sans]".to_owned()),
+            EventObject::TagSize(20.5),
+            EventObject::TagBold(true),
+            EventObject::TagItalic(false),
+            EventObject::TagUnderline(true),
+            EventObject::TagStrikeout(false),
+            EventObject::TagPosition(Point3D {x: -1.5, y: 2.0, z: 0.0}),
+            EventObject::TagAlignment(Alignment::Numpad(Numpad::TopRight)),
+            EventObject::TagAlignment(Alignment::Offset(Point2D {x: 1.0, y: -2.0})),
+            EventObject::TagMargin(Margin::All(1.0, 2.0, 3.0, 4.0)),
+            EventObject::TagMargin(Margin::Top(1.0)),
+            EventObject::TagMargin(Margin::Right(2.0)),
+            EventObject::TagMargin(Margin::Bottom(3.0)),
+            EventObject::TagMargin(Margin::Left(4.0)),
+            EventObject::TagWrapStyle(WrapStyle::NoWrap),
+            EventObject::TagDirection(Direction::BottomToTop),
+            EventObject::TagSpace(Space::All(1.0, 2.0)),
+            EventObject::TagSpace(Space::Horizontal(3.0)),
+            EventObject::TagSpace(Space::Vertical(4.0)),
+            EventObject::TagRotate(Rotate::X(45.0)),
+            EventObject::TagRotate(Rotate::Y(-45.0)),
+            EventObject::TagRotate(Rotate::Z(0.1)),
+            EventObject::TagScale(Scale::All(1.0, 2.0, 3.0)),
+            EventObject::TagScale(Scale::X(1.5)),
+            EventObject::TagScale(Scale::Y(2.5)),
+            EventObject::TagScale(Scale::Z(3.5)),
+            EventObject::TagTranslate(Translate::All(1.0, 2.0, 3.0)),
+            EventObject::TagTranslate(Translate::X(1.5)),
+            EventObject::TagTranslate(Translate::Y(2.5)),
+            EventObject::TagTranslate(Translate::Z(3.5)),
+            EventObject::TagShear(Shear::All(0.5, 1.0)),
+            EventObject::TagShear(Shear::X(0.5)),
+            EventObject::TagShear(Shear::Y(1.0)),
+            EventObject::TagMatrix(Box::new([1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0])),
+            EventObject::TagReset,
+            EventObject::TagBorder(Border::All(1.0, 2.0)),
+            EventObject::TagBorder(Border::Horizontal(3.0)),
+            EventObject::TagBorder(Border::Vertical(4.0)),
+            EventObject::TagJoin(Join::Bevel),
+            EventObject::TagCap(Cap::Square),
+            EventObject::TagTexture("Fancy".to_owned()),
+            EventObject::TagTexture("".to_owned()),
+            EventObject::TagTexFill {x0: 0.0, y0: 0.5, x1: 1.0, y1: 1.5, wrap: TextureWrapping::Mirror},
+            EventObject::TagColor(Color::Mono([255, 0, 128])),
+            EventObject::TagColor(Color::Linear([[1, 2, 3], [4, 5, 6]])),
+            EventObject::TagBorderColor(Color::CornersWithStop([[0;3], [1;3], [2;3], [3;3], [4;3]])),
+            EventObject::TagAlpha(Alpha::Mono(128)),
+            EventObject::TagBorderAlpha(Alpha::Corners([0, 1, 2, 255])),
+            EventObject::TagBlur(Blur::All(1.0, 2.0)),
+            EventObject::TagBlur(Blur::Horizontal(3.0)),
+            EventObject::TagBlur(Blur::Vertical(4.0)),
+            EventObject::TagBlend(Blend::Difference),
+            EventObject::TagTarget(Target::Mask),
+            EventObject::TagMaskMode(MaskMode::Invert),
+            EventObject::TagMaskClear,
+            EventObject::TagAnimate(Box::new(Animate {
+                time: Some((100, -100)),
+                formula: Some(Formula::new("max(t,0.5)").unwrap()),
+                tags: vec![EventObject::TagSize(40.0), EventObject::TagColor(Color::Mono([0, 0, 255]))]
+            })),
+            EventObject::TagAnimate(Box::new(Animate {
+                time: None,
+                formula: None,
+                tags: vec![EventObject::TagBold(true)]
+            })),
+            EventObject::TagKaraoke(120),
+            EventObject::TagKaraokeSet(-50),
+            EventObject::TagKaraokeColor([0, 255, 0]),
+            EventObject::GeometryText("Line 1\nLine [2] \\o/".to_owned()),
+            EventObject::GeometryPoints(vec![Point2D {x: 0.0, y: 1.0}, Point2D {x: -2.5, y: 3.0}]),
+            EventObject::GeometryShape(vec![
+                ShapeSegment::MoveTo(Point2D {x: 0.0, y: 0.0}),
+                ShapeSegment::LineTo(Point2D {x: 10.0, y: 0.0}),
+                ShapeSegment::LineTo(Point2D {x: 10.0, y: 10.0}),
+                ShapeSegment::CurveTo(Point2D {x: 5.0, y: 15.0}, Point2D {x: 0.0, y: 15.0}, Point2D {x: 0.0, y: 10.0}),
+                ShapeSegment::ArcBy(Point2D {x: 0.0, y: 5.0}, 90.0),
+                ShapeSegment::Close,
+                ShapeSegment::MoveTo(Point2D {x: 20.0, y: 0.0}),
+                ShapeSegment::LineTo(Point2D {x: 30.0, y: 0.0})
+            ])
+        ];
+        for object in &objects {
+            assert_eq!(object.to_string().parse::<EventObject>().as_ref().ok(), Some(object), "{}", object);
+        }
+        assert_eq!(EventObject::TagAnimate(Box::new(Animate {
+            time: Some((100, -100)),
+            formula: Some(Formula::new("t^2").unwrap()),
+            tags: vec![EventObject::TagSize(40.0), EventObject::TagBold(false)]
+        })).to_string(), "[animate=100,-100,t^2,[size=40;bold=n]]");
+        assert_eq!(objects[objects.len() - 1].to_string(), "[mode=shape]m 0 0 l 10 0 10 10 b 5 15 0 15 0 10 a 0 5 90 c m 20 0 l 30 0");
+        // Sequences
+        let mut sequence = objects.clone();
+        sequence.push(EventObject::GeometryText("A".to_owned()));
+        sequence.push(EventObject::GeometryText("B".to_owned()));
+        let data = EventObject::format_many(&sequence).unwrap();
+        assert!(data.ends_with("[mode=text]A[mode=text]B"), "{}", data);
+        assert_eq!(EventObject::parse_many(&data).map_err(|err| err.to_string() ), Ok(sequence));
+        // Escaped & unrepresentable values
+        for object in &[EventObject::TagFont("[Arial]".to_owned()), EventObject::TagTexture("a]b\\c".to_owned())] {
+            assert_eq!(EventObject::format_many(&[object.clone()]).ok().and_then(|data| EventObject::parse_many(&data).ok() ), Some(vec![object.clone()]));
+        }
+        let unknown_data = EventObject::format_many(&[EventObject::TagUnknown {name: "glow]".into(), value: Some("[5]".into())}]).unwrap();
+        assert_eq!(
+            SsbRender::from_with_options(Ssb::default().parse_owned(Cursor::new(format!("#EVENTS\n0-1.0|||{}", unknown_data))).unwrap(), &RenderOptions::new().lenient(true)).map(|(ssb_render, _)| ssb_render.events[0].objects.clone() ).ok(),
+            Some(vec![EventObject::TagUnknown {name: "glow]".into(), value: Some("[5]".into())}])
+        );
+        for (object, tag) in vec![
+            (EventObject::TagFont("a;b".to_owned()), "font=a;b"),
+            (EventObject::TagTexture(";".to_owned()), "texture=;"),
+            (EventObject::TagUnknown {name: "a=b".into(), value: None}, "a=b"),
+            (EventObject::TagUnknown {name: "".into(), value: None}, ""),
+            (EventObject::TagUnknown {name: "glow".into(), value: Some("1;2".into())}, "glow=1;2"),
+            (EventObject::TagAnimate(Box::new(Animate {time: None, formula: None, tags: vec![EventObject::TagFont("a;b".to_owned())]})), "font=a;b")
+        ] {
+            assert_eq!(EventObject::format_many(&[object]).map_err(|err| err.to_string() ), Err(format!("Tag '{}' not representable!", tag)));
+        }
+        // Writing is lossy but infallible
+        assert_eq!(EventObject::TagFont("a;b".to_owned()).to_string(), "[font=a;b]");
+        assert_eq!(EventObject::TagUnknown {name: "".into(), value: Some("x".into())}.to_string(), "[=x]");
+        // Errors
+        assert_eq!("[bold=y][italic=y]".parse::<EventObject>().map_err(|err| err.to_string() ), Err("Expected one event object, found 2!".to_owned()));
+        assert_eq!("[mode=text]".parse::<EventObject>().map_err(|err| err.to_string() ), Err("Expected one event object, found 0!".to_owned()));
+        assert_eq!("A[size=big]".parse::<EventObject>().map_err(|err| err.to_string() ), Err("Invalid event data! <0:2>\nInvalid size 'big'!".to_owned()));
     }
-}
\ No newline at end of file
+}
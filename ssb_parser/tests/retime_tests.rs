@@ -0,0 +1,88 @@
+mod retime_tests {
+    // Imports
+    use ssb_parser::{
+        objects::{
+            ssb_objects::EventTrigger,
+            event_objects::EventObject
+        },
+        retiming::TimeMap,
+        Ssb,
+        SsbRender
+    };
+    use std::{
+        convert::TryFrom,
+        io::Cursor
+    };
+
+
+    // Testers
+    #[test]
+    fn test_retime_ssb() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#MACROS\nred: [color=ff0000]\n#EVENTS\n1.-3.|red||[k=500;kset=1000;k=500]A[animate=500,1000,[alpha=0]]B\n'title'|||C")).unwrap();
+        // Shift
+        let mut shifted = ssb.clone();
+        shifted.retime(&TimeMap::shift(500)).unwrap();
+        assert_eq!(shifted.events[0].trigger, EventTrigger::Time((1500, 3500)));
+        assert_eq!(shifted.events[0].data, ssb.events[0].data);
+        assert_eq!(shifted.events[1], ssb.events[1]);
+        // Shift with clamped start
+        let mut clamped = ssb.clone();
+        clamped.retime(&TimeMap::shift(-1500)).unwrap();
+        assert_eq!(clamped.events[0].trigger, EventTrigger::Time((0, 1500)));
+        assert_eq!(clamped.events[0].data, "[k=500;kset=500;k=500]A[animate=0,500,[alpha=0]]B");
+        let mut ssb_animate = Ssb::default().parse_owned(Cursor::new("#EVENTS\n1.-3.|||[animate=1000,1500,[alpha=0]]A")).unwrap();
+        ssb_animate.retime(&TimeMap::shift(-1500)).unwrap();
+        assert_eq!(ssb_animate.events[0].data, "[animate=500,1000,[alpha=0]]A");
+        assert_eq!(
+            ssb.clone().retime(&TimeMap::shift(-1800)).map_err(|err| err.to_string() ),
+            Err("Time '500,1000,[alpha=0]' not retimable! <3:44>".to_owned())
+        );
+        // Stretch
+        let mut stretched = ssb.clone();
+        stretched.retime(&TimeMap::stretch(2.0).unwrap()).unwrap();
+        assert_eq!(stretched.events[0].trigger, EventTrigger::Time((2000, 6000)));
+        assert_eq!(stretched.events[0].data, "[k=1000;kset=2000;k=1000]A[animate=1000,2000,[alpha=0]]B");
+        // Non-linear map with animation counting from event end
+        let mut mapped = Ssb::default().parse_owned(Cursor::new("#EVENTS\n1.-3.|||[animate=-500,0,[alpha=0]]A")).unwrap();
+        mapped.retime(&TimeMap::piecewise(&[(0, 0), (2000, 2000), (3000, 5000)]).unwrap()).unwrap();
+        assert_eq!(mapped.events[0].trigger, EventTrigger::Time((1000, 5000)));
+        assert_eq!(mapped.events[0].data, "[animate=-1500,0,[alpha=0]]A");
+        // Errors leave data unchanged
+        let mut failed = ssb.clone();
+        assert_eq!(failed.retime(&TimeMap::shift(-3000)).map_err(|err| err.to_string() ), Err("Event 1000-3000 ends before zero by retiming! <3:0>".to_owned()));
+        assert_eq!(failed, ssb);
+        failed.macros.insert("karaoke".to_owned(), "[k=100]".to_owned());
+        assert_eq!(failed.retime(&TimeMap::stretch(2.0).unwrap()).map_err(|err| err.to_string() ), Err("Macro 'karaoke' contains relative times, not retimable!".to_owned()));
+        failed.events[0].data = "[k=${duration}]".to_owned();
+        assert!(failed.retime(&TimeMap::shift(100)).is_ok());
+        failed.events[0].data = "${karaoke}".to_owned();
+        assert_eq!(failed.retime(&TimeMap::shift(-1500)).map_err(|err| err.to_string() ), Err("Macro 'karaoke' contains relative times, not retimable! <3:0>".to_owned()));
+        failed.events[0].data = "[k=${duration}]".to_owned();
+        failed.macros.clear();
+        assert_eq!(failed.retime(&TimeMap::stretch(2.0).unwrap()).map_err(|err| err.to_string() ), Err("Time '${duration}' not retimable! <3:14>".to_owned()));
+    }
+
+    #[test]
+    fn test_retime_ssb_render() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#EVENTS\n1.-3.|||[kset=500;k=1000;animate=-500,500,[alpha=0]]A")).unwrap();
+        let mut ssb_render = SsbRender::try_from(ssb).unwrap();
+        // Event start gets clamped, relative times keep mapped times
+        ssb_render.retime(&TimeMap::piecewise(&[(1000, 0), (2000, 2000)]).unwrap()).unwrap();
+        assert_eq!(ssb_render.events[0].trigger, EventTrigger::Time((0, 3000)));
+        assert_eq!(ssb_render.events[0].objects[0], EventObject::TagKaraokeSet(1000));
+        assert_eq!(ssb_render.events[0].objects[1], EventObject::TagKaraoke(1500));
+        match &ssb_render.events[0].objects[2] {
+            EventObject::TagAnimate(animate) => assert_eq!(animate.time, Some((-500, 1000))),
+            object => panic!("Unexpected object: {:?}", object)
+        }
+        assert!(ssb_render.clone().retime(&TimeMap::shift(-3000)).is_err());
+        // Non-linear map with animation counting from event end
+        let ssb = Ssb::default().parse_owned(Cursor::new("#EVENTS\n1.-3.|||[animate=-500,0,[alpha=0]]A")).unwrap();
+        let mut ssb_render = SsbRender::try_from(ssb).unwrap();
+        ssb_render.retime(&TimeMap::piecewise(&[(0, 0), (2000, 2000), (3000, 5000)]).unwrap()).unwrap();
+        match &ssb_render.events[0].objects[0] {
+            EventObject::TagAnimate(animate) => assert_eq!(animate.time, Some((-1500, 0))),
+            object => panic!("Unexpected object: {:?}", object)
+        }
+    }
+}
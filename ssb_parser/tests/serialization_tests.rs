@@ -23,7 +23,7 @@ mod serialization_tests {
         let ssb_json = serde_json::to_string(&ssb_default).expect("Ssb serialization must work!");
         assert_eq!(
             ssb_json,
-            r#"{"info_title":null,"info_author":null,"info_description":null,"info_version":null,"info_custom":{},"target_width":null,"target_height":null,"target_depth":1000,"target_view":"Perspective","macros":{},"events":[],"fonts":{},"textures":{}}"#.to_owned()
+            r#"{"info_title":null,"info_author":null,"info_description":null,"info_version":null,"info_custom":{},"target_width":null,"target_height":null,"target_depth":1000,"target_view":"Perspective","target_framerate":null,"macros":{},"events":[],"fonts":{},"textures":{}}"#.to_owned()
         );
         // Deserialize
         assert_eq!(
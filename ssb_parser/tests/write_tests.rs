@@ -0,0 +1,95 @@
+mod write_tests {
+    // Imports
+    use ssb_parser::{
+        objects::ssb_objects::{Event,EventTrigger,TextureDataVariant,ResourceData},
+        Ssb
+    };
+    use std::{
+        fs::File,
+        io::{BufReader, Cursor}
+    };
+
+
+    // Helpers
+    fn without_locations(mut ssb: Ssb) -> Ssb {
+        ssb.events.iter_mut().for_each(|event| event.data_location = (0, 0) );
+        ssb
+    }
+
+
+    // Tester
+    #[test]
+    fn test_write_simple() {
+        let mut ssb = Ssb {
+            info_author: Some("Youka".to_owned()),
+            target_width: Some(123),
+            ..Ssb::default()
+        };
+        ssb.info_custom.insert("Foo".to_owned(), "Bar".to_owned());
+        ssb.macros.insert("foo".to_owned(), "[bold=y]".to_owned());
+        ssb.events.push(Event {
+            trigger: EventTrigger::Time((2000, 3_723_004)),
+            macro_name: Some("foo".to_owned()),
+            note: None,
+            data: "Hello\\nworld|!".to_owned(),
+            data_location: (0, 0)
+        });
+        ssb.textures.insert("Fancy".to_owned(), TextureDataVariant::Raw(ResourceData::from(b"Fancy".to_vec())));
+        assert_eq!(
+            ssb.to_string(),
+"#INFO
+Author: Youka
+Foo: Bar
+
+#TARGET
+Width: 123
+Depth: 1000
+View: perspective
+
+#MACROS
+foo: [bold=y]
+
+#EVENTS
+0:02.000-1:02:03.004|foo||Hello\\nworld|!
+
+#RESOURCES
+Texture: Fancy,data,RmFuY3k=
+"
+        );
+    }
+
+    #[test]
+    fn test_write_roundtrip() {
+        // Parse original
+        let ssb = Ssb::default().parse_owned(
+            BufReader::new(
+                File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.ssb"))
+                .expect("Test SSB file must exist!")
+            )
+        ).unwrap_or_else(|exception| panic!("SSB parsing error: {}", exception) );
+        // Write & parse again
+        let ssb_text = ssb.to_string();
+        let ssb_reparsed = Ssb::default().parse_owned(Cursor::new(&ssb_text)).unwrap_or_else(|exception| panic!("SSB reparsing error: {}", exception) );
+        assert_eq!(ssb_reparsed.to_string(), ssb_text);
+        assert_eq!(without_locations(ssb_reparsed), without_locations(ssb));
+    }
+
+    #[test]
+    fn test_write_errors() {
+        let mut ssb = Ssb::default();
+        ssb.macros.insert("a: b".to_owned(), "".to_owned());
+        assert_eq!(ssb.write(vec![]).map_err(|err| err.to_string()), Err("Macro name 'a: b' not writable!".to_owned()));
+        let mut ssb = Ssb::default();
+        ssb.events.push(Event {
+            trigger: EventTrigger::Time((1, 0)),
+            macro_name: None,
+            note: None,
+            data: "".to_owned(),
+            data_location: (0, 0)
+        });
+        assert_eq!(ssb.write(vec![]).map_err(|err| err.to_string()), Err("Event start time greater than end time!".to_owned()));
+        ssb.events[0].trigger = EventTrigger::Id("x".to_owned());
+        ssb.events[0].data = "multiple\nlines".to_owned();
+        assert_eq!(ssb.write(vec![]).map_err(|err| err.to_string()), Err("Event data 'multiple\\nlines' contains line break or separator!".to_owned()));
+    }
+}
\ No newline at end of file
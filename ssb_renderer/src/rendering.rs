@@ -1,51 +1,134 @@
 // Imports
 use ssb_parser::{
     SsbRender,
-    objects::ssb_objects::EventTrigger
+    index::EventIndex,
+    style::{StyleResolver,StyleState,Geometry},
+    tags::CustomTag,
+    objects::ssb_objects::Framerate
 };
 use puny2d::raster::image::ImageView;
 use crate::error::RenderingError;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc
+};
 
 
 /// Condition to trigger rendering on specific image.
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RenderTrigger<'a> {
     Id(&'a str),
-    Time(u32)
+    Time(u32),
+    /// Frame number by framerate of video.
+    Frame(u32, Framerate)
+}
+
+/// Hook to draw custom tags (parsed by a [`TagRegistry`](ssb_parser::tags::TagRegistry)) on images.
+pub trait TagPainter: Send + Sync {
+    /// Draw tag with resolved style at its place in event, inside animations with their progress (0 to 1, formula applied), otherwise 1.
+    fn paint(&self, tag: &dyn CustomTag, progress: f64, style: &StyleState, img: &mut ImageView<'_>) -> Result<(), RenderingError>;
+}
+impl<F> TagPainter for F
+    where F: Fn(&dyn CustomTag, f64, &StyleState, &mut ImageView<'_>) -> Result<(), RenderingError> + Send + Sync {
+    fn paint(&self, tag: &dyn CustomTag, progress: f64, style: &StyleState, img: &mut ImageView<'_>) -> Result<(), RenderingError> {
+        self(tag, progress, style, img)
+    }
+}
+// Painters by tag name, equal by identity
+#[derive(Default, Clone)]
+struct TagPainters(HashMap<String, Arc<dyn TagPainter>>);
+impl fmt::Debug for TagPainters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names = self.0.keys().collect::<Vec<_>>();
+        names.sort_unstable();
+        f.debug_tuple("TagPainters").field(&names).finish()
+    }
+}
+impl PartialEq for TagPainters {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().all(|(name, painter)| other.0.get(name).map_or(false, |other_painter| Arc::ptr_eq(painter, other_painter)) )
+    }
 }
 
 /// Renderer for ssb data on images.
 #[derive(Debug, PartialEq, Clone)]
 pub struct SsbRenderer {
-    data: SsbRender
+    data: SsbRender,
+    index: EventIndex,
+    frame_indices: HashMap<Framerate, EventIndex>,
+    painters: TagPainters
 }
 impl SsbRenderer {
     /// Consumes ssb data as rendering blueprint.
     pub fn new(data: SsbRender) -> Self {
         Self {
-            data
+            index: EventIndex::with_framerate(&data.events, data.target_framerate.as_ref()),
+            frame_indices: HashMap::default(),
+            data,
+            painters: TagPainters::default()
         }
     }
+    /// Draw custom tags of name by painter (replaces previous one).
+    pub fn register_painter<P>(&mut self, name: &str, painter: P) -> &mut Self
+        where P: TagPainter + 'static {
+        self.painters.0.insert(name.to_owned(), Arc::new(painter));
+        self
+    }
+    /// Lookup of events by trigger in rendering blueprint (frame-triggered ones by target framerate of script).
+    pub fn index(&self) -> &EventIndex {
+        &self.index
+    }
     /// Renders on image by ssb matching trigger.
     pub fn render<'data>(&mut self, mut img: ImageView<'data>, trigger: RenderTrigger) -> Result<ImageView<'data>,RenderingError> {
-        // Find match of render and ssb trigger
-        for event in &self.data.events {
-            if match (&event.trigger, trigger) {
-                (EventTrigger::Id(event_id), RenderTrigger::Id(render_id)) => event_id == render_id,
-                (EventTrigger::Time((start_ms, end_ms)), RenderTrigger::Time(current_ms)) => (start_ms..end_ms).contains(&&current_ms),
-                _ => false
-            } {
-
-
-                // TODO: whole rendering process
-                for row in img.plane_rows_mut(0).expect("One plane should always exist!") {
-                    for sample in row {
-                        *sample = std::u8::MAX - *sample;
+        // Resolve frame-triggered events by framerate of video, otherwise by framerate of script (indices cached per framerate)
+        let framerate = match trigger {
+            RenderTrigger::Frame(_, framerate) => Some(framerate),
+            _ => self.data.target_framerate
+        };
+        let index = match framerate {
+            Some(framerate) if self.data.target_framerate != Some(framerate) => {
+                let events = &self.data.events;
+                &*self.frame_indices.entry(framerate).or_insert_with(|| EventIndex::with_framerate(events, Some(&framerate)) )
+            }
+            _ => &self.index
+        };
+        // Find matches of render trigger
+        let event_indices = match trigger {
+            RenderTrigger::Id(render_id) => index.by_id(render_id).to_vec(),
+            RenderTrigger::Time(current_ms) => index.at_time(current_ms),
+            RenderTrigger::Frame(current_frame, framerate) => framerate.frame_to_ms(current_frame).map_or_else(Vec::new, |current_ms| index.at_time(current_ms) )
+        };
+        let current_ms = match trigger {
+            RenderTrigger::Id(_) => None,
+            RenderTrigger::Time(current_ms) => Some(current_ms),
+            RenderTrigger::Frame(current_frame, framerate) => framerate.frame_to_ms(current_frame)
+        };
+        for event_index in event_indices {
+            let event = &self.data.events[event_index];
+            // Styled geometries, animated by time of time-triggered events
+            let styled_geometries = match (current_ms, event.trigger.time_range(framerate.as_ref())) {
+                (Some(current_ms), Some(event_time)) => StyleResolver::at_time(event, event_time, current_ms),
+                _ => StyleResolver::new(&event.objects)
+            }.collect::<Vec<_>>();
+            // Custom tags by painters (also animated ones)
+            for (style, geometry) in &styled_geometries {
+                if let Geometry::Custom {tag, progress} = geometry {
+                    if let Some(painter) = self.painters.0.get(tag.name()) {
+                        painter.paint(*tag, *progress, style, &mut img)?;
                     }
                 }
+            }
 
 
+            // TODO: whole rendering process
+            for row in img.plane_rows_mut(0).expect("One plane should always exist!") {
+                for sample in row {
+                    *sample = std::u8::MAX - *sample;
+                }
             }
+
+
         }
         // Return still valid image reference
         Ok(img)
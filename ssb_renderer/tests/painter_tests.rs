@@ -0,0 +1,54 @@
+mod painter_tests {
+    // Imports
+    use ssb_parser::{
+        tags::{CustomTag,TagRegistry},
+        RenderOptions,
+        Ssb,
+        SsbRender
+    };
+    use ssb_renderer::{
+        image::{ColorType,ImageView},
+        RenderTrigger,
+        SsbRenderer
+    };
+    use std::{
+        any::Any,
+        io::Cursor,
+        sync::{Arc,Mutex}
+    };
+
+
+    // Custom tag (value kept as it is)
+    #[derive(Debug)]
+    struct Glow(String);
+    impl CustomTag for Glow {
+        fn name(&self) -> &str {
+            "glow"
+        }
+        fn value(&self) -> Option<String> {
+            Some(self.0.clone())
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+
+    // Tester
+    #[test]
+    fn test_paint_custom_tags() {
+        let ssb = Ssb::default().parse_owned(Cursor::new("#EVENTS\n0-1.0|||[glow=1][animate=[size=40;glow=5]]Hi")).unwrap();
+        let mut registry = TagRegistry::new();
+        registry.register("glow", |value: Option<&str>| Ok::<Arc<dyn CustomTag>, _>(Arc::new(Glow(value.unwrap_or_default().to_owned()))) );
+        let mut renderer = SsbRenderer::new(SsbRender::from_with_options(ssb, &RenderOptions::new().registry(&registry)).unwrap().0);
+        let painted = Arc::new(Mutex::new(vec![]));
+        let painted_by_painter = painted.clone();
+        renderer.register_painter("glow", move |tag: &dyn CustomTag, progress: f64, style: &ssb_parser::style::StyleState, _: &mut ImageView<'_>| {
+            painted_by_painter.lock().unwrap().push((tag.value(), progress, style.size));
+            Ok(())
+        });
+        let mut data = vec![0u8; 2 * 2 * 3];
+        renderer.render(ImageView::new(2, 2, 2 * 3, ColorType::RGB24, vec![&mut data]).unwrap(), RenderTrigger::Time(250)).unwrap();
+        assert_eq!(*painted.lock().unwrap(), vec![(Some("1".to_owned()), 1.0, 30.0), (Some("5".to_owned()), 0.25, 32.5)]);
+    }
+}